@@ -32,22 +32,17 @@ command -v docker >/dev/null 2>&1 || {
   exit 2
 }
 
-regions=( ap-east-1 ap-northeast-1 ap-northeast-2 ap-south-1 ap-southeast-1 ap-southeast-2 ca-central-1 eu-central-1 eu-north-1 eu-west-1 eu-west-2 eu-west-3 me-south-1 sa-east-1 us-east-1 us-east-2 us-west-1 us-west-2 )
-
-for region in "${regions[@]}"
-do
-    docker run --rm \
+# Regions enabled for the account are discovered by nuker
+docker run --rm \
     -v ${config_file}:/home/nuker/nuker.toml \
     -e AWS_ACCESS_KEY_ID=${aws_access_key_id} \
     -e AWS_SECRET_ACCESS_KEY=${aws_secret_access_key} \
     ashrithr/nuker:${image_version} \
     --config /home/nuker/nuker.toml \
-    --region ${region} \
-    --exclude s3 \
+    --exclude s3_bucket \
     --no-dry-run \
     --force \
     -vvvv
-done
 
 if [[ $# -eq 5 ]]; then
   command -v mailx >/dev/null 2>&1 || {
@@ -1,70 +1,155 @@
-mod asg;
+mod cloudtrail;
 mod cloudwatch;
-mod ebs_snapshot;
-mod ebs_volume;
-mod ec2_address;
-mod ec2_eni;
-mod ec2_igw;
-mod ec2_instance;
-mod ec2_nat_gw;
-mod ec2_network_acl;
-mod ec2_peer_conn;
-mod ec2_rt;
-mod ec2_sg;
-mod ec2_subnet;
-mod ec2_vpc;
-mod ec2_vpc_endpoint;
-mod ec2_vpn_gw;
-mod ecs_cluster;
-mod eks_cluster;
-mod elb_alb;
-mod elb_nlb;
-mod emr_cluster;
-mod es_domain;
-mod glue_endpoint;
-mod rds_cluster;
-mod rds_instance;
-mod rs_cluster;
-mod s3_bucket;
-mod sagemaker_notebook;
+mod regions;
 mod sts;
 
-pub use cloudwatch::CwClient;
+/// Declares the modules of the resource types built into the Nuker, every
+/// module provides the `CLIENT` identifying its resource type and its
+/// `resource_type()`. The resource types are available as associated
+/// constants of `Client` and are registered with the `registry` on first use.
+macro_rules! resource_types {
+    ($($module:ident => $name:ident,)*) => {
+        $(mod $module;)*
+
+        #[allow(non_upper_case_globals)]
+        impl Client {
+            $(pub const $name: Client = $module::CLIENT;)*
+        }
+
+        pub(crate) fn resource_types() -> Vec<ResourceType> {
+            vec![$($module::resource_type(),)*]
+        }
+    };
+}
+
+resource_types! {
+    asg => Asg,
+    cfn_stack => CfnStack,
+    cloudfront_distribution => CloudfrontDistribution,
+    dynamodb_table => DynamodbTable,
+    ebs_snapshot => EbsSnapshot,
+    ebs_volume => EbsVolume,
+    ec2_address => Ec2Address,
+    ec2_eni => Ec2Eni,
+    ec2_igw => Ec2Igw,
+    ec2_instance => Ec2Instance,
+    ec2_nat_gw => Ec2NatGW,
+    ec2_network_acl => Ec2NetworkACL,
+    ec2_peer_conn => Ec2PeeringConnection,
+    ec2_rt => Ec2RouteTable,
+    ec2_sg => Ec2Sg,
+    ec2_subnet => Ec2Subnet,
+    ec2_vpc => Ec2Vpc,
+    ec2_vpc_endpoint => Ec2VpcEndpoint,
+    ec2_vpn_gw => Ec2VpnGW,
+    ecs_cluster => EcsCluster,
+    eks_cluster => EksCluster,
+    elasticache_cluster => ElasticacheCluster,
+    elasticache_replication_group => ElasticacheReplicationGroup,
+    elasticache_subnet_group => ElasticacheSubnetGroup,
+    elb_alb => ElbAlb,
+    elb_nlb => ElbNlb,
+    emr_cluster => EmrCluster,
+    es_domain => EsDomain,
+    glue_endpoint => GlueEndpoint,
+    iam_access_key => IamAccessKey,
+    iam_instance_profile => IamInstanceProfile,
+    iam_role => IamRole,
+    iam_user => IamUser,
+    lambda_function => LambdaFunction,
+    rds_cluster => RdsCluster,
+    rds_instance => RdsInstance,
+    route53_hosted_zone => Route53HostedZone,
+    rs_cluster => RsCluster,
+    s3_bucket => S3Bucket,
+    sagemaker_app => SagemakerApp,
+    sagemaker_endpoint => SagemakerEndpoint,
+    sagemaker_endpoint_config => SagemakerEndpointConfig,
+    sagemaker_model => SagemakerModel,
+    sagemaker_notebook => SagemakerNotebook,
+    sagemaker_processing_job => SagemakerProcessingJob,
+    sagemaker_training_job => SagemakerTrainingJob,
+}
+
+pub(crate) use cloudwatch::check_template;
+pub use cloudwatch::{CwClient, MetricEvaluation};
+pub(crate) use regions::region_from_name;
+
+/// Scope of the global resources, matched by the region of the overrides
+pub(crate) const GLOBAL_SCOPE: &str = "global";
 
 use crate::Event;
 use crate::{
-    aws::{
-        asg::AsgClient, ebs_snapshot::EbsSnapshotClient, ebs_volume::EbsVolumeClient,
-        ec2_address::Ec2AddressClient, ec2_eni::Ec2EniClient, ec2_igw::Ec2IgwClient,
-        ec2_instance::Ec2InstanceClient, ec2_nat_gw::Ec2NatGWClient,
-        ec2_network_acl::Ec2NetworkAclClient, ec2_peer_conn::Ec2PeerConnClient,
-        ec2_rt::Ec2RtClient, ec2_sg::Ec2SgClient, ec2_subnet::Ec2SubnetClient,
-        ec2_vpc::Ec2VpcClient, ec2_vpc_endpoint::Ec2VpcEndpointClient, ec2_vpn_gw::Ec2VpnGWClient,
-        ecs_cluster::EcsClusterClient, eks_cluster::EksClusterClient, elb_alb::ElbAlbClient,
-        elb_nlb::ElbNlbClient, emr_cluster::EmrClusterClient, es_domain::EsDomainClient,
-        glue_endpoint::GlueEndpointClient, rds_cluster::RdsClusterClient,
-        rds_instance::RdsInstanceClient, rs_cluster::RsClusterClient, s3_bucket::S3BucketClient,
-        sagemaker_notebook::SagemakerNotebookClient, sts::StsService,
-    },
+    aws::{cloudtrail::CloudTrailService, regions::RegionService, sts::StsService},
     client::Client,
     client::NukerClient,
-    config::Config,
+    config::{Config, EndpointsConfig, MetricsConfig, RequiredTag, TagSource},
+    explain::Explanation,
     graph::{is_dag, Dag},
-    resource::EnforcementState,
+    hooks::{self, Hooks},
+    policy::PolicyEngine,
+    registry::{self, ResourceType},
+    resource::{EnforcementState, NTag, Resource},
     Error, Result,
 };
+use async_trait::async_trait;
 use rusoto_core::{Client as RClient, HttpClient, Region};
-use rusoto_credential::{ChainProvider, ProfileProvider};
+use rusoto_credential::{
+    AwsCredentials, ChainProvider, CredentialsError, ProfileProvider, ProvideAwsCredentials,
+    StaticProvider,
+};
+use rusoto_ec2::{CreateTagsRequest, DescribeTagsRequest, Ec2, Ec2Client, Filter, Tag};
+use std::result::Result as StdResult;
 use std::time::Duration;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tracing::trace;
+use tracing::{debug, error, info, trace, warn};
+
+const CW_SERVICE: &str = "monitoring";
+const STS_SERVICE: &str = "sts";
+const CLOUDTRAIL_SERVICE: &str = "cloudtrail";
 
 #[derive(Clone)]
 pub struct ClientDetails {
     account_number: String,
     client: RClient,
     pub region: Region,
+    /// Regions being scanned, global clients use these to determine which
+    /// resources are in scope
+    regions: Vec<Region>,
+    endpoints: EndpointsConfig,
+    /// Event history of the region, used to look up who created a resource
+    /// and when
+    cloudtrail: CloudTrailService,
+}
+
+impl ClientDetails {
+    /// HTTP client shared by the service clients
+    pub fn client(&self) -> &RClient {
+        &self.client
+    }
+
+    pub fn account_number(&self) -> &str {
+        &self.account_number
+    }
+
+    /// Regions being scanned
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub(crate) fn cloudtrail(&self) -> &CloudTrailService {
+        &self.cloudtrail
+    }
+
+    /// Client details with the region resolved to the custom endpoint of the
+    /// service, if one is configured
+    fn for_service(&self, service: &str) -> ClientDetails {
+        ClientDetails {
+            region: self.endpoints.region(service, &self.region),
+            ..self.clone()
+        }
+    }
 }
 
 /// AWS Nuker for nuking resources in AWS.
@@ -72,61 +157,165 @@ pub struct AwsNuker {
     pub client_details: ClientDetails,
     config: Config,
     clients: HashMap<Client, Box<dyn NukerClient>>,
+    /// Clients of the resource types that are not scanned, created on first
+    /// use
+    lazy_clients: HashMap<Client, Box<dyn NukerClient>>,
     cw_client: Arc<Box<CwClient>>,
     tx: Sender<Event>,
     rx: Receiver<Event>,
     dag: Dag,
+    hooks: Hooks,
+    policy: Option<PolicyEngine>,
     dry_run: bool,
 }
 
 impl AwsNuker {
+    /// Creates a nuker for the regional resources in `region`, global
+    /// resources are handled by a nuker created using `new_global`.
     pub async fn new(
-        profile: Option<String>,
+        credentials: Credentials,
         region: Region,
-        mut config: Config,
+        config: Config,
         excluded_clients: Vec<Client>,
         dry_run: bool,
+    ) -> Result<AwsNuker> {
+        let regions = vec![region.clone()];
+        let excluded_clients = Client::iter()
+            .filter(|c| c.is_global() || excluded_clients.contains(c))
+            .collect();
+
+        AwsNuker::with_regions(
+            credentials,
+            region.clone(),
+            regions,
+            region.name().to_string(),
+            config,
+            excluded_clients,
+            dry_run,
+        )
+        .await
+    }
+
+    /// Creates a nuker for the global (account scoped) resources, these are
+    /// scanned once using the endpoint in `region` and are in scope as long
+    /// as they belong to one of the `regions`.
+    pub async fn new_global(
+        credentials: Credentials,
+        region: Region,
+        regions: Vec<Region>,
+        config: Config,
+        excluded_clients: Vec<Client>,
+        dry_run: bool,
+    ) -> Result<AwsNuker> {
+        let excluded_clients = Client::iter()
+            .filter(|c| !c.is_global() || excluded_clients.contains(c))
+            .collect();
+
+        AwsNuker::with_regions(
+            credentials,
+            region,
+            regions,
+            GLOBAL_SCOPE.to_string(),
+            config,
+            excluded_clients,
+            dry_run,
+        )
+        .await
+    }
+
+    async fn with_regions(
+        credentials: Credentials,
+        region: Region,
+        regions: Vec<Region>,
+        scope: String,
+        config: Config,
+        mut excluded_clients: Vec<Client>,
+        dry_run: bool,
     ) -> Result<AwsNuker> {
         let mut hyper_builder = hyper::Client::builder();
         hyper_builder.pool_idle_timeout(Duration::from_secs(10));
         let http_client = HttpClient::from_builder(hyper_builder, hyper_tls::HttpsConnector::new());
-        let client = RClient::new_with(credentials_provider(&profile)?, http_client);
+        let client = RClient::new_with(credentials.provider()?, http_client);
         let mut clients: HashMap<Client, Box<dyn NukerClient>> = HashMap::new();
-        let sts_client = StsService::new(&client, &region)?;
+        let endpoints = config.endpoints.clone();
+        let sts_client = StsService::new(&client, &endpoints.region(STS_SERVICE, &region))?;
         let account_num = sts_client.get_account_number().await?;
-        let cw_client = create_cw_client(&profile, &region, &mut config, &account_num)?;
+        let mut config = config.for_scope(&scope, &account_num)?;
+        excluded_clients.extend(config.excluded().iter().copied());
+        let cw_client = create_cw_client(
+            &credentials,
+            &endpoints.region(CW_SERVICE, &region),
+            &mut config,
+            &account_num,
+        )?;
+        let cloudtrail =
+            CloudTrailService::new(&client, &endpoints.region(CLOUDTRAIL_SERVICE, &region))?;
 
         let client_details = ClientDetails {
             account_number: account_num,
             region: region.clone(),
+            regions,
+            endpoints,
             client,
+            cloudtrail,
         };
 
         for client in Client::iter() {
             if !excluded_clients.contains(&client) {
                 clients.insert(
                     client,
-                    create_client(&client, &client_details, &config, dry_run).clone(),
+                    create_client(&client, &client_details, &config, dry_run)?,
                 );
             }
         }
 
         let (tx, rx) = channel(100);
+        let policy = PolicyEngine::new(&config.policy);
 
         Ok(AwsNuker {
             client_details,
             config,
             clients,
+            lazy_clients: HashMap::new(),
             cw_client,
             tx,
             rx,
             dag: Dag::new(),
+            hooks: Hooks::default(),
+            policy,
             dry_run,
         })
     }
 
+    /// Hooks invoked around the scans and cleanups
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Client of the resource type, clients of the resource types that were
+    /// not scanned (dependencies, resources given to cleanup) are created on
+    /// first use.
+    fn client(&mut self, client_type: &Client) -> Result<Box<dyn NukerClient>> {
+        if let Some(client) = self.clients.get(client_type) {
+            return Ok(dyn_clone::clone_box(&**client));
+        }
+
+        if !self.lazy_clients.contains_key(client_type) {
+            let client = create_client(
+                client_type,
+                &self.client_details,
+                &self.config,
+                self.dry_run,
+            )?;
+            self.lazy_clients.insert(*client_type, client);
+        }
+
+        Ok(dyn_clone::clone_box(&*self.lazy_clients[client_type]))
+    }
+
     /// Locates resources across all clients for a particular region
-    pub async fn locate_resources(&mut self) {
+    async fn locate_resources(&mut self) {
         let mut handles = Vec::new();
 
         for (client_type, client) in &self.clients {
@@ -134,10 +323,16 @@ impl AwsNuker {
             let tx = self.tx.clone();
             let client_type = client_type.clone();
             let cw_client = self.cw_client.clone();
-            let config = self.config.get(&client_type).unwrap().clone();
+            let config = self.config.resource(&client_type);
+            let policy = self.policy.clone();
+            let hooks = self.hooks.clone();
+            let region = self.client_details.region.clone();
 
             handles.push(tokio::spawn(async move {
-                client.publish(tx, client_type, config, cw_client).await
+                hooks.pre_scan(client_type, &region).await;
+                client
+                    .publish(tx, client_type, config, cw_client, policy)
+                    .await
             }));
         }
 
@@ -147,11 +342,16 @@ impl AwsNuker {
     /// Builds a dependency graph of resources
     async fn build_dag(&mut self) -> Result<()> {
         let mut done: usize = 0;
+        let mut scanned: HashMap<Client, Vec<Resource>> = HashMap::new();
 
         while let Some(r) = self.rx.recv().await {
             match r {
                 Event::Resource(resource) => {
                     self.dag.add_node_to_dag(resource.clone());
+                    scanned
+                        .entry(resource.type_)
+                        .or_default()
+                        .push(resource.clone());
 
                     if resource.enforcement_state == EnforcementState::Delete
                         || resource.enforcement_state == EnforcementState::DeleteDependent
@@ -159,28 +359,18 @@ impl AwsNuker {
                         // FIXME: This is redundant
                         if let Some(deps) = resource.dependencies {
                             for mut dep in deps {
-                                if !self.clients.contains_key(&dep.type_) {
-                                    dep.dependencies = self
-                                        .clients
-                                        .entry(dep.type_)
-                                        // For handling clients required for dependent resources
-                                        .or_insert(create_client(
-                                            &resource.type_,
-                                            &self.client_details,
-                                            &self.config,
-                                            self.dry_run,
-                                        ))
-                                        .dependencies(&dep)
-                                        .await;
-
-                                    done = done + 1; // newly created client does not send Shutdown event
-                                } else {
-                                    dep.dependencies = self
-                                        .clients
-                                        .get(&dep.type_)
-                                        .unwrap()
-                                        .dependencies(&dep)
-                                        .await;
+                                match self.client(&dep.type_) {
+                                    Ok(client) => {
+                                        dep.dependencies = client.dependencies(&dep).await
+                                    }
+                                    Err(err) => {
+                                        error!(
+                                            resource = dep.id.as_str(),
+                                            error = ?err,
+                                            "Skipping the dependencies of the resource"
+                                        );
+                                        continue;
+                                    }
                                 }
 
                                 self.dag.add_node_to_dag(dep);
@@ -188,7 +378,11 @@ impl AwsNuker {
                         }
                     }
                 }
-                Event::Shutdown(_et) => {
+                Event::Shutdown(et) => {
+                    let resources = scanned.remove(&et).unwrap_or_default();
+                    self.hooks
+                        .post_scan(et, &self.client_details.region, &resources)
+                        .await;
                     done = done + 1;
 
                     if done == self.clients.keys().len() {
@@ -211,215 +405,566 @@ impl AwsNuker {
         Ok(())
     }
 
-    /// Prints resources to console
-    pub async fn print_resources(&mut self) -> Result<()> {
+    /// Scans for the resources and evaluates them against the rules, the
+    /// resources are returned ordered by their dependencies.
+    pub async fn scan(&mut self) -> Result<Vec<Resource>> {
+        self.dag = Dag::new();
+        self.locate_resources().await;
         self.build_dag().await?;
 
-        for r in self.dag.order_by_dependencies()? {
-            println!("{}", r);
+        self.dag.order_by_dependencies()
+    }
+
+    /// Cleans up the provided resources in the given order, resources are
+    /// stopped, removed or tagged based on their enforcement state. A
+    /// resource is left as is when the pre-delete hooks veto its cleanup,
+    /// resources failing to be cleaned up are reported and skipped.
+    pub async fn cleanup(&mut self, resources: &[Resource]) -> Result<()> {
+        for resource in resources {
+            let enforced = hooks::is_enforced(resource);
+
+            if enforced && !self.hooks.pre_delete(resource).await {
+                info!(
+                    resource = resource.id.as_str(),
+                    "Skipping cleanup, vetoed by the pre-delete hook"
+                );
+                continue;
+            }
+
+            let client = match self.client(&resource.type_) {
+                Ok(client) => client,
+                Err(err) => {
+                    error!(resource = resource.id.as_str(), error = ?err, "Failed cleaning up");
+                    continue;
+                }
+            };
+
+            let result = if resource.enforcement_state == EnforcementState::Tag {
+                let required_tags = self
+                    .config
+                    .resource(&resource.type_)
+                    .required_tags
+                    .unwrap_or_default();
+                let tags = remediation_tags(
+                    resource,
+                    &required_tags,
+                    client.as_ref(),
+                    &self.client_details.cloudtrail,
+                )
+                .await;
+
+                if tags.is_empty() {
+                    Ok(())
+                } else {
+                    client.tag(resource, &tags).await
+                }
+            } else {
+                client.cleanup(resource).await
+            };
+
+            if enforced {
+                self.hooks.post_delete(resource, &result).await;
+            }
+            // A failing resource does not prevent cleaning up the others
+            if let Err(err) = result {
+                error!(resource = resource.id.as_str(), error = ?err, "Failed cleaning up");
+            }
         }
+        trace!("Done cleaning up resources");
 
         Ok(())
     }
 
-    /// Cleans up resources for a particular region across all targeted clients
-    pub async fn cleanup_resources(&mut self) -> Result<()> {
-        for resource in self.dag.order_by_dependencies()? {
-            self.clients
-                .entry(resource.type_)
-                // For handling clients required for dependent resources
-                .or_insert(create_client(
-                    &resource.type_,
-                    &self.client_details,
-                    &self.config,
-                    self.dry_run,
-                ))
-                .cleanup(&resource)
-                .await?;
+    /// Explains the enforcement state of the provided resources by evaluating
+    /// every rule against them, nothing is stopped or removed.
+    pub async fn explain(&mut self, resources: &[Resource]) -> Vec<Explanation> {
+        let mut explanations = Vec::new();
+
+        for resource in resources {
+            let config = self.config.resource(&resource.type_);
+            let cw_client = self.cw_client.clone();
+
+            match self.client(&resource.type_) {
+                Ok(client) => explanations.push(
+                    client
+                        .explain(resource, &config, cw_client, self.policy.as_ref())
+                        .await,
+                ),
+                Err(err) => {
+                    error!(resource = resource.id.as_str(), error = ?err, "Failed explaining")
+                }
+            }
         }
-        trace!("Done cleaning up resources");
 
-        Ok(())
+        explanations
     }
 }
 
-fn credentials_provider(profile: &Option<String>) -> Result<ChainProvider> {
-    let profile_provider = match profile {
-        Some(profile) => {
-            let mut p = ProfileProvider::new()?;
-            p.set_profile(profile);
-            p
-        }
-        None => ProfileProvider::new()?,
-    };
+/// Discovers the regions enabled for the account using the default region of
+/// the environment.
+pub async fn discover_regions(
+    credentials: &Credentials,
+    endpoints: &EndpointsConfig,
+) -> Result<Vec<Region>> {
+    let client = RClient::new_with(credentials.provider()?, HttpClient::new()?);
+    let region = endpoints.region(Client::Ec2Instance.service(), &Region::default());
+
+    RegionService::new(&client, &region)?
+        .get_enabled_regions()
+        .await
+}
 
-    let mut provider = ChainProvider::with_profile_provider(profile_provider);
-    provider.set_timeout(Duration::from_millis(250));
-    Ok(provider)
+/// Credentials used for accessing AWS
+#[derive(Debug, Clone, Default)]
+pub enum Credentials {
+    /// Credentials of the environment, the default profile or the instance
+    /// profile, in that order
+    #[default]
+    Default,
+    /// Credentials of a named profile
+    Profile(String),
+    Static {
+        access_key: String,
+        secret_key: String,
+        session_token: Option<String>,
+    },
 }
 
-fn create_client(rt: &Client, cd: &ClientDetails, c: &Config, dr: bool) -> Box<dyn NukerClient> {
-    match rt {
-        Client::Asg => {
-            Box::new(AsgClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EbsSnapshot => {
-            Box::new(EbsSnapshotClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EbsVolume => {
-            Box::new(EbsVolumeClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Address => {
-            Box::new(Ec2AddressClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Eni => {
-            Box::new(Ec2EniClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Igw => {
-            Box::new(Ec2IgwClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Instance => {
-            Box::new(Ec2InstanceClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2NatGW => {
-            Box::new(Ec2NatGWClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2NetworkACL => {
-            Box::new(Ec2NetworkAclClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2PeeringConnection => {
-            Box::new(Ec2PeerConnClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2RouteTable => {
-            Box::new(Ec2RtClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Sg => {
-            Box::new(Ec2SgClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Subnet => {
-            Box::new(Ec2SubnetClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2Vpc => {
-            Box::new(Ec2VpcClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2VpcEndpoint => {
-            Box::new(Ec2VpcEndpointClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::Ec2VpnGW => {
-            Box::new(Ec2VpnGWClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EcsCluster => {
-            Box::new(EcsClusterClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EksCluster => {
-            Box::new(EksClusterClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::ElbAlb => {
-            Box::new(ElbAlbClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::ElbNlb => {
-            Box::new(ElbNlbClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EmrCluster => {
-            Box::new(EmrClusterClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::EsDomain => {
-            Box::new(EsDomainClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::GlueEndpoint => {
-            Box::new(GlueEndpointClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::RdsCluster => {
-            Box::new(RdsClusterClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::RdsInstance => {
-            Box::new(RdsInstanceClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
-        }
-        Client::RsCluster => {
-            Box::new(RsClusterClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
+impl From<Option<String>> for Credentials {
+    fn from(profile: Option<String>) -> Self {
+        profile.map_or(Credentials::Default, Credentials::Profile)
+    }
+}
+
+impl Credentials {
+    fn provider(&self) -> Result<CredentialsProvider> {
+        let profile = match self {
+            Credentials::Static {
+                access_key,
+                secret_key,
+                session_token,
+            } => {
+                return Ok(CredentialsProvider::Static(StaticProvider::new(
+                    access_key.to_owned(),
+                    secret_key.to_owned(),
+                    session_token.to_owned(),
+                    None,
+                )))
+            }
+            Credentials::Profile(profile) => Some(profile),
+            Credentials::Default => None,
+        };
+
+        let mut profile_provider = ProfileProvider::new()?;
+        if let Some(profile) = profile {
+            profile_provider.set_profile(profile);
         }
-        Client::S3Bucket => {
-            Box::new(S3BucketClient::new(cd, c.get(&rt).unwrap(), dr)) as Box<dyn NukerClient>
+
+        let mut provider = ChainProvider::with_profile_provider(profile_provider);
+        provider.set_timeout(Duration::from_millis(250));
+        Ok(CredentialsProvider::Chain(provider))
+    }
+}
+
+enum CredentialsProvider {
+    Chain(ChainProvider),
+    Static(StaticProvider),
+}
+
+#[async_trait]
+impl ProvideAwsCredentials for CredentialsProvider {
+    async fn credentials(&self) -> StdResult<AwsCredentials, CredentialsError> {
+        match self {
+            CredentialsProvider::Chain(p) => p.credentials().await,
+            CredentialsProvider::Static(p) => p.credentials().await,
         }
-        Client::SagemakerNotebook => {
-            Box::new(SagemakerNotebookClient::new(cd, c.get(&rt).unwrap(), dr))
-                as Box<dyn NukerClient>
+    }
+}
+
+fn create_client(
+    rt: &Client,
+    cd: &ClientDetails,
+    c: &Config,
+    dr: bool,
+) -> Result<Box<dyn NukerClient>> {
+    let resource_type = registry::get(rt).ok_or_else(|| {
+        Error::Validation(format!("Resource type {} is not registered", rt.name()))
+    })?;
+    let cd = &cd.for_service(resource_type.service);
+
+    Ok((resource_type.builder)(cd, &c.resource(rt), dr))
+}
+
+/// Tags remediating the required tags violated by the resource, the value of a
+/// tag is inferred from its source when configured, falling back to its
+/// default value. Tags without a value are left for manual remediation.
+async fn remediation_tags(
+    resource: &Resource,
+    required_tags: &[RequiredTag],
+    client: &dyn NukerClient,
+    cloudtrail: &CloudTrailService,
+) -> Vec<NTag> {
+    let tags = resource.tags.clone().unwrap_or_default();
+    let create_event = registry::get(&resource.type_).and_then(|rt| rt.create_event);
+    let mut remediation = Vec::new();
+
+    for rt in required_tags.iter().filter(|rt| rt.is_violated(&tags)) {
+        let name = match rt.names().next() {
+            Some(name) => name,
+            None => continue,
+        };
+        let inferred = match rt.infer {
+            Some(TagSource::Creator) => match create_event {
+                Some(event_name) => match cloudtrail.creator(&resource.id, event_name).await {
+                    Ok(creator) => creator,
+                    Err(err) => {
+                        warn!(resource = resource.id.as_str(), error = ?err, "Failed looking up the creator");
+                        None
+                    }
+                },
+                None => None,
+            },
+            Some(TagSource::Parent) => client
+                .parent_tags(resource)
+                .await
+                .and_then(|parent| rt.find(&parent).map(|(_, value)| value.to_string())),
+            None => None,
+        };
+
+        match inferred
+            .filter(|value| rt.allows(value))
+            .or_else(|| rt.default.clone())
+        {
+            Some(value) => remediation.push(NTag {
+                key: Some(name.to_string()),
+                value: Some(value),
+            }),
+            None => warn!(
+                resource = resource.id.as_str(),
+                tag = name,
+                "No value to remediate the tag"
+            ),
         }
-        Client::DefaultClient => {
-            panic!("Default client cannot be initiated");
+    }
+
+    remediation
+}
+
+/// Applies the tags to an EC2 resource
+async fn create_ec2_tags(
+    client: &Ec2Client,
+    resource: &Resource,
+    tags: &[NTag],
+    dry_run: bool,
+) -> Result<()> {
+    debug!(resource = resource.id.as_str(), "Tagging");
+
+    if !dry_run {
+        client
+            .create_tags(CreateTagsRequest {
+                resources: vec![resource.id.clone()],
+                tags: tags
+                    .iter()
+                    .map(|tag| Tag {
+                        key: tag.key.clone(),
+                        value: tag.value.clone(),
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Tags of the EC2 resource identified by its id
+async fn describe_ec2_tags(client: &Ec2Client, id: &str) -> Option<Vec<NTag>> {
+    let result = client
+        .describe_tags(DescribeTagsRequest {
+            filters: Some(vec![Filter {
+                name: Some("resource-id".to_string()),
+                values: Some(vec![id.to_string()]),
+            }]),
+            ..Default::default()
+        })
+        .await;
+
+    match result {
+        Ok(result) => Some(
+            result
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| NTag {
+                    key: tag.key,
+                    value: tag.value,
+                })
+                .collect(),
+        ),
+        Err(err) => {
+            warn!(resource = id, error = ?err, "Failed describing the tags");
+            None
         }
     }
 }
 
 fn create_cw_client(
-    profile: &Option<String>,
+    credentials: &Credentials,
     region: &Region,
     config: &mut Config,
     account_num: &String,
 ) -> Result<Arc<Box<CwClient>>> {
     let cw_client: rusoto_cloudwatch::CloudWatchClient =
         rusoto_cloudwatch::CloudWatchClient::new_with_client(
-            RClient::new_with(credentials_provider(profile)?, HttpClient::new()?),
+            RClient::new_with(credentials.provider()?, HttpClient::new()?),
             region.to_owned(),
         );
 
     Ok(Arc::new(Box::new(CwClient {
         client: cw_client,
         account_num: account_num.into(),
-        ec2_metric_filters: config
-            .get_mut(&Client::Ec2Instance)
-            .unwrap()
-            .metric_filters
-            .take(),
-        ebs_metric_filters: config
-            .get_mut(&Client::EbsVolume)
-            .unwrap()
-            .metric_filters
-            .take(),
-        elb_alb_metric_filters: config
-            .get_mut(&Client::ElbAlb)
-            .unwrap()
-            .metric_filters
-            .take(),
-        elb_nlb_metric_filters: config
-            .get_mut(&Client::ElbNlb)
-            .unwrap()
-            .metric_filters
-            .take(),
-        rds_metric_filters: config
-            .get_mut(&Client::RdsInstance)
-            .unwrap()
-            .metric_filters
-            .take(),
-        aurora_metric_filters: config
-            .get_mut(&Client::RdsCluster)
-            .unwrap()
-            .metric_filters
-            .take(),
-        redshift_metric_filters: config
-            .get_mut(&Client::RsCluster)
-            .unwrap()
-            .metric_filters
-            .take(),
-        emr_metric_filters: config
-            .get_mut(&Client::EmrCluster)
-            .unwrap()
-            .metric_filters
-            .take(),
-        es_metric_filters: config
-            .get_mut(&Client::EsDomain)
-            .unwrap()
-            .metric_filters
-            .take(),
-        ecs_metric_filters: config
-            .get_mut(&Client::EcsCluster)
-            .unwrap()
-            .metric_filters
-            .take(),
-        eks_metric_filters: config
-            .get_mut(&Client::EksCluster)
-            .unwrap()
-            .metric_filters
-            .take(),
+        metric_filters: Client::iter()
+            .filter_map(|c| {
+                let filters = config.get_mut(&c)?.metric_filters.take()?;
+                Some((c, filters))
+            })
+            .collect(),
+        metrics: Client::iter()
+            .filter_map(|c| {
+                let metrics = config
+                    .get(&c)
+                    .and_then(|rc| rc.metrics.clone())
+                    .or_else(|| registry::get(&c)?.metrics.map(MetricsConfig::from))?;
+                Some((c, metrics))
+            })
+            .collect(),
+        idle_match: Client::iter()
+            .filter_map(|c| Some((c, config.get(&c)?.idle_match)))
+            .collect(),
+        trace: None,
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_cloudtrail::CloudTrailClient;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    /// Client of a volume attached to an instance tagged `CostCenter`
+    #[derive(Clone)]
+    struct VolumeClient;
+
+    #[async_trait]
+    impl NukerClient for VolumeClient {
+        async fn scan(&self) -> Result<Vec<Resource>> {
+            Ok(Vec::new())
+        }
+
+        async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+            None
+        }
+
+        async fn additional_filters(
+            &self,
+            _resource: &Resource,
+            _config: &crate::config::ResourceConfig,
+        ) -> Option<bool> {
+            None
+        }
+
+        async fn stop(&self, _resource: &Resource) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete(&self, _resource: &Resource) -> Result<()> {
+            Ok(())
+        }
+
+        async fn parent_tags(&self, _resource: &Resource) -> Option<Vec<NTag>> {
+            Some(vec![NTag {
+                key: Some("CostCenter".to_string()),
+                value: Some("42".to_string()),
+            }])
+        }
+    }
+
+    /// Client publishing a volume to delete, which depends on a resource of a
+    /// type that is not registered
+    #[derive(Clone)]
+    struct DependentClient;
+
+    #[async_trait]
+    impl NukerClient for DependentClient {
+        async fn scan(&self) -> Result<Vec<Resource>> {
+            Ok(Vec::new())
+        }
+
+        async fn publish(
+            &self,
+            tx: Sender<Event>,
+            c: Client,
+            _config: crate::config::ResourceConfig,
+            _cw_client: Arc<Box<CwClient>>,
+            _policy: Option<PolicyEngine>,
+        ) {
+            let dependency = Resource {
+                id: "unknown-1234".to_string(),
+                type_: Client::new("unregistered"),
+                ..Default::default()
+            };
+            let resource = Resource {
+                id: "vol-1234".to_string(),
+                type_: c,
+                enforcement_state: EnforcementState::Delete,
+                dependencies: Some(vec![dependency]),
+                ..Default::default()
+            };
+
+            tx.send(Event::Resource(resource)).await.unwrap();
+            tx.send(Event::Shutdown(c)).await.unwrap();
+        }
+
+        async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+            None
+        }
+
+        async fn additional_filters(
+            &self,
+            _resource: &Resource,
+            _config: &crate::config::ResourceConfig,
+        ) -> Option<bool> {
+            None
+        }
+
+        async fn stop(&self, _resource: &Resource) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete(&self, _resource: &Resource) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn scan_past_unregistered_dependencies() {
+        let mut config = crate::config::parse_config("").unwrap();
+        let credentials = Credentials::Static {
+            access_key: "access".to_string(),
+            secret_key: "secret".to_string(),
+            session_token: None,
+        };
+        let cw_client = create_cw_client(
+            &credentials,
+            &Region::UsEast1,
+            &mut config,
+            &"123456789012".into(),
+        )
+        .unwrap();
+        let client = RClient::new_with(MockCredentialsProvider, MockRequestDispatcher::default());
+        let (tx, rx) = channel(100);
+        let mut clients: HashMap<Client, Box<dyn NukerClient>> = HashMap::new();
+        clients.insert(Client::EbsVolume, Box::new(DependentClient));
+        let mut nuker = AwsNuker {
+            client_details: ClientDetails {
+                account_number: "123456789012".to_string(),
+                client: client.clone(),
+                region: Region::UsEast1,
+                regions: vec![Region::UsEast1],
+                endpoints: config.endpoints.clone(),
+                cloudtrail: CloudTrailService {
+                    client: CloudTrailClient::new_with_client(client, Region::UsEast1),
+                },
+            },
+            config,
+            clients,
+            lazy_clients: HashMap::new(),
+            cw_client,
+            tx,
+            rx,
+            dag: Dag::new(),
+            hooks: Hooks::default(),
+            policy: None,
+            dry_run: true,
+        };
+
+        // Clients created for the cleanup are not scanned
+        nuker.client(&Client::Ec2Instance).unwrap();
+
+        for _ in 0..2 {
+            let resources = nuker.scan().await.unwrap();
+            let ids: Vec<&str> = resources.iter().map(|r| r.id.as_str()).collect();
+
+            assert_eq!(ids, vec!["unknown-1234", "vol-1234"]);
+        }
+        assert_eq!(nuker.clients.len(), 1);
+        assert!(nuker.lazy_clients.contains_key(&Client::Ec2Instance));
+    }
+
+    #[tokio::test]
+    async fn infer_remediation_tags() {
+        // Events are returned from the newest to the oldest, the creator is
+        // the user of the event creating the volume
+        let cloudtrail = CloudTrailService {
+            client: CloudTrailClient::new_with(
+                MockRequestDispatcher::default().with_body(
+                    r#"{"Events": [
+                        {"EventName": "AttachVolume", "Username": "bob"},
+                        {"EventName": "CreateVolume", "Username": "alice"}
+                    ]}"#,
+                ),
+                MockCredentialsProvider,
+                Default::default(),
+            ),
+        };
+        let required_tags: Vec<RequiredTag> = toml::from_str::<toml::Value>(
+            r#"
+[[tags]]
+name = "Owner"
+infer = "creator"
+default = "unknown"
+
+[[tags]]
+name = "CostCenter"
+infer = "parent"
+
+[[tags]]
+name = "Team"
+default = "platform"
+
+[[tags]]
+name = "Name"
+"#,
+        )
+        .unwrap()["tags"]
+            .clone()
+            .try_into()
+            .unwrap();
+        let resource = Resource {
+            id: "vol-1234".to_string(),
+            type_: Client::EbsVolume,
+            tags: Some(vec![NTag {
+                key: Some("Name".to_string()),
+                value: Some("data".to_string()),
+            }]),
+            ..Default::default()
+        };
+
+        let tags: Vec<(Option<String>, Option<String>)> =
+            remediation_tags(&resource, &required_tags, &VolumeClient, &cloudtrail)
+                .await
+                .into_iter()
+                .map(|tag| (tag.key, tag.value))
+                .collect();
+
+        assert_eq!(
+            tags,
+            vec![
+                (Some("Owner".to_string()), Some("alice".to_string())),
+                (Some("CostCenter".to_string()), Some("42".to_string())),
+                (Some("Team".to_string()), Some("platform".to_string())),
+            ]
+        );
+    }
+}
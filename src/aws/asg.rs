@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -13,6 +14,8 @@ use rusoto_core::Region;
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("asg");
+
 #[derive(Clone)]
 pub struct AsgClient {
     pub client: AutoscalingClient,
@@ -45,6 +48,7 @@ impl AsgClient {
                 tags: self.package_tags(asg.tags),
                 state: Some(ResourceState::from_str(asg.status.as_ref().unwrap()).unwrap()),
                 start_time: Some(asg.created_time),
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -158,3 +162,17 @@ impl NukerClient for AsgClient {
         self.delete_asg(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "autoscaling",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(AsgClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
@@ -0,0 +1,350 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_cloudformation::{
+    CloudFormation, CloudFormationClient, DeleteStackInput, DescribeStacksInput, Stack, Tag,
+    UpdateTerminationProtectionInput,
+};
+use rusoto_core::Region;
+use tracing::{debug, trace};
+
+const DELETE_FAILED_STATUS: &str = "DELETE_FAILED";
+
+pub(crate) const CLIENT: Client = Client::new("cfn_stack");
+
+#[derive(Clone)]
+pub struct CfnStackClient {
+    client: CloudFormationClient,
+    region: Region,
+    config: ResourceConfig,
+    dry_run: bool,
+}
+
+impl CfnStackClient {
+    pub fn new(cd: &ClientDetails, config: &ResourceConfig, dry_run: bool) -> Self {
+        CfnStackClient {
+            client: CloudFormationClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            config: config.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(&self, stacks: Vec<Stack>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for stack in stacks {
+            // Nested stacks are removed along with their root stack
+            if stack.parent_id.is_some() {
+                continue;
+            }
+
+            let mut termination_protection: Option<bool> = None;
+
+            if let Some(ref tp) = self.config.termination_protection {
+                if tp.ignore {
+                    termination_protection = stack.enable_termination_protection;
+                }
+            }
+
+            resources.push(Resource {
+                id: stack.stack_name,
+                arn: stack.stack_id,
+                type_: ClientType::CfnStack,
+                region: self.region.clone(),
+                tags: self.package_tags(stack.tags),
+                state: Some(stack_state(stack.stack_status.as_str())),
+                start_time: Some(stack.creation_time),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: None,
+                dependencies: None,
+                termination_protection,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_stacks(&self, stack_name: Option<String>) -> Result<Vec<Stack>> {
+        let mut next_token: Option<String> = None;
+        let mut stacks: Vec<Stack> = Vec::new();
+
+        loop {
+            let req = self.client.describe_stacks(DescribeStacksInput {
+                next_token,
+                stack_name: stack_name.clone(),
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                if let Some(ss) = result.stacks {
+                    for s in ss {
+                        stacks.push(s);
+                    }
+                }
+
+                if result.next_token.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_token;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(stacks)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key.clone()),
+                    value: Some(tag.value.clone()),
+                })
+                .collect()
+        })
+    }
+
+    /// Logical resources to retain are only accepted by CloudFormation for
+    /// stacks that previously failed to delete.
+    async fn get_retain_resources(&self, stack_name: &str) -> Option<Vec<String>> {
+        if let Some(ref retain_resources) = self.config.retain_resources {
+            let stacks = self
+                .get_stacks(Some(stack_name.to_owned()))
+                .await
+                .unwrap_or_default();
+
+            if stacks
+                .iter()
+                .any(|s| s.stack_status.as_str() == DELETE_FAILED_STATUS)
+            {
+                return Some(retain_resources.clone());
+            }
+        }
+
+        None
+    }
+
+    async fn disable_termination_protection(&self, stack_name: &str) -> Result<()> {
+        debug!(
+            "Termination protection is enabled for: {}. Trying to disable it.",
+            stack_name
+        );
+
+        let req = self
+            .client
+            .update_termination_protection(UpdateTerminationProtectionInput {
+                enable_termination_protection: false,
+                stack_name: stack_name.to_owned(),
+            });
+
+        handle_future!(req);
+
+        Ok(())
+    }
+
+    async fn delete_stack(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            if let Some(tp_enabled) = resource.termination_protection {
+                if tp_enabled {
+                    self.disable_termination_protection(resource.id.as_ref())
+                        .await?;
+                }
+            }
+
+            let retain_resources = self.get_retain_resources(resource.id.as_ref()).await;
+
+            let req = self.client.delete_stack(DeleteStackInput {
+                stack_name: resource.id.to_owned(),
+                retain_resources,
+                ..Default::default()
+            });
+
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+/// Maps a CloudFormation stack status onto a `ResourceState`. Stacks that
+/// failed to create, update or delete and stacks that were rolled back are
+/// marked as `Failed`.
+fn stack_state(status: &str) -> ResourceState {
+    match status {
+        "CREATE_COMPLETE"
+        | "UPDATE_COMPLETE"
+        | "IMPORT_COMPLETE"
+        | "UPDATE_ROLLBACK_COMPLETE"
+        | "IMPORT_ROLLBACK_COMPLETE" => ResourceState::Running,
+        "CREATE_FAILED"
+        | "ROLLBACK_COMPLETE"
+        | "ROLLBACK_FAILED"
+        | "DELETE_FAILED"
+        | "UPDATE_ROLLBACK_FAILED"
+        | "IMPORT_ROLLBACK_FAILED" => ResourceState::Failed,
+        "DELETE_IN_PROGRESS" | "DELETE_COMPLETE" => ResourceState::Deleted,
+        s if s.ends_with("_IN_PROGRESS") => ResourceState::Pending,
+        _ => ResourceState::Unknown,
+    }
+}
+
+#[async_trait]
+impl NukerClient for CfnStackClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized CloudFormation stack resource scanner");
+        let stacks = self.get_stacks(None).await?;
+
+        self.package_resources(stacks).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    /// Failed stacks are still enforced, they are removed by the additional
+    /// rules.
+    fn filter_by_state(&self, resource: &Resource) -> bool {
+        !matches!(
+            resource.state,
+            Some(ResourceState::Running) | Some(ResourceState::Failed) | None
+        )
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        if resource.state == Some(ResourceState::Failed) {
+            debug!(
+                resource = resource.id.as_str(),
+                "Stack is in a failed or rolled back state."
+            );
+            return Some(true);
+        }
+
+        Some(false)
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_stack(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "cloudformation",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(CfnStackClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    fn create_client(dispatcher: MockRequestDispatcher, config: ResourceConfig) -> CfnStackClient {
+        CfnStackClient {
+            client: CloudFormationClient::new_with(
+                dispatcher,
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            config,
+            dry_run: false,
+        }
+    }
+
+    fn describe_stacks(status: &str) -> MockRequestDispatcher {
+        MockRequestDispatcher::default().with_body(&format!(
+            r#"<DescribeStacksResponse><DescribeStacksResult><Stacks><member>
+                <StackName>app</StackName>
+                <StackStatus>{}</StackStatus>
+                <CreationTime>2021-03-01T10:00:00Z</CreationTime>
+            </member></Stacks></DescribeStacksResult>
+            <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+            </DescribeStacksResponse>"#,
+            status
+        ))
+    }
+
+    #[test]
+    fn map_stack_status() {
+        assert_eq!(stack_state("CREATE_COMPLETE"), ResourceState::Running);
+        assert_eq!(stack_state("UPDATE_ROLLBACK_COMPLETE"), ResourceState::Running);
+        assert_eq!(stack_state("ROLLBACK_COMPLETE"), ResourceState::Failed);
+        assert_eq!(stack_state("DELETE_FAILED"), ResourceState::Failed);
+        assert_eq!(stack_state("DELETE_IN_PROGRESS"), ResourceState::Deleted);
+        assert_eq!(stack_state("UPDATE_IN_PROGRESS"), ResourceState::Pending);
+        assert_eq!(stack_state("REVIEW"), ResourceState::Unknown);
+    }
+
+    #[tokio::test]
+    async fn skip_nested_stacks() {
+        let client = create_client(MockRequestDispatcher::default(), ResourceConfig::default());
+        let stacks = vec![
+            Stack {
+                stack_name: "app".to_string(),
+                stack_status: "ROLLBACK_COMPLETE".to_string(),
+                creation_time: "2021-03-01T10:00:00Z".to_string(),
+                ..Default::default()
+            },
+            Stack {
+                stack_name: "app-network".to_string(),
+                stack_status: "CREATE_COMPLETE".to_string(),
+                parent_id: Some("arn:aws:cloudformation:us-east-1:123456789012:stack/app".into()),
+                ..Default::default()
+            },
+        ];
+
+        let resources = client.package_resources(stacks).await.unwrap();
+
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].id, "app");
+        assert_eq!(resources[0].state, Some(ResourceState::Failed));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00Z")
+        );
+    }
+
+    #[tokio::test]
+    async fn retain_resources_after_failed_deletion() {
+        let config = ResourceConfig {
+            retain_resources: Some(vec!["Bucket".to_string()]),
+            ..Default::default()
+        };
+
+        let client = create_client(describe_stacks("DELETE_FAILED"), config.clone());
+        assert_eq!(
+            client.get_retain_resources("app").await,
+            Some(vec!["Bucket".to_string()])
+        );
+
+        let client = create_client(describe_stacks("CREATE_COMPLETE"), config);
+        assert_eq!(client.get_retain_resources("app").await, None);
+    }
+}
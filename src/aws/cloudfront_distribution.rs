@@ -0,0 +1,260 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_cloudfront::{
+    CloudFront, CloudFrontClient, DeleteDistributionRequest, DistributionSummary,
+    GetDistributionConfigRequest, ListDistributionsRequest, ListTagsForResourceRequest, Tag,
+    UpdateDistributionRequest,
+};
+use rusoto_core::Region;
+use tracing::{debug, trace};
+
+const DEPLOYED_STATUS: &str = "Deployed";
+
+pub(crate) const CLIENT: Client = Client::new("cloudfront_distribution");
+
+#[derive(Clone)]
+pub struct CloudfrontDistributionClient {
+    client: CloudFrontClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl CloudfrontDistributionClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        CloudfrontDistributionClient {
+            client: CloudFrontClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_distributions(&self) -> Result<Vec<DistributionSummary>> {
+        let mut next_token: Option<String> = None;
+        let mut distributions: Vec<DistributionSummary> = Vec::new();
+
+        loop {
+            let req = self.client.list_distributions(ListDistributionsRequest {
+                marker: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                let list = match result.distribution_list {
+                    Some(list) => list,
+                    None => break,
+                };
+
+                for d in list.items.unwrap_or_default() {
+                    distributions.push(d);
+                }
+
+                if !list.is_truncated || list.next_marker.is_none() {
+                    break;
+                } else {
+                    next_token = list.next_marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(distributions)
+    }
+
+    async fn package_resources(
+        &self,
+        distributions: Vec<DistributionSummary>,
+    ) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for distribution in distributions {
+            let tags = self.get_tags(&distribution.arn).await;
+
+            resources.push(Resource {
+                id: distribution.id,
+                arn: Some(distribution.arn),
+                type_: ClientType::CloudfrontDistribution,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(distribution_state(
+                    distribution.status.as_str(),
+                    distribution.enabled,
+                )),
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: Some(vec![distribution.price_class]),
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self
+            .client
+            .list_tags_for_resource(ListTagsForResourceRequest {
+                resource: arn.to_string(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.tags.items)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key),
+                    value: tag.value,
+                })
+                .collect()
+        })
+    }
+
+    /// Disabling a distribution takes a while to propagate, the distribution
+    /// can only be deleted once it is disabled and deployed.
+    async fn disable_distribution(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Disabling");
+
+        if !self.dry_run {
+            let req = self
+                .client
+                .get_distribution_config(GetDistributionConfigRequest {
+                    id: resource.id.to_owned(),
+                });
+            let result = handle_future_with_return!(req)?;
+
+            if let Some(mut distribution_config) = result.distribution_config {
+                distribution_config.enabled = false;
+
+                let req = self.client.update_distribution(UpdateDistributionRequest {
+                    distribution_config,
+                    id: resource.id.to_owned(),
+                    if_match: result.e_tag,
+                });
+                handle_future!(req);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_distribution(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            // Deleting requires the ETag of the current configuration
+            let req = self
+                .client
+                .get_distribution_config(GetDistributionConfigRequest {
+                    id: resource.id.to_owned(),
+                });
+            let e_tag = handle_future_with_return!(req)?.e_tag;
+
+            let req = self.client.delete_distribution(DeleteDistributionRequest {
+                id: resource.id.to_owned(),
+                if_match: e_tag,
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+/// Enabled distributions are considered running, a distribution that is
+/// disabled and deployed is available for deletion.
+fn distribution_state(status: &str, enabled: bool) -> ResourceState {
+    if status != DEPLOYED_STATUS {
+        ResourceState::Pending
+    } else if enabled {
+        ResourceState::Running
+    } else {
+        ResourceState::Available
+    }
+}
+
+#[async_trait]
+impl NukerClient for CloudfrontDistributionClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized CloudFront distribution resource scanner");
+        let distributions = self.get_distributions().await?;
+
+        self.package_resources(distributions).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, resource: &Resource) -> Result<()> {
+        self.disable_distribution(resource).await
+    }
+
+    /// Enabled distributions are disabled first and are deleted by a later
+    /// run once the change is deployed.
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        if resource.state == Some(ResourceState::Running) {
+            self.disable_distribution(resource).await
+        } else {
+            self.delete_distribution(resource).await
+        }
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "cloudfront",
+        global: true,
+        builder: |cd, config, dry_run| {
+            Box::new(CloudfrontDistributionClient::new(cd, config, dry_run))
+        },
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_distribution_state() {
+        assert_eq!(
+            distribution_state(DEPLOYED_STATUS, true),
+            ResourceState::Running
+        );
+        assert_eq!(
+            distribution_state(DEPLOYED_STATUS, false),
+            ResourceState::Available
+        );
+        assert_eq!(distribution_state("InProgress", true), ResourceState::Pending);
+        assert_eq!(
+            distribution_state("InProgress", false),
+            ResourceState::Pending
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    config::ResourceConfig,
+    resource::{Creation, EVENT_HISTORY},
+    Error, Result,
+};
+use chrono::Utc;
+use rusoto_cloudtrail::{
+    CloudTrail, CloudTrailClient, Event, LookupAttribute, LookupEventsRequest, LookupEventsResponse,
+};
+use rusoto_core::{Client, Region};
+use std::{collections::HashSet, time::Duration};
+use tracing::warn;
+
+const RESOURCE_NAME_ATTRIBUTE: &str = "ResourceName";
+const EVENT_NAME_ATTRIBUTE: &str = "EventName";
+const MAX_RESULTS: i64 = 50;
+const MAX_ATTEMPTS: u32 = 3;
+const THROTTLING_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Creations recorded in the event history during a scan
+#[derive(Debug, Clone)]
+pub enum RecentCreations {
+    /// No minimum age is configured, the event history is not looked up
+    Skipped,
+    /// Ids of the resources created within the minimum age
+    Found(HashSet<String>),
+    /// The event history could not be looked up
+    Failed,
+}
+
+impl RecentCreations {
+    /// Creation of the resource, none when the event history is not looked up
+    pub fn creation(&self, resource_id: &str) -> Option<Creation> {
+        match self {
+            RecentCreations::Skipped => None,
+            RecentCreations::Found(ids) if ids.contains(resource_id) => Some(Creation::Recent),
+            RecentCreations::Found(_) => Some(Creation::NotRecorded),
+            RecentCreations::Failed => Some(Creation::Unknown),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CloudTrailService {
+    pub client: CloudTrailClient,
+}
+
+impl CloudTrailService {
+    pub fn new(client: &Client, region: &Region) -> Result<Self> {
+        Ok(CloudTrailService {
+            client: CloudTrailClient::new_with_client(client.clone(), region.clone()),
+        })
+    }
+
+    /// User that created the resource, identified as the user of the
+    /// `event_name` event (for instance `CreateVolume`) recorded for the
+    /// resource
+    pub async fn creator(&self, resource_id: &str, event_name: &str) -> Result<Option<String>> {
+        Ok(self
+            .creation_event(resource_id, event_name)
+            .await?
+            .and_then(|event| event.username))
+    }
+
+    /// Resources created within the minimum age, identified by the
+    /// `event_name` events (for instance `CreateVpc`) recorded since then.
+    /// The event history is looked up once per scan of a resource type whose
+    /// API does not return the creation time, and only when a minimum age is
+    /// configured.
+    pub async fn recent_creations(
+        &self,
+        event_name: &str,
+        config: &ResourceConfig,
+    ) -> RecentCreations {
+        let min_age = match config.min_age {
+            Some(min_age) => min_age.min(EVENT_HISTORY),
+            None => return RecentCreations::Skipped,
+        };
+        let since = Utc::now()
+            - chrono::Duration::from_std(min_age).unwrap_or_else(|_| chrono::Duration::zero());
+        let mut ids = HashSet::new();
+        let mut next_token = None;
+
+        loop {
+            let result = match self
+                .lookup_events(LookupEventsRequest {
+                    lookup_attributes: Some(vec![LookupAttribute {
+                        attribute_key: EVENT_NAME_ATTRIBUTE.to_string(),
+                        attribute_value: event_name.to_string(),
+                    }]),
+                    start_time: Some(since.timestamp() as f64),
+                    max_results: Some(MAX_RESULTS),
+                    next_token,
+                    ..Default::default()
+                })
+                .await
+            {
+                Ok(result) => result,
+                Err(Error::Throttling(msg)) => {
+                    warn!(
+                        event = event_name,
+                        "CloudTrail throttled the lookup of the creations, the resources are skipped: {}",
+                        msg
+                    );
+                    return RecentCreations::Failed;
+                }
+                Err(err) => {
+                    warn!(
+                        event = event_name,
+                        error = ?err,
+                        "Failed looking up the creations, the resources are skipped"
+                    );
+                    return RecentCreations::Failed;
+                }
+            };
+
+            for event in result.events.unwrap_or_default() {
+                ids.extend(
+                    event
+                        .resources
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|resource| resource.resource_name),
+                );
+            }
+
+            if result.next_token.is_none() {
+                return RecentCreations::Found(ids);
+            }
+            next_token = result.next_token;
+        }
+    }
+
+    /// Event creating the resource, the lookup is limited to the event
+    /// history of CloudTrail, which spans the last 90 days.
+    async fn creation_event(&self, resource_id: &str, event_name: &str) -> Result<Option<Event>> {
+        let mut next_token = None;
+
+        loop {
+            let result = self
+                .lookup_events(LookupEventsRequest {
+                    lookup_attributes: Some(vec![LookupAttribute {
+                        attribute_key: RESOURCE_NAME_ATTRIBUTE.to_string(),
+                        attribute_value: resource_id.to_string(),
+                    }]),
+                    max_results: Some(MAX_RESULTS),
+                    next_token,
+                    ..Default::default()
+                })
+                .await?;
+
+            if let Some(event) = result
+                .events
+                .unwrap_or_default()
+                .into_iter()
+                .find(|event| event.event_name.as_deref() == Some(event_name))
+            {
+                return Ok(Some(event));
+            }
+
+            if result.next_token.is_none() {
+                return Ok(None);
+            }
+            next_token = result.next_token;
+        }
+    }
+
+    /// Looks up a page of events, CloudTrail allows two lookups per second
+    /// so throttled lookups are retried after a backoff.
+    async fn lookup_events(&self, request: LookupEventsRequest) -> Result<LookupEventsResponse> {
+        let mut attempt = 1;
+
+        loop {
+            match self
+                .client
+                .lookup_events(request.clone())
+                .await
+                .map_err(Error::from)
+            {
+                Err(Error::Throttling(_)) if attempt < MAX_ATTEMPTS => {
+                    tokio::time::sleep(THROTTLING_BACKOFF * attempt).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    fn create_service(dispatcher: MockRequestDispatcher) -> CloudTrailService {
+        CloudTrailService {
+            client: CloudTrailClient::new_with(
+                dispatcher,
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn look_up_recent_creations() {
+        let service = create_service(
+            MockRequestDispatcher::default()
+                .with_body(
+                    r#"{"Events": [{
+                        "EventName": "CreateSecurityGroup",
+                        "Resources": [
+                            {"ResourceType": "AWS::EC2::SecurityGroup", "ResourceName": "sg-1234"},
+                            {"ResourceType": "AWS::EC2::VPC", "ResourceName": "vpc-1234"}
+                        ]
+                    }]}"#,
+                )
+                .with_request_checker(|request| match request.payload {
+                    Some(rusoto_core::signature::SignedRequestPayload::Buffer(ref body)) => {
+                        let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+
+                        assert_eq!(
+                            body["LookupAttributes"][0]["AttributeKey"],
+                            EVENT_NAME_ATTRIBUTE
+                        );
+                        assert_eq!(
+                            body["LookupAttributes"][0]["AttributeValue"],
+                            "CreateSecurityGroup"
+                        );
+                        assert!(body["StartTime"].is_number());
+                    }
+                    _ => panic!("missing body"),
+                }),
+        );
+        let config = ResourceConfig {
+            min_age: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+
+        let creations = service
+            .recent_creations("CreateSecurityGroup", &config)
+            .await;
+
+        assert_eq!(creations.creation("sg-1234"), Some(Creation::Recent));
+        assert_eq!(creations.creation("sg-5678"), Some(Creation::NotRecorded));
+        assert!(matches!(
+            service
+                .recent_creations("CreateSecurityGroup", &ResourceConfig::default())
+                .await,
+            RecentCreations::Skipped
+        ));
+    }
+
+    #[tokio::test]
+    async fn report_throttling() {
+        let service = create_service(
+            MockRequestDispatcher::with_status(400)
+                .with_body(r#"{"__type": "ThrottlingException", "message": "Rate exceeded"}"#),
+        );
+
+        let result = service
+            .client
+            .lookup_events(LookupEventsRequest::default())
+            .await
+            .map_err(Error::from);
+
+        assert!(matches!(result, Err(Error::Throttling(ref msg)) if msg == "Rate exceeded"));
+        assert_eq!(
+            RecentCreations::Failed.creation("sg-1234"),
+            Some(Creation::Unknown)
+        );
+    }
+}
@@ -1,34 +1,93 @@
 use crate::{
     client::ClientType,
-    config::{FilterOp, MetricFilter, MetricStatistic},
+    config::{FilterOp, IdleMatch, IdleState, MetricFilter, MetricStatistic, MetricsConfig},
     handle_future_with_return,
+    resource::Resource,
 };
 use chrono::{DateTime, TimeZone, Utc};
 use rusoto_cloudwatch::{
     CloudWatch, CloudWatchClient, Datapoint, Dimension, DimensionFilter, GetMetricStatisticsInput,
     GetMetricStatisticsOutput, ListMetricsInput,
 };
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tracing::{trace, warn};
 
+const TAG_PLACEHOLDER_PREFIX: &str = "tag:";
+
 #[derive(Clone)]
 pub struct CwClient {
     pub client: CloudWatchClient,
     pub account_num: String,
-    pub ec2_metric_filters: Option<Vec<MetricFilter>>,
-    pub ebs_metric_filters: Option<Vec<MetricFilter>>,
-    pub elb_alb_metric_filters: Option<Vec<MetricFilter>>,
-    pub elb_nlb_metric_filters: Option<Vec<MetricFilter>>,
-    pub rds_metric_filters: Option<Vec<MetricFilter>>,
-    pub aurora_metric_filters: Option<Vec<MetricFilter>>,
-    pub redshift_metric_filters: Option<Vec<MetricFilter>>,
-    pub emr_metric_filters: Option<Vec<MetricFilter>>,
-    pub es_metric_filters: Option<Vec<MetricFilter>>,
-    pub ecs_metric_filters: Option<Vec<MetricFilter>>,
-    pub eks_metric_filters: Option<Vec<MetricFilter>>,
+    /// Idle rules keyed by the resource type
+    pub metric_filters: HashMap<ClientType, Vec<MetricFilter>>,
+    /// Metrics the idle rules are evaluated against, keyed by the resource
+    /// type
+    pub metrics: HashMap<ClientType, MetricsConfig>,
+    /// How the idle rules are combined, keyed by the resource type
+    pub idle_match: HashMap<ClientType, IdleMatch>,
+    /// Records the evaluations of the idle rules when set
+    pub trace: Option<Arc<Mutex<Vec<MetricEvaluation>>>>,
+}
+
+/// Evaluation of an idle rule against the metrics of a resource
+#[derive(Debug, Clone)]
+pub struct MetricEvaluation {
+    pub resource_id: String,
+    pub metric_filter: MetricFilter,
+    pub dimensions: Vec<(String, String)>,
+    /// Values of the statistic for the retrieved datapoints, `None` when the
+    /// metric is not published for the resource
+    pub datapoints: Option<Vec<f64>>,
+    pub state: IdleState,
 }
 
 impl CwClient {
+    /// Client recording the evaluations of the idle rules, which are returned
+    /// by `take_trace`
+    pub fn traced(&self) -> CwClient {
+        CwClient {
+            trace: Some(Arc::new(Mutex::new(Vec::new()))),
+            ..self.clone()
+        }
+    }
+
+    pub fn take_trace(&self) -> Vec<MetricEvaluation> {
+        self.trace
+            .as_ref()
+            .map(|t| std::mem::take(&mut *t.lock().unwrap()))
+            .unwrap_or_default()
+    }
+
+    fn record(
+        &self,
+        resource_id: &str,
+        metric_filter: &MetricFilter,
+        dimensions: &[Dimension],
+        datapoints: Option<&[Datapoint]>,
+        state: IdleState,
+    ) {
+        if let Some(ref trace) = self.trace {
+            trace.lock().unwrap().push(MetricEvaluation {
+                resource_id: resource_id.to_string(),
+                metric_filter: metric_filter.clone(),
+                dimensions: dimensions
+                    .iter()
+                    .map(|d| (d.name.clone(), d.value.clone()))
+                    .collect(),
+                datapoints: datapoints.map(|dps| {
+                    dps.iter()
+                        .map(|dp| statistic_value(dp, metric_filter.statistic))
+                        .collect()
+                }),
+                state,
+            });
+        }
+    }
+
     async fn get_metric_statistics_maximum(
         &self,
         dimensions: Vec<Dimension>,
@@ -55,44 +114,29 @@ impl CwClient {
         handle_future_with_return!(req).ok()
     }
 
-    async fn filter_resource(
+    /// Checks the resource against the idle rules of its type, using the
+    /// CloudWatch namespace of the metrics of the resource type. Every rule
+    /// is evaluated and the states are combined based on the idle match of the
+    /// resource type.
+    pub async fn filter_resource(
         &self,
         resource_id: &str,
         default_dimensions: Vec<Dimension>,
         resource_type: ClientType,
     ) -> bool {
-        let mut result = false;
-        let metric_filters = match resource_type {
-            ClientType::Ec2Instance => &self.ec2_metric_filters,
-            ClientType::EbsVolume => &self.ebs_metric_filters,
-            ClientType::ElbAlb => &self.elb_alb_metric_filters,
-            ClientType::ElbNlb => &self.elb_nlb_metric_filters,
-            ClientType::RdsInstance => &self.rds_metric_filters,
-            ClientType::RdsCluster => &self.aurora_metric_filters,
-            ClientType::RsCluster => &self.redshift_metric_filters,
-            ClientType::EmrCluster => &self.emr_metric_filters,
-            ClientType::EsDomain => &self.es_metric_filters,
-            ClientType::EcsCluster => &self.ecs_metric_filters,
-            ClientType::EksCluster => &self.eks_metric_filters,
-            _ => &None,
-        };
-        let namespace = match resource_type {
-            ClientType::Ec2Instance => Some("AWS/EC2"),
-            ClientType::EbsVolume => Some("AWS/EBS"),
-            ClientType::ElbAlb => Some("AWS/ApplicationELB"),
-            ClientType::ElbNlb => Some("AWS/NetworkELB"),
-            ClientType::RdsInstance => Some("AWS/RDS"),
-            ClientType::RdsCluster => Some("AWS/RDS"),
-            ClientType::RsCluster => Some("AWS/Redshift"),
-            ClientType::EmrCluster => Some("AWS/ElasticMapReduce"),
-            ClientType::EsDomain => Some("AWS/ES"),
-            ClientType::EcsCluster => Some("AWS/ECS"),
-            ClientType::EksCluster => Some("AWS/EKS"),
-            _ => None,
-        };
-
-        if metric_filters.is_some() && namespace.is_some() {
-            for metric_filter in metric_filters.as_ref().unwrap() {
+        let mut states = Vec::new();
+        let metric_filters = self.metric_filters.get(&resource_type);
+        let idle_match = self
+            .idle_match
+            .get(&resource_type)
+            .copied()
+            .unwrap_or_default();
+        let metrics = self.metrics.get(&resource_type);
+        let namespace = metrics.map(|m| m.namespace.as_str());
+        let sparse_metrics = metrics.map(|m| m.sparse).unwrap_or(false);
+
+        if let (Some(metric_filters), Some(namespace)) = (metric_filters, namespace) {
+            for metric_filter in metric_filters {
                 trace!(metric_filter = ?metric_filter, "Checking resource against Idle Rules");
 
                 let mut dimensions: Vec<Dimension> = Vec::new();
@@ -109,218 +153,134 @@ impl CwClient {
                 dimensions.extend(default_dimensions.clone());
                 dimensions.extend(user_dimensions);
 
-                if self
+                let published = self
                     .is_valid_metric(
-                        namespace.unwrap(),
+                        namespace,
                         &self.to_dimension_filters(&dimensions[..])[..],
                         &metric_filter.name,
                     )
-                    .await
-                {
-                    let metrics = self
-                        .get_metric_statistics_maximum(
-                            dimensions,
-                            namespace.unwrap().to_string(),
-                            metric_filter.name.to_string(),
-                            metric_filter.duration,
-                            metric_filter.period,
-                            metric_filter.statistic,
-                        )
-                        .await
-                        .unwrap_or_default()
-                        .datapoints
-                        .unwrap_or_default();
-
-                    result = self.filter_metrics(
-                        &metrics,
-                        metric_filter.value as f64,
+                    .await;
+                let metrics = if published {
+                    self.get_metric_statistics_maximum(
+                        dimensions.clone(),
+                        namespace.to_string(),
+                        metric_filter.name.to_string(),
                         metric_filter.duration,
                         metric_filter.period,
-                        metric_filter.op,
                         metric_filter.statistic,
-                    );
+                    )
+                    .await
+                    .unwrap_or_default()
+                    .datapoints
+                    .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
 
+                let state = if metrics.is_empty() {
                     trace!(
                         resource = resource_id,
-                        result = result,
-                        "Idle Rules DataPoints: {:?}",
-                        metrics
+                        published = published,
+                        "No datapoints for resource."
                     );
+                    metric_filter
+                        .no_datapoints
+                        .unwrap_or_else(|| IdleState::from_idle(sparse_metrics))
+                } else if sparse_metrics {
+                    IdleState::from_idle(self.compare_metrics(
+                        &metrics,
+                        metric_filter.value as f64,
+                        metric_filter.op,
+                        metric_filter.statistic,
+                    ))
                 } else {
-                    warn!(resource = resource_id, metric_filter = ?metric_filter, "Invalid Metric.");
-                    result = false;
-                }
+                    IdleState::from_idle(self.filter_metrics(
+                        &metrics,
+                        metric_filter.value as f64,
+                        metric_filter.duration,
+                        metric_filter.period,
+                        metric_filter.op,
+                        metric_filter.statistic,
+                    ))
+                };
+
+                trace!(
+                    resource = resource_id,
+                    state = ?state,
+                    "Idle Rules DataPoints: {:?}",
+                    metrics
+                );
+                self.record(
+                    resource_id,
+                    metric_filter,
+                    &dimensions,
+                    if published { Some(&metrics) } else { None },
+                    state,
+                );
+                states.push(state);
             }
         }
 
-        result
-    }
-
-    pub async fn filter_instance(&self, instance_id: &str) -> bool {
-        self.filter_resource(
-            instance_id,
-            vec![Dimension {
-                name: "InstanceId".to_string(),
-                value: instance_id.to_string(),
-            }],
-            ClientType::Ec2Instance,
-        )
-        .await
+        idle_match.is_idle(&states)
     }
 
-    pub async fn filter_volume(&self, volume_id: &str) -> bool {
-        self.filter_resource(
-            volume_id,
-            vec![Dimension {
-                name: "VolumeId".to_string(),
-                value: volume_id.to_string(),
-            }],
-            ClientType::EbsVolume,
-        )
-        .await
-    }
-
-    pub async fn filter_db_instance(&self, instance_name: &str) -> bool {
-        self.filter_resource(
-            instance_name,
-            vec![Dimension {
-                name: "DBInstanceIdentifier".to_string(),
-                value: instance_name.to_string(),
-            }],
-            ClientType::RdsInstance,
-        )
-        .await
-    }
-
-    pub async fn filter_db_cluster(&self, cluster_identifier: &str) -> bool {
-        self.filter_resource(
-            cluster_identifier,
-            vec![Dimension {
-                name: "DBClusterIdentifier".to_string(),
-                value: cluster_identifier.to_string(),
-            }],
-            ClientType::RdsCluster,
-        )
-        .await
-    }
-
-    pub async fn filter_rs_cluster(&self, cluster_id: &str) -> bool {
-        self.filter_resource(
-            cluster_id,
-            vec![Dimension {
-                name: "ClusterIdentifier".to_string(),
-                value: cluster_id.to_string(),
-            }],
-            ClientType::RsCluster,
-        )
-        .await
-    }
-
-    pub async fn filter_es_domain(&self, domain_name: &str) -> bool {
-        self.filter_resource(
-            domain_name,
-            vec![
-                Dimension {
-                    name: "DomainName".to_string(),
-                    value: domain_name.to_string(),
-                },
-                Dimension {
-                    name: "ClientId".to_string(),
-                    value: self.account_num.to_string(),
-                },
-            ],
-            ClientType::EsDomain,
-        )
-        .await
-    }
+    /// Checks the resource against the idle rules of its type, the resource
+    /// is identified by the dimensions of the metrics of the resource type
+    pub async fn filter_idle(&self, resource: &Resource) -> bool {
+        let metrics = match self.metrics.get(&resource.type_) {
+            Some(metrics) => metrics,
+            None => return false,
+        };
+        let mut dimensions = Vec::new();
+
+        for (name, template) in &metrics.dimensions {
+            match render_template(template, resource, &self.account_num) {
+                Ok(value) => dimensions.push(Dimension {
+                    name: name.to_string(),
+                    value,
+                }),
+                Err(err) => {
+                    warn!(
+                        resource = resource.id.as_str(),
+                        dimension = name.as_str(),
+                        "Failed rendering the dimension: {}",
+                        err
+                    );
+                    return false;
+                }
+            }
+        }
 
-    pub async fn filter_alb_load_balancer(&self, lb_name: &str) -> bool {
-        self.filter_resource(
-            lb_name,
-            vec![Dimension {
-                name: "LoadBalancer".to_string(),
-                value: lb_name.to_string(),
-            }],
-            ClientType::ElbAlb,
-        )
-        .await
+        self.filter_resource(resource.id.as_str(), dimensions, resource.type_)
+            .await
     }
 
-    pub async fn filter_nlb_load_balancer(&self, lb_name: &str) -> bool {
-        self.filter_resource(
-            lb_name,
-            vec![Dimension {
-                name: "LoadBalancer".to_string(),
-                value: lb_name.to_string(),
-            }],
-            ClientType::ElbNlb,
-        )
-        .await
-    }
+    /// A replication group is idle only when every member cluster is idle.
+    /// The member clusters are in the region of the replication group.
+    pub async fn filter_elasticache_replication_group(
+        &self,
+        group: &Resource,
+        member_clusters: &[String],
+    ) -> bool {
+        if member_clusters.is_empty() {
+            return false;
+        }
 
-    pub async fn filter_ecs_cluster(&self, cluster_name: &str) -> bool {
-        self.filter_resource(
-            cluster_name,
-            vec![Dimension {
-                name: "ClusterName".to_string(),
-                value: cluster_name.to_string(),
-            }],
-            ClientType::EcsCluster,
-        )
-        .await
-    }
+        for cluster_id in member_clusters {
+            let cluster = Resource {
+                id: cluster_id.to_string(),
+                type_: ClientType::ElasticacheReplicationGroup,
+                region: group.region.clone(),
+                ..Default::default()
+            };
+            let idle = self.filter_idle(&cluster).await;
 
-    pub async fn filter_eks_cluster(&self, cluster_name: &str) -> bool {
-        self.filter_resource(
-            cluster_name,
-            vec![Dimension {
-                name: "ClusterName".to_string(),
-                value: cluster_name.to_string(),
-            }],
-            ClientType::EksCluster,
-        )
-        .await
-    }
+            if !idle {
+                return false;
+            }
+        }
 
-    pub async fn filter_emr_cluster(&self, cluster_id: &str) -> bool {
-        // FIXME: make this code generic
-        // let mut result = false;
-
-        // if self.emr_metric_filters.is_some() {
-        //     for metric_filter in self.emr_metric_filters.as_ref().unwrap() {
-        //         let metrics = self
-        //             .get_metric_statistics_maximum(
-        //                 vec![Dimension {
-        //                     name: "JobFlowId".to_string(),
-        //                     value: cluster_id.to_string(),
-        //                 }],
-        //                 metric_filter.namespace.to_string(),
-        //                 metric_filter.metric.to_string(),
-        //                 metric_filter.duration,
-        //                 metric_filter.granularity,
-        //             )
-        //             .await
-        //             .unwrap_or_default()
-        //             .datapoints
-        //             .unwrap_or_default();
-
-        //         result = metrics.iter().all(|m| {
-        //             m.maximum.unwrap_or_default()
-        //                 == metric_filter.minimum.unwrap_or_default() as f64
-        //         });
-        //     }
-        // }
-
-        // result
-        self.filter_resource(
-            cluster_id,
-            vec![Dimension {
-                name: "JobFlowId".to_string(),
-                value: cluster_id.to_string(),
-            }],
-            ClientType::EmrCluster,
-        )
-        .await
+        true
     }
 
     /// Validates if a given metric is valid for the provided namespace
@@ -338,12 +298,20 @@ impl CwClient {
                 namespace: Some(namespace.into()),
                 ..Default::default()
             })
-            .await
-            .map_err(|err| err.to_string())
-            .unwrap()
-            .metrics;
-
-        result.is_some() && result.unwrap().len() > 0
+            .await;
+
+        match result {
+            Ok(output) => output.metrics.is_some_and(|metrics| !metrics.is_empty()),
+            Err(err) => {
+                warn!(
+                    namespace = namespace,
+                    metric = metric_name,
+                    "Failed listing the metrics: {}",
+                    err
+                );
+                false
+            }
+        }
     }
 
     fn filter_metrics(
@@ -476,9 +444,94 @@ impl CwClient {
     }
 }
 
+/// Renders the template of a dimension for the resource, see
+/// [`MetricsConfig`] for the supported placeholders
+fn render_template(
+    template: &str,
+    resource: &Resource,
+    account_num: &str,
+) -> Result<String, String> {
+    expand_template(template, |placeholder| match placeholder {
+        "id" => Ok(resource.id.clone()),
+        "arn" => resource
+            .arn
+            .clone()
+            .ok_or_else(|| "the resource has no arn".to_string()),
+        "arn_suffix" => resource
+            .arn
+            .as_deref()
+            .and_then(arn_suffix)
+            .map(String::from)
+            .ok_or_else(|| "the arn of the resource has no suffix".to_string()),
+        "region" => Ok(resource.region.name().to_string()),
+        "account" => Ok(account_num.to_string()),
+        placeholder => match placeholder.strip_prefix(TAG_PLACEHOLDER_PREFIX) {
+            Some(key) => resource
+                .tags
+                .iter()
+                .flatten()
+                .find(|tag| tag.key.as_deref() == Some(key))
+                .and_then(|tag| tag.value.clone())
+                .ok_or_else(|| format!("the resource has no tag {}", key)),
+            None => Err(format!("unknown placeholder {{{}}}", placeholder)),
+        },
+    })
+}
+
+/// Checks the placeholders of the template of a dimension
+pub(crate) fn check_template(template: &str) -> Result<(), String> {
+    expand_template(template, |placeholder| match placeholder {
+        "id" | "arn" | "arn_suffix" | "region" | "account" => Ok(String::new()),
+        p if p.starts_with(TAG_PLACEHOLDER_PREFIX) => Ok(String::new()),
+        p => Err(format!("unknown placeholder {{{}}}", p)),
+    })
+    .map(|_| ())
+}
+
+fn expand_template(
+    template: &str,
+    resolve: impl Fn(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest
+            .find('}')
+            .ok_or_else(|| format!("unterminated placeholder in '{}'", template))?;
+        expanded.push_str(&resolve(&rest[1..end])?);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Part of the ARN following the resource type, for instance
+/// `app/my-alb/50dc6c495c0c9188` for a load balancer
+fn arn_suffix(arn: &str) -> Option<&str> {
+    let resource = arn.splitn(6, ':').nth(5)?;
+    resource.find('/').map(|i| &resource[i + 1..])
+}
+
+fn statistic_value(datapoint: &Datapoint, statistic: MetricStatistic) -> f64 {
+    match statistic {
+        MetricStatistic::SampleCount => datapoint.sample_count,
+        MetricStatistic::Average => datapoint.average,
+        MetricStatistic::Sum => datapoint.sum,
+        MetricStatistic::Minimum => datapoint.minimum,
+        MetricStatistic::Maximum => datapoint.maximum,
+    }
+    .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::resource::NTag;
     use rusoto_cloudwatch::Datapoint;
     use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
     use std::time::Duration;
@@ -491,24 +544,22 @@ mod tests {
                 Default::default(),
             ),
             account_num: "1234567890".to_string(),
-            ec2_metric_filters: Some(vec![MetricFilter {
-                name: "CPUUtilization".to_string(),
-                value: 0.0,
-                duration: Duration::from_secs(86400),
-                period: Duration::from_secs(3600),
-                statistic: MetricStatistic::Maximum,
-                ..Default::default()
-            }]),
-            ebs_metric_filters: None,
-            elb_alb_metric_filters: None,
-            elb_nlb_metric_filters: None,
-            rds_metric_filters: None,
-            aurora_metric_filters: None,
-            redshift_metric_filters: None,
-            emr_metric_filters: None,
-            es_metric_filters: None,
-            ecs_metric_filters: None,
-            eks_metric_filters: None,
+            metric_filters: vec![(
+                ClientType::Ec2Instance,
+                vec![MetricFilter {
+                    name: "CPUUtilization".to_string(),
+                    value: 0.0,
+                    duration: Duration::from_secs(86400),
+                    period: Duration::from_secs(3600),
+                    statistic: MetricStatistic::Maximum,
+                    ..Default::default()
+                }],
+            )]
+            .into_iter()
+            .collect(),
+            metrics: HashMap::new(),
+            idle_match: HashMap::new(),
+            trace: None,
         }
     }
 
@@ -570,7 +621,11 @@ mod tests {
     #[test]
     fn check_metrics_filter_by_min_utilization_of_10() {
         let cw_client = &create_client();
-        for _ in cw_client.ec2_metric_filters.as_ref().unwrap() {
+        for _ in cw_client
+            .metric_filters
+            .get(&ClientType::Ec2Instance)
+            .unwrap()
+        {
             assert_eq!(
                 cw_client.filter_metrics(
                     &get_metrics_for_min_utilization(),
@@ -588,7 +643,11 @@ mod tests {
     #[test]
     fn check_metrics_filter_by_min_num_of_metrics_required() {
         let cw_client = &create_client();
-        for _ in cw_client.ec2_metric_filters.as_ref().unwrap() {
+        for _ in cw_client
+            .metric_filters
+            .get(&ClientType::Ec2Instance)
+            .unwrap()
+        {
             assert_eq!(
                 cw_client.filter_metrics(
                     &get_metrics_for_min_utilization(),
@@ -606,7 +665,11 @@ mod tests {
     #[test]
     fn check_metrics_filter_by_min_utilization_of_20() {
         let cw_client = &create_client();
-        for _ in cw_client.ec2_metric_filters.as_ref().unwrap() {
+        for _ in cw_client
+            .metric_filters
+            .get(&ClientType::Ec2Instance)
+            .unwrap()
+        {
             assert_eq!(
                 cw_client.filter_metrics(
                     &get_metrics_for_min_utilization(),
@@ -620,4 +683,35 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn render_dimension_templates() {
+        let resource = Resource {
+            id: "my-alb".to_string(),
+            arn: Some(
+                "arn:aws:elasticloadbalancing:us-east-1:123456789012:loadbalancer/app/my-alb/50dc6c495c0c9188"
+                    .to_string(),
+            ),
+            tags: Some(vec![NTag {
+                key: Some("Name".to_string()),
+                value: Some("web".to_string()),
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_template("{arn_suffix}", &resource, "123456789012"),
+            Ok("app/my-alb/50dc6c495c0c9188".to_string())
+        );
+        assert_eq!(
+            render_template("{tag:Name}-{account}", &resource, "123456789012"),
+            Ok("web-123456789012".to_string())
+        );
+        assert!(render_template("{tag:Owner}", &resource, "123456789012").is_err());
+        assert!(check_template("{id}").is_ok());
+        assert_eq!(
+            check_template("{name}"),
+            Err("unknown placeholder {name}".to_string())
+        );
+    }
 }
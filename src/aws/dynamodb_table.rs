@@ -0,0 +1,427 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::{handle_future, handle_future_with_return};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use rusoto_core::{signature::SignedRequest, Client as RClient, Region};
+use rusoto_dynamodb::{
+    CreateBackupInput, DeleteTableInput, DescribeTableInput, DynamoDb, DynamoDbClient,
+    ListTablesInput, ListTagsOfResourceInput, TableDescription, Tag,
+};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use tracing::{debug, trace};
+
+const PAY_PER_REQUEST: &str = "PAY_PER_REQUEST";
+const PROVISIONED: &str = "PROVISIONED";
+
+pub(crate) const CLIENT: Client = Client::new("dynamodb_table");
+
+#[derive(Clone)]
+pub struct DynamodbTableClient {
+    client: DynamoDbClient,
+    raw_client: RClient,
+    region: Region,
+    config: ResourceConfig,
+    dry_run: bool,
+}
+
+impl DynamodbTableClient {
+    pub fn new(cd: &ClientDetails, config: &ResourceConfig, dry_run: bool) -> Self {
+        DynamodbTableClient {
+            client: DynamoDbClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            raw_client: cd.client.clone(),
+            region: cd.region.clone(),
+            config: config.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(&self, tables: Vec<TableDescription>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for table in tables {
+            let table_name = table.table_name.unwrap_or_default();
+            let tags = match table.table_arn {
+                Some(ref arn) => self.list_tags(arn).await,
+                None => None,
+            };
+            let mut termination_protection: Option<bool> = None;
+
+            if let Some(ref tp) = self.config.termination_protection {
+                if tp.ignore {
+                    termination_protection = self.get_deletion_protection(&table_name).await;
+                }
+            }
+
+            resources.push(Resource {
+                id: table_name,
+                arn: table.table_arn,
+                type_: ClientType::DynamodbTable,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: ResourceState::from_str(table.table_status.as_deref().unwrap_or_default())
+                    .ok(),
+                start_time: table
+                    .creation_date_time
+                    .map(|ct| Utc.timestamp(ct as i64, 0).to_rfc3339()),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: Some(vec![billing_type(
+                    table
+                        .billing_mode_summary
+                        .and_then(|b| b.billing_mode)
+                        .as_deref(),
+                    table
+                        .provisioned_throughput
+                        .as_ref()
+                        .and_then(|p| p.read_capacity_units),
+                    table
+                        .provisioned_throughput
+                        .as_ref()
+                        .and_then(|p| p.write_capacity_units),
+                )]),
+                dependencies: None,
+                termination_protection,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_tables(&self) -> Result<Vec<TableDescription>> {
+        let mut next_token: Option<String> = None;
+        let mut tables: Vec<TableDescription> = Vec::new();
+
+        loop {
+            let req = self.client.list_tables(ListTablesInput {
+                exclusive_start_table_name: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for table_name in result.table_names.unwrap_or_default() {
+                    let req = self
+                        .client
+                        .describe_table(DescribeTableInput { table_name });
+
+                    if let Ok(Some(table)) = handle_future_with_return!(req).map(|r| r.table) {
+                        tables.push(table);
+                    }
+                }
+
+                if result.last_evaluated_table_name.is_none() {
+                    break;
+                } else {
+                    next_token = result.last_evaluated_table_name;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(tables)
+    }
+
+    async fn list_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let mut next_token: Option<String> = None;
+        let mut tags: Vec<Tag> = Vec::new();
+
+        loop {
+            let req = self.client.list_tags_of_resource(ListTagsOfResourceInput {
+                resource_arn: arn.to_owned(),
+                next_token,
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                tags.extend(result.tags.unwrap_or_default());
+
+                if result.next_token.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_token;
+                }
+            } else {
+                return None;
+            }
+        }
+
+        Some(tags)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key),
+                    value: Some(tag.value),
+                })
+                .collect()
+        })
+    }
+
+    /// `rusoto_dynamodb` does not model table deletion protection, so the
+    /// setting is read and updated using requests signed against the DynamoDB
+    /// JSON API directly.
+    async fn dispatch_raw(&self, target: &str, payload: Value) -> Result<Value> {
+        let mut request = SignedRequest::new("POST", "dynamodb", &self.region, "/");
+        request.set_content_type("application/x-amz-json-1.0".to_owned());
+        request.add_header("x-amz-target", &format!("DynamoDB_20120810.{}", target));
+        request.set_payload(Some(payload.to_string()));
+
+        let mut response = self
+            .raw_client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|err| Error::HttpDispatch(format!("{:?}", err)))?;
+        let response = response
+            .buffer()
+            .await
+            .map_err(|err| Error::HttpDispatch(err.to_string()))?;
+
+        if !response.status.is_success() {
+            return Err(Error::Rusoto {
+                type_: target.to_string(),
+                msg: String::from_utf8_lossy(response.body.as_ref()).to_string(),
+            });
+        }
+
+        serde_json::from_slice(response.body.as_ref())
+            .map_err(|err| Error::HttpDispatch(err.to_string()))
+    }
+
+    async fn get_deletion_protection(&self, table_name: &str) -> Option<bool> {
+        match self
+            .dispatch_raw("DescribeTable", json!({ "TableName": table_name }))
+            .await
+        {
+            Ok(result) => result["Table"]["DeletionProtectionEnabled"].as_bool(),
+            Err(err) => {
+                trace!(resource = table_name, err = ?err, "Failed fetching deletion protection");
+                None
+            }
+        }
+    }
+
+    async fn disable_deletion_protection(&self, table_name: &str) -> Result<()> {
+        debug!(
+            "Deletion protection is enabled for: {}. Trying to disable it.",
+            table_name
+        );
+
+        self.dispatch_raw(
+            "UpdateTable",
+            json!({ "TableName": table_name, "DeletionProtectionEnabled": false }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn backup_table(&self, table_name: &str) -> Result<()> {
+        let backup_name = format!("{}-nuker-{}", table_name, Utc::now().format("%Y%m%d%H%M%S"));
+        debug!(
+            resource = table_name,
+            backup = backup_name.as_str(),
+            "Backing up"
+        );
+
+        let req = self.client.create_backup(CreateBackupInput {
+            backup_name,
+            table_name: table_name.to_owned(),
+        });
+        handle_future_with_return!(req)?;
+
+        Ok(())
+    }
+
+    async fn delete_table(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            if let Some(tp_enabled) = resource.termination_protection {
+                if tp_enabled {
+                    self.disable_deletion_protection(resource.id.as_ref())
+                        .await?;
+                }
+            }
+
+            // Never delete a table whose backup failed
+            if self.config.backup_before_delete {
+                self.backup_table(resource.id.as_ref()).await?;
+            }
+
+            let req = self.client.delete_table(DeleteTableInput {
+                table_name: resource.id.to_owned(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the billing mode of a table along with its provisioned capacity,
+/// for instance `PAY_PER_REQUEST` or `PROVISIONED:5:5` (read and write
+/// capacity units).
+fn billing_type(
+    billing_mode: Option<&str>,
+    read_capacity: Option<i64>,
+    write_capacity: Option<i64>,
+) -> String {
+    match billing_mode {
+        Some(PAY_PER_REQUEST) => PAY_PER_REQUEST.to_string(),
+        _ => format!(
+            "{}:{}:{}",
+            PROVISIONED,
+            read_capacity.unwrap_or_default(),
+            write_capacity.unwrap_or_default()
+        ),
+    }
+}
+
+#[async_trait]
+impl NukerClient for DynamodbTableClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized DynamoDB table resource scanner");
+        let tables = self.get_tables().await?;
+
+        self.package_resources(tables).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_table(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "dynamodb",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(DynamodbTableClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/DynamoDB",
+            dimensions: &[("TableName", "{id}")],
+            sparse: true,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_dynamodb::{BillingModeSummary, ProvisionedThroughputDescription};
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    fn create_client(dispatcher: MockRequestDispatcher, config: ResourceConfig) -> DynamodbTableClient {
+        let raw_client = RClient::new_with(MockCredentialsProvider, dispatcher);
+
+        DynamodbTableClient {
+            client: DynamoDbClient::new_with_client(raw_client.clone(), Region::UsEast1),
+            raw_client,
+            region: Region::UsEast1,
+            config,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn represent_billing_mode() {
+        assert_eq!(billing_type(Some(PAY_PER_REQUEST), None, None), "PAY_PER_REQUEST");
+        assert_eq!(
+            billing_type(Some(PROVISIONED), Some(5), Some(10)),
+            "PROVISIONED:5:10"
+        );
+        // Tables created before on-demand capacity have no billing mode
+        assert_eq!(billing_type(None, Some(1), Some(1)), "PROVISIONED:1:1");
+    }
+
+    #[tokio::test]
+    async fn package_tables() {
+        let client = create_client(MockRequestDispatcher::default(), ResourceConfig::default());
+        let tables = vec![
+            TableDescription {
+                table_name: Some("orders".to_string()),
+                table_status: Some("ACTIVE".to_string()),
+                creation_date_time: Some(1614592800.512),
+                billing_mode_summary: Some(BillingModeSummary {
+                    billing_mode: Some(PAY_PER_REQUEST.to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            TableDescription {
+                table_name: Some("events".to_string()),
+                table_status: Some("DELETING".to_string()),
+                provisioned_throughput: Some(ProvisionedThroughputDescription {
+                    read_capacity_units: Some(5),
+                    write_capacity_units: Some(5),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ];
+
+        let resources = client.package_resources(tables).await.unwrap();
+
+        assert_eq!(resources[0].id, "orders");
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00+00:00")
+        );
+        assert_eq!(
+            resources[0].resource_type,
+            Some(vec!["PAY_PER_REQUEST".to_string()])
+        );
+        assert_eq!(resources[1].state, Some(ResourceState::Deleted));
+        assert_eq!(resources[1].start_time, None);
+        assert_eq!(
+            resources[1].resource_type,
+            Some(vec!["PROVISIONED:5:5".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn read_deletion_protection() {
+        let client = create_client(
+            MockRequestDispatcher::default()
+                .with_body(r#"{"Table": {"TableName": "orders", "DeletionProtectionEnabled": true}}"#)
+                .with_request_checker(|request| {
+                    assert_eq!(
+                        request.headers["x-amz-target"][0],
+                        b"DynamoDB_20120810.DescribeTable".to_vec()
+                    );
+                }),
+            ResourceConfig::default(),
+        );
+
+        assert_eq!(client.get_deletion_protection("orders").await, Some(true));
+    }
+}
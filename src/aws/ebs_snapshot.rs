@@ -1,6 +1,7 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{create_ec2_tags, ClientDetails};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -12,6 +13,11 @@ use rusoto_ec2::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ebs_snapshot");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateSnapshot";
+
 #[derive(Clone)]
 pub struct EbsSnapshotClient {
     client: Ec2Client,
@@ -52,6 +58,7 @@ impl EbsSnapshotClient {
                 state: ResourceState::from_str(snapshot.state.take().unwrap_or_default().as_str())
                     .ok(),
                 start_time: snapshot.start_time.take(),
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -150,4 +157,22 @@ impl NukerClient for EbsSnapshotClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_snapshot(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(EbsSnapshotClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: None,
+    }
 }
@@ -1,6 +1,7 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{create_ec2_tags, describe_ec2_tags, ClientDetails};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -15,6 +16,11 @@ use tracing::{debug, trace};
 static ROOT_VOLUME_MOUNTS: &'static [&'static str] = &["/dev/sda1", "/dev/xvda"];
 // const GP2_TYPE: &str = "gp2";
 
+pub(crate) const CLIENT: Client = Client::new("ebs_volume");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateVolume";
+
 #[derive(Clone)]
 pub struct EbsVolumeClient {
     client: Ec2Client,
@@ -68,6 +74,7 @@ impl EbsVolumeClient {
                 state: ResourceState::from_str(volume.state.take().unwrap_or_default().as_str())
                     .ok(),
                 start_time: volume.create_time.take(),
+                created: None,
                 enforcement_state: if is_root_vol {
                     EnforcementState::Skip
                 } else {
@@ -189,4 +196,44 @@ impl NukerClient for EbsVolumeClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_volume(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+
+    async fn parent_tags(&self, resource: &Resource) -> Option<Vec<NTag>> {
+        let result = self
+            .client
+            .describe_volumes(DescribeVolumesRequest {
+                volume_ids: Some(vec![resource.id.clone()]),
+                ..Default::default()
+            })
+            .await
+            .ok()?;
+        let instance_id = result
+            .volumes?
+            .into_iter()
+            .flat_map(|volume| volume.attachments.unwrap_or_default())
+            .find_map(|attachment| attachment.instance_id)?;
+
+        describe_ec2_tags(&self.client, &instance_id).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(EbsVolumeClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: Some(CwMetrics {
+            namespace: "AWS/EBS",
+            dimensions: &[("VolumeId", "{id}")],
+            sparse: false,
+        }),
+    }
 }
@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -9,6 +10,8 @@ use rusoto_core::Region;
 use rusoto_ec2::{Address, DescribeAddressesRequest, Ec2, Ec2Client, ReleaseAddressRequest, Tag};
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_address");
+
 #[derive(Clone)]
 pub struct Ec2AddressClient {
     client: Ec2Client,
@@ -53,6 +56,7 @@ impl Ec2AddressClient {
                 },
                 enforcement_reason: None,
                 start_time: None,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 resource_type: None,
                 dependencies: None,
@@ -140,3 +144,17 @@ impl NukerClient for Ec2AddressClient {
         self.delete_address(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2AddressClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
@@ -1,6 +1,9 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{
+    cloudtrail::CloudTrailService, create_ec2_tags, describe_ec2_tags, ClientDetails,
+};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -14,12 +17,18 @@ use rusoto_ec2::{
 use std::str::FromStr;
 use tracing::{debug, trace, warn};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_eni");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateNetworkInterface";
+
 #[derive(Clone)]
 pub struct Ec2EniClient {
     client: Ec2Client,
     region: Region,
     account_num: String,
     config: ResourceConfig,
+    cloudtrail: CloudTrailService,
     dry_run: bool,
 }
 
@@ -30,12 +39,17 @@ impl Ec2EniClient {
             region: cd.region.clone(),
             account_num: cd.account_number.clone(),
             config: config.clone(),
+            cloudtrail: cd.cloudtrail().clone(),
             dry_run,
         }
     }
 
     async fn package_resources(&self, mut enis: Vec<NetworkInterface>) -> Result<Vec<Resource>> {
         let mut resources: Vec<Resource> = Vec::new();
+        let creations = self
+            .cloudtrail
+            .recent_creations(CREATE_EVENT, &self.config)
+            .await;
 
         for eni in &mut enis {
             let arn = format!(
@@ -45,6 +59,8 @@ impl Ec2EniClient {
                 eni.network_interface_id.as_ref().unwrap(),
             );
 
+            let created = creations.creation(eni.network_interface_id.as_deref().unwrap());
+
             resources.push(Resource {
                 id: eni.network_interface_id.take().unwrap(),
                 arn: Some(arn),
@@ -53,6 +69,7 @@ impl Ec2EniClient {
                 tags: self.package_tags(eni.tag_set.take()),
                 state: ResourceState::from_str(eni.status.as_deref().unwrap_or_default()).ok(),
                 start_time: None,
+                created,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -187,6 +204,7 @@ impl Ec2EniClient {
                                 tags: self.package_tags(instance.tags),
                                 state: None,
                                 start_time: None,
+                                created: None,
                                 enforcement_state: EnforcementState::DeleteDependent,
                                 enforcement_reason: Some(EnforcementReason::Dependent),
                                 resource_type: None,
@@ -237,4 +255,37 @@ impl NukerClient for Ec2EniClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_eni(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+
+    async fn parent_tags(&self, resource: &Resource) -> Option<Vec<NTag>> {
+        let result = self
+            .client
+            .describe_network_interface_attribute(DescribeNetworkInterfaceAttributeRequest {
+                network_interface_id: resource.id.to_string(),
+                attribute: Some("attachment".to_string()),
+                ..Default::default()
+            })
+            .await
+            .ok()?;
+        let instance_id = result.attachment?.instance_id?;
+
+        describe_ec2_tags(&self.client, &instance_id).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2EniClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: None,
+    }
 }
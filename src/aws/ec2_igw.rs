@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::NukerClient;
+use crate::client::{Client, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::Resource;
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -12,6 +13,8 @@ use rusoto_ec2::{
 };
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_igw");
+
 #[derive(Clone)]
 pub struct Ec2IgwClient {
     client: Ec2Client,
@@ -102,3 +105,17 @@ impl NukerClient for Ec2IgwClient {
         self.delete_igw(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2IgwClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
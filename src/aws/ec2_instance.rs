@@ -1,6 +1,7 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{create_ec2_tags, ClientDetails};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -14,6 +15,11 @@ use rusoto_ec2::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_instance");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "RunInstances";
+
 #[derive(Clone)]
 pub struct Ec2InstanceClient {
     client: Ec2Client,
@@ -83,6 +89,7 @@ impl Ec2InstanceClient {
                 )
                 .ok(),
                 start_time: instance.launch_time,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: instance.instance_type.map(|t| vec![t]),
@@ -247,4 +254,26 @@ impl NukerClient for Ec2InstanceClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_instance(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2InstanceClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: Some(CwMetrics {
+            namespace: "AWS/EC2",
+            dimensions: &[("InstanceId", "{id}")],
+            sparse: false,
+        }),
+    }
 }
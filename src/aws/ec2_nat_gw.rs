@@ -1,7 +1,8 @@
 use crate::aws::ClientDetails;
-use crate::client::NukerClient;
+use crate::client::{Client, NukerClient};
 use crate::config::ResourceConfig;
 use crate::handle_future;
+use crate::registry::ResourceType;
 use crate::resource::Resource;
 use crate::Result;
 use async_trait::async_trait;
@@ -9,6 +10,8 @@ use rusoto_core::Region;
 use rusoto_ec2::{DeleteNatGatewayRequest, Ec2, Ec2Client};
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_nat_gw");
+
 #[derive(Clone)]
 pub struct Ec2NatGWClient {
     client: Ec2Client,
@@ -71,3 +74,17 @@ impl NukerClient for Ec2NatGWClient {
         self.delete_nat_gateway(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2NatGWClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
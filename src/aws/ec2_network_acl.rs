@@ -1,7 +1,8 @@
 use crate::aws::ClientDetails;
-use crate::client::NukerClient;
+use crate::client::{Client, NukerClient};
 use crate::config::ResourceConfig;
 use crate::handle_future;
+use crate::registry::ResourceType;
 use crate::resource::Resource;
 use crate::Result;
 use async_trait::async_trait;
@@ -9,6 +10,8 @@ use rusoto_core::Region;
 use rusoto_ec2::{DeleteNetworkAclRequest, Ec2, Ec2Client};
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_network_acl");
+
 #[derive(Clone)]
 pub struct Ec2NetworkAclClient {
     client: Ec2Client,
@@ -71,3 +74,17 @@ impl NukerClient for Ec2NetworkAclClient {
         self.delete_nacl(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2NetworkAclClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
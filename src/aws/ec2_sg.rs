@@ -1,6 +1,7 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{cloudtrail::CloudTrailService, create_ec2_tags, ClientDetails};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -13,12 +14,18 @@ use rusoto_ec2::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_sg");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateSecurityGroup";
+
 #[derive(Clone)]
 pub struct Ec2SgClient {
     client: Ec2Client,
     region: Region,
     account_num: String,
     config: ResourceConfig,
+    cloudtrail: CloudTrailService,
     dry_run: bool,
 }
 
@@ -29,12 +36,17 @@ impl Ec2SgClient {
             region: cd.region.clone(),
             account_num: cd.account_number.clone(),
             config: config.clone(),
+            cloudtrail: cd.cloudtrail().clone(),
             dry_run,
         }
     }
 
     async fn package_resources(&self, sgs: Vec<SecurityGroup>) -> Result<Vec<Resource>> {
         let mut resources: Vec<Resource> = Vec::new();
+        let creations = self
+            .cloudtrail
+            .recent_creations(CREATE_EVENT, &self.config)
+            .await;
 
         for sg in sgs {
             let arn = format!(
@@ -44,6 +56,8 @@ impl Ec2SgClient {
                 sg.group_id.as_ref().unwrap(),
             );
 
+            let created = creations.creation(sg.group_id.as_deref().unwrap());
+
             resources.push(Resource {
                 id: sg.group_id.unwrap(),
                 arn: Some(arn),
@@ -52,6 +66,7 @@ impl Ec2SgClient {
                 tags: self.package_tags(sg.tags),
                 state: None,
                 start_time: None,
+                created,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -193,6 +208,7 @@ impl Ec2SgClient {
                         state: ResourceState::from_str(eni.status.as_deref().unwrap_or_default())
                             .ok(),
                         start_time: None,
+                        created: None,
                         enforcement_state: EnforcementState::DeleteDependent,
                         enforcement_reason: Some(EnforcementReason::Dependent),
                         resource_type: None,
@@ -270,4 +286,22 @@ impl NukerClient for Ec2SgClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_sg(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2SgClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: None,
+    }
 }
@@ -1,6 +1,7 @@
-use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::aws::{cloudtrail::CloudTrailService, create_ec2_tags, ClientDetails};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -16,12 +17,18 @@ use rusoto_ec2::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ec2_vpc");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateVpc";
+
 #[derive(Clone)]
 pub struct Ec2VpcClient {
     client: Ec2Client,
     region: Region,
     account_num: String,
     config: ResourceConfig,
+    cloudtrail: CloudTrailService,
     dry_run: bool,
 }
 
@@ -32,12 +39,17 @@ impl Ec2VpcClient {
             region: cd.region.clone(),
             account_num: cd.account_number.clone(),
             config: config.clone(),
+            cloudtrail: cd.cloudtrail().clone(),
             dry_run,
         }
     }
 
     async fn package_resources(&self, vpcs: Vec<Vpc>) -> Result<Vec<Resource>> {
         let mut resources: Vec<Resource> = Vec::new();
+        let creations = self
+            .cloudtrail
+            .recent_creations(CREATE_EVENT, &self.config)
+            .await;
 
         for vpc in vpcs {
             let vpc_id = vpc.vpc_id.as_ref().unwrap();
@@ -57,6 +69,8 @@ impl Ec2VpcClient {
                 }
             };
 
+            let created = creations.creation(vpc_id);
+
             resources.push(Resource {
                 id: vpc_id.into(),
                 arn: Some(arn),
@@ -65,6 +79,7 @@ impl Ec2VpcClient {
                 tags: self.package_tags(vpc.tags),
                 state: ResourceState::from_str(vpc.state.as_deref().unwrap()).ok(),
                 start_time: None,
+                created,
                 enforcement_state,
                 enforcement_reason: None,
                 resource_type: None,
@@ -151,6 +166,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(igw.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -193,6 +209,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(subnet.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -241,6 +258,7 @@ impl Ec2VpcClient {
                                 tags: self.package_tags(rt.tags),
                                 state: None,
                                 start_time: None,
+                                created: None,
                                 enforcement_state: EnforcementState::DeleteDependent,
                                 enforcement_reason: Some(EnforcementReason::Dependent),
                                 resource_type: None,
@@ -285,6 +303,7 @@ impl Ec2VpcClient {
                                 tags: self.package_tags(nacl.tags),
                                 state: None,
                                 start_time: None,
+                                created: None,
                                 enforcement_state: EnforcementState::DeleteDependent,
                                 enforcement_reason: Some(EnforcementReason::Dependent),
 
@@ -329,6 +348,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(conn.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -371,6 +391,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(endpoint.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -406,6 +427,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(nat.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -449,6 +471,7 @@ impl Ec2VpcClient {
                                 tags: self.package_tags(sg.tags),
                                 state: None,
                                 start_time: None,
+                                created: None,
                                 enforcement_state: EnforcementState::DeleteDependent,
                                 enforcement_reason: Some(EnforcementReason::Dependent),
                                 resource_type: None,
@@ -492,6 +515,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(vpn_gateway.tags),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -534,6 +558,7 @@ impl Ec2VpcClient {
                             tags: self.package_tags(network_interface.tag_set),
                             state: None,
                             start_time: None,
+                            created: None,
                             enforcement_state: EnforcementState::DeleteDependent,
                             enforcement_reason: Some(EnforcementReason::Dependent),
                             resource_type: None,
@@ -592,4 +617,22 @@ impl NukerClient for Ec2VpcClient {
     async fn delete(&self, resource: &Resource) -> Result<()> {
         self.delete_vpc(resource).await
     }
+
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        create_ec2_tags(&self.client, resource, tags, self.dry_run).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ec2",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(Ec2VpcClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: None,
+    }
 }
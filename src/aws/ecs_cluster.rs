@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -14,6 +15,8 @@ use rusoto_ecs::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("ecs_cluster");
+
 #[derive(Clone)]
 pub struct EcsClusterClient {
     client: EcsClient,
@@ -49,6 +52,7 @@ impl EcsClusterClient {
                 tags: self.package_tags(cluster.tags),
                 state: ResourceState::from_str(cluster.status.as_ref().unwrap()).ok(),
                 start_time: None,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type,
@@ -227,3 +231,21 @@ impl NukerClient for EcsClusterClient {
         self.delete_cluster(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "ecs",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(EcsClusterClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/ECS",
+            dimensions: &[("ClusterName", "{id}")],
+            sparse: false,
+        }),
+    }
+}
@@ -0,0 +1,303 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
+use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_elasticache::{
+    CacheCluster, DeleteCacheClusterMessage, DescribeCacheClustersMessage, ElastiCache,
+    ElastiCacheClient, ListTagsForResourceMessage, Tag,
+};
+use std::str::FromStr;
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("elasticache_cluster");
+
+#[derive(Clone)]
+pub struct ElasticacheClusterClient {
+    client: ElastiCacheClient,
+    region: Region,
+    account_num: String,
+    dry_run: bool,
+}
+
+impl ElasticacheClusterClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        ElasticacheClusterClient {
+            client: ElastiCacheClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            account_num: cd.account_number.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(&self, clusters: Vec<CacheCluster>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for cluster in clusters {
+            let cluster_id = cluster.cache_cluster_id.unwrap_or_default();
+            let arn = cluster.arn.unwrap_or_else(|| {
+                format!(
+                    "arn:aws:elasticache:{}:{}:cluster:{}",
+                    self.region.name(),
+                    self.account_num,
+                    cluster_id
+                )
+            });
+            let tags = self.list_tags(&arn).await;
+
+            resources.push(Resource {
+                id: cluster_id,
+                arn: Some(arn),
+                type_: ClientType::ElasticacheCluster,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: ResourceState::from_str(
+                    cluster.cache_cluster_status.as_deref().unwrap_or_default(),
+                )
+                .ok(),
+                start_time: cluster.cache_cluster_create_time,
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: cluster.cache_node_type.map(|t| vec![t]),
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_clusters(&self, cluster_id: Option<String>) -> Result<Vec<CacheCluster>> {
+        let mut next_token: Option<String> = None;
+        let mut clusters: Vec<CacheCluster> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .describe_cache_clusters(DescribeCacheClustersMessage {
+                    cache_cluster_id: cluster_id.clone(),
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                if let Some(cs) = result.cache_clusters {
+                    for c in cs {
+                        clusters.push(c);
+                    }
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(clusters)
+    }
+
+    async fn list_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self
+            .client
+            .list_tags_for_resource(ListTagsForResourceMessage {
+                resource_name: arn.to_owned(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tag_list)
+            .unwrap_or_default()
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: tag.key,
+                    value: tag.value,
+                })
+                .collect()
+        })
+    }
+
+    /// Clusters that are members of a replication group can only be removed
+    /// by removing the replication group, which is tracked as a dependency.
+    async fn get_dependencies(&self, resource: &Resource) -> Result<Vec<Resource>> {
+        let mut resources = Vec::new();
+
+        for cluster in self.get_clusters(Some(resource.id.clone())).await? {
+            if let Some(replication_group_id) = cluster.replication_group_id {
+                resources.push(Resource {
+                    id: replication_group_id,
+                    arn: None,
+                    type_: ClientType::ElasticacheReplicationGroup,
+                    region: self.region.clone(),
+                    tags: None,
+                    state: None,
+                    start_time: None,
+                    created: None,
+                    enforcement_state: EnforcementState::DeleteDependent,
+                    enforcement_reason: Some(EnforcementReason::Dependent),
+                    resource_type: None,
+                    dependencies: None,
+                    termination_protection: None,
+                });
+            }
+        }
+
+        Ok(resources)
+    }
+
+    async fn delete_cluster(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let clusters = self.get_clusters(Some(resource.id.clone())).await?;
+
+            if clusters.iter().any(|c| c.replication_group_id.is_some()) {
+                debug!(
+                    resource = resource.id.as_str(),
+                    "Cluster is removed along with its replication group"
+                );
+                return Ok(());
+            }
+
+            let req = self.client.delete_cache_cluster(DeleteCacheClusterMessage {
+                cache_cluster_id: resource.id.to_owned(),
+                ..Default::default()
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for ElasticacheClusterClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized ElastiCache cluster resource scanner");
+        let clusters = self.get_clusters(None).await?;
+
+        self.package_resources(clusters).await
+    }
+
+    async fn dependencies(&self, resource: &Resource) -> Option<Vec<Resource>> {
+        self.get_dependencies(resource).await.ok()
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_cluster(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "elasticache",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(ElasticacheClusterClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/ElastiCache",
+            dimensions: &[("CacheClusterId", "{id}")],
+            sparse: false,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> ElasticacheClusterClient {
+        ElasticacheClusterClient {
+            client: ElastiCacheClient::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            account_num: "123456789012".to_string(),
+            dry_run: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn package_clusters() {
+        let client = create_client(MockRequestDispatcher::default());
+        let clusters = vec![CacheCluster {
+            cache_cluster_id: Some("sessions".to_string()),
+            cache_cluster_status: Some("available".to_string()),
+            cache_cluster_create_time: Some("2021-03-01T10:00:00.512Z".to_string()),
+            cache_node_type: Some("cache.t3.micro".to_string()),
+            ..Default::default()
+        }];
+
+        let resources = client.package_resources(clusters).await.unwrap();
+
+        assert_eq!(resources[0].id, "sessions");
+        assert_eq!(
+            resources[0].arn.as_deref(),
+            Some("arn:aws:elasticache:us-east-1:123456789012:cluster:sessions")
+        );
+        assert_eq!(resources[0].state, Some(ResourceState::Available));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00.512Z")
+        );
+        assert_eq!(
+            resources[0].resource_type,
+            Some(vec!["cache.t3.micro".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn depend_on_replication_group() {
+        let client = create_client(MockRequestDispatcher::default().with_body(
+            r#"<DescribeCacheClustersResponse><DescribeCacheClustersResult>
+                <CacheClusters><CacheCluster>
+                    <CacheClusterId>sessions-001</CacheClusterId>
+                    <ReplicationGroupId>sessions</ReplicationGroupId>
+                </CacheCluster></CacheClusters>
+            </DescribeCacheClustersResult>
+            <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+            </DescribeCacheClustersResponse>"#,
+        ));
+        let resource = Resource {
+            id: "sessions-001".to_string(),
+            type_: ClientType::ElasticacheCluster,
+            ..Default::default()
+        };
+
+        let dependencies = client.get_dependencies(&resource).await.unwrap();
+
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].id, "sessions");
+        assert_eq!(
+            dependencies[0].type_,
+            ClientType::ElasticacheReplicationGroup
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::aws::{ClientDetails, CwClient};
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_elasticache::{
+    DeleteReplicationGroupMessage, DescribeCacheClustersMessage, DescribeReplicationGroupsMessage,
+    ElastiCache, ElastiCacheClient, ListTagsForResourceMessage, ReplicationGroup, Tag,
+};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("elasticache_replication_group");
+
+#[derive(Clone)]
+pub struct ElasticacheReplicationGroupClient {
+    client: ElastiCacheClient,
+    region: Region,
+    account_num: String,
+    dry_run: bool,
+}
+
+impl ElasticacheReplicationGroupClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        ElasticacheReplicationGroupClient {
+            client: ElastiCacheClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            account_num: cd.account_number.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(&self, groups: Vec<ReplicationGroup>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+        let create_times = self.get_cluster_create_times().await;
+
+        for group in groups {
+            let group_id = group.replication_group_id.unwrap_or_default();
+            let arn = group.arn.unwrap_or_else(|| {
+                format!(
+                    "arn:aws:elasticache:{}:{}:replicationgroup:{}",
+                    self.region.name(),
+                    self.account_num,
+                    group_id
+                )
+            });
+            let tags = self.list_tags(&arn).await;
+            // Replication groups do not expose a creation time, the oldest
+            // member cluster is used instead.
+            let start_time = group
+                .member_clusters
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|c| create_times.get(c))
+                .min()
+                .cloned();
+
+            resources.push(Resource {
+                id: group_id,
+                arn: Some(arn),
+                type_: ClientType::ElasticacheReplicationGroup,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: ResourceState::from_str(group.status.as_deref().unwrap_or_default()).ok(),
+                start_time,
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: group.cache_node_type.map(|t| vec![t]),
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_replication_groups(
+        &self,
+        replication_group_id: Option<String>,
+    ) -> Result<Vec<ReplicationGroup>> {
+        let mut next_token: Option<String> = None;
+        let mut groups: Vec<ReplicationGroup> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .describe_replication_groups(DescribeReplicationGroupsMessage {
+                    replication_group_id: replication_group_id.clone(),
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                if let Some(gs) = result.replication_groups {
+                    for g in gs {
+                        groups.push(g);
+                    }
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(groups)
+    }
+
+    async fn get_cluster_create_times(&self) -> HashMap<String, String> {
+        let mut next_token: Option<String> = None;
+        let mut create_times: HashMap<String, String> = HashMap::new();
+
+        loop {
+            let req = self
+                .client
+                .describe_cache_clusters(DescribeCacheClustersMessage {
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for cluster in result.cache_clusters.unwrap_or_default() {
+                    if let (Some(id), Some(ct)) =
+                        (cluster.cache_cluster_id, cluster.cache_cluster_create_time)
+                    {
+                        create_times.insert(id, ct);
+                    }
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        create_times
+    }
+
+    async fn list_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self
+            .client
+            .list_tags_for_resource(ListTagsForResourceMessage {
+                resource_name: arn.to_owned(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tag_list)
+            .unwrap_or_default()
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: tag.key,
+                    value: tag.value,
+                })
+                .collect()
+        })
+    }
+
+    async fn delete_replication_group(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self
+                .client
+                .delete_replication_group(DeleteReplicationGroupMessage {
+                    replication_group_id: resource.id.to_owned(),
+                    retain_primary_cluster: Some(false),
+                    ..Default::default()
+                });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for ElasticacheReplicationGroupClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized ElastiCache replication group resource scanner");
+        let groups = self.get_replication_groups(None).await?;
+
+        self.package_resources(groups).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    /// Metrics are published per cache cluster, a replication group is idle
+    /// when all of its member clusters are idle.
+    async fn filter_by_idle_rules(
+        &self,
+        resource: &Resource,
+        cw_client: Arc<Box<CwClient>>,
+    ) -> bool {
+        let member_clusters: Vec<String> = self
+            .get_replication_groups(Some(resource.id.clone()))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|g| g.member_clusters.unwrap_or_default())
+            .collect();
+
+        cw_client
+            .filter_elasticache_replication_group(resource, &member_clusters)
+            .await
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_replication_group(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "elasticache",
+        global: false,
+        builder: |cd, config, dry_run| {
+            Box::new(ElasticacheReplicationGroupClient::new(cd, config, dry_run))
+        },
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/ElastiCache",
+            dimensions: &[("CacheClusterId", "{id}")],
+            sparse: false,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    #[tokio::test]
+    async fn start_at_the_oldest_member() {
+        let client = ElasticacheReplicationGroupClient {
+            client: ElastiCacheClient::new_with(
+                MockRequestDispatcher::default().with_body(
+                    r#"<DescribeCacheClustersResponse><DescribeCacheClustersResult>
+                        <CacheClusters>
+                            <CacheCluster>
+                                <CacheClusterId>sessions-001</CacheClusterId>
+                                <CacheClusterCreateTime>2021-03-02T10:00:00Z</CacheClusterCreateTime>
+                            </CacheCluster>
+                            <CacheCluster>
+                                <CacheClusterId>sessions-002</CacheClusterId>
+                                <CacheClusterCreateTime>2021-03-01T10:00:00Z</CacheClusterCreateTime>
+                            </CacheCluster>
+                            <CacheCluster>
+                                <CacheClusterId>queue-001</CacheClusterId>
+                                <CacheClusterCreateTime>2020-01-01T10:00:00Z</CacheClusterCreateTime>
+                            </CacheCluster>
+                        </CacheClusters>
+                    </DescribeCacheClustersResult>
+                    <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+                    </DescribeCacheClustersResponse>"#,
+                ),
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            account_num: "123456789012".to_string(),
+            dry_run: false,
+        };
+        let groups = vec![ReplicationGroup {
+            replication_group_id: Some("sessions".to_string()),
+            status: Some("available".to_string()),
+            member_clusters: Some(vec!["sessions-001".to_string(), "sessions-002".to_string()]),
+            ..Default::default()
+        }];
+
+        let resources = client.package_resources(groups).await.unwrap();
+
+        assert_eq!(resources[0].id, "sessions");
+        assert_eq!(resources[0].state, Some(ResourceState::Available));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00Z")
+        );
+    }
+}
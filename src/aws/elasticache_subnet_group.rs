@@ -0,0 +1,326 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_elasticache::{
+    CacheCluster, CacheSubnetGroup, DeleteCacheSubnetGroupMessage, DescribeCacheClustersMessage,
+    DescribeCacheSubnetGroupsMessage, ElastiCache, ElastiCacheClient, ListTagsForResourceMessage,
+    Tag,
+};
+use tracing::{debug, trace};
+
+const DEFAULT_SUBNET_GROUP: &str = "default";
+
+pub(crate) const CLIENT: Client = Client::new("elasticache_subnet_group");
+
+#[derive(Clone)]
+pub struct ElasticacheSubnetGroupClient {
+    client: ElastiCacheClient,
+    region: Region,
+    account_num: String,
+    dry_run: bool,
+}
+
+impl ElasticacheSubnetGroupClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        ElasticacheSubnetGroupClient {
+            client: ElastiCacheClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            account_num: cd.account_number.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(
+        &self,
+        subnet_groups: Vec<CacheSubnetGroup>,
+    ) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+        let clusters = self.get_clusters().await;
+
+        for subnet_group in subnet_groups {
+            let group_name = subnet_group.cache_subnet_group_name.unwrap_or_default();
+
+            if group_name == DEFAULT_SUBNET_GROUP {
+                continue;
+            }
+
+            let arn = subnet_group.arn.unwrap_or_else(|| {
+                format!(
+                    "arn:aws:elasticache:{}:{}:subnetgroup:{}",
+                    self.region.name(),
+                    self.account_num,
+                    group_name
+                )
+            });
+            let tags = self.list_tags(&arn).await;
+            let in_use = clusters
+                .iter()
+                .any(|c| c.cache_subnet_group_name.as_deref() == Some(group_name.as_str()));
+
+            resources.push(Resource {
+                id: group_name,
+                arn: Some(arn),
+                type_: ClientType::ElasticacheSubnetGroup,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: if in_use {
+                    Some(ResourceState::Running)
+                } else {
+                    Some(ResourceState::Available)
+                },
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_subnet_groups(&self) -> Result<Vec<CacheSubnetGroup>> {
+        let mut next_token: Option<String> = None;
+        let mut subnet_groups: Vec<CacheSubnetGroup> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .describe_cache_subnet_groups(DescribeCacheSubnetGroupsMessage {
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                if let Some(sgs) = result.cache_subnet_groups {
+                    for sg in sgs {
+                        subnet_groups.push(sg);
+                    }
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(subnet_groups)
+    }
+
+    async fn get_clusters(&self) -> Vec<CacheCluster> {
+        let mut next_token: Option<String> = None;
+        let mut clusters: Vec<CacheCluster> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .describe_cache_clusters(DescribeCacheClustersMessage {
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                clusters.extend(result.cache_clusters.unwrap_or_default());
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        clusters
+    }
+
+    async fn list_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self
+            .client
+            .list_tags_for_resource(ListTagsForResourceMessage {
+                resource_name: arn.to_owned(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tag_list)
+            .unwrap_or_default()
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: tag.key,
+                    value: tag.value,
+                })
+                .collect()
+        })
+    }
+
+    /// Clusters launched in the subnet group have to be removed before the
+    /// subnet group can be removed.
+    async fn get_dependencies(&self, resource: &Resource) -> Result<Vec<Resource>> {
+        let mut resources = Vec::new();
+
+        for cluster in self.get_clusters().await {
+            if cluster.cache_subnet_group_name.as_deref() == Some(resource.id.as_str()) {
+                resources.push(Resource {
+                    id: cluster.cache_cluster_id.unwrap_or_default(),
+                    arn: cluster.arn,
+                    type_: ClientType::ElasticacheCluster,
+                    region: self.region.clone(),
+                    tags: None,
+                    state: None,
+                    start_time: None,
+                    created: None,
+                    enforcement_state: EnforcementState::DeleteDependent,
+                    enforcement_reason: Some(EnforcementReason::Dependent),
+                    resource_type: None,
+                    dependencies: None,
+                    termination_protection: None,
+                });
+            }
+        }
+
+        Ok(resources)
+    }
+
+    async fn delete_subnet_group(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self
+                .client
+                .delete_cache_subnet_group(DeleteCacheSubnetGroupMessage {
+                    cache_subnet_group_name: resource.id.to_owned(),
+                });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for ElasticacheSubnetGroupClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized ElastiCache subnet group resource scanner");
+        let subnet_groups = self.get_subnet_groups().await?;
+
+        self.package_resources(subnet_groups).await
+    }
+
+    async fn dependencies(&self, resource: &Resource) -> Option<Vec<Resource>> {
+        self.get_dependencies(resource).await.ok()
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        if resource.state == Some(ResourceState::Available) {
+            debug!(
+                resource = resource.id.as_str(),
+                "Subnet group is not used by any cluster."
+            );
+            return Some(true);
+        }
+
+        Some(false)
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_subnet_group(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "elasticache",
+        global: false,
+        builder: |cd, config, dry_run| {
+            Box::new(ElasticacheSubnetGroupClient::new(cd, config, dry_run))
+        },
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    #[tokio::test]
+    async fn remove_unused_subnet_groups() {
+        let client = ElasticacheSubnetGroupClient {
+            client: ElastiCacheClient::new_with(
+                MockRequestDispatcher::default().with_body(
+                    r#"<DescribeCacheClustersResponse><DescribeCacheClustersResult>
+                        <CacheClusters><CacheCluster>
+                            <CacheClusterId>sessions-001</CacheClusterId>
+                            <CacheSubnetGroupName>sessions</CacheSubnetGroupName>
+                        </CacheCluster></CacheClusters>
+                    </DescribeCacheClustersResult>
+                    <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+                    </DescribeCacheClustersResponse>"#,
+                ),
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            account_num: "123456789012".to_string(),
+            dry_run: false,
+        };
+        let subnet_groups = ["default", "sessions", "legacy"]
+            .iter()
+            .map(|name| CacheSubnetGroup {
+                cache_subnet_group_name: Some(name.to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        let resources = client.package_resources(subnet_groups).await.unwrap();
+
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].id, "sessions");
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            client
+                .additional_filters(&resources[0], &ResourceConfig::default())
+                .await,
+            Some(false)
+        );
+        assert_eq!(resources[1].id, "legacy");
+        assert_eq!(resources[1].state, Some(ResourceState::Available));
+        assert_eq!(
+            client
+                .additional_filters(&resources[1], &ResourceConfig::default())
+                .await,
+            Some(true)
+        );
+    }
+}
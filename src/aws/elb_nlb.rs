@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -16,6 +17,8 @@ use tracing::{debug, trace};
 const ALB_TYPE: &str = "application";
 const NLB_TYPE: &str = "network";
 
+pub(crate) const CLIENT: Client = Client::new("elb_nlb");
+
 #[derive(Clone)]
 pub struct ElbNlbClient {
     client: ElbClient,
@@ -52,6 +55,7 @@ impl ElbNlbClient {
                 state: ResourceState::from_str(lb.state.as_ref().unwrap().code.as_ref().unwrap())
                     .ok(),
                 start_time: lb.created_time,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -177,3 +181,21 @@ impl NukerClient for ElbNlbClient {
         self.delete_load_balancer(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "elasticloadbalancing",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(ElbNlbClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/NetworkELB",
+            dimensions: &[("LoadBalancer", "{arn_suffix}")],
+            sparse: false,
+        }),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -13,6 +14,8 @@ use rusoto_emr::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("emr_cluster");
+
 #[derive(Clone)]
 pub struct EmrClusterClient {
     client: EmrClient,
@@ -62,6 +65,7 @@ impl EmrClusterClient {
                 )
                 .ok(),
                 start_time,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: instance_types,
@@ -203,3 +207,21 @@ impl NukerClient for EmrClusterClient {
         self.terminate_cluster(resource).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "elasticmapreduce",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(EmrClusterClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/ElasticMapReduce",
+            dimensions: &[("JobFlowId", "{id}")],
+            sparse: false,
+        }),
+    }
+}
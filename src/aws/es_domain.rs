@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -14,6 +15,8 @@ use rusoto_es::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("es_domain");
+
 #[derive(Clone)]
 pub struct EsDomainClient {
     client: EsClient,
@@ -78,6 +81,7 @@ impl EsDomainClient {
                 tags: self.package_tags(tags),
                 state: ResourceState::from_str(domain_state.unwrap_or_default().as_str()).ok(),
                 start_time,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: instance_type,
@@ -194,3 +198,21 @@ impl NukerClient for EsDomainClient {
         self.terminate_resource(resource.id.to_owned()).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &["es"],
+        service: "es",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(EsDomainClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/ES",
+            dimensions: &[("DomainName", "{id}"), ("ClientId", "{account}")],
+            sparse: false,
+        }),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -12,6 +13,8 @@ use rusoto_glue::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("glue_endpoint");
+
 #[derive(Clone)]
 pub struct GlueEndpointClient {
     client: GlueClient,
@@ -90,6 +93,7 @@ impl GlueEndpointClient {
                     "{}",
                     endpoint.created_timestamp.unwrap_or(0f64) as i64
                 )),
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: endpoint.worker_type.map(|t| vec![t]),
@@ -163,3 +167,17 @@ impl NukerClient for GlueEndpointClient {
         self.delete_endpoint(resource.id.as_ref()).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "glue",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(GlueEndpointClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
@@ -0,0 +1,233 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_iam::{
+    DeleteInstanceProfileRequest, GetInstanceProfileRequest, Iam, IamClient, InstanceProfile,
+    ListInstanceProfilesRequest, RemoveRoleFromInstanceProfileRequest,
+};
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("iam_instance_profile");
+
+#[derive(Clone)]
+pub struct IamInstanceProfileClient {
+    client: IamClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl IamInstanceProfileClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        IamInstanceProfileClient {
+            client: IamClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_instance_profiles(&self) -> Result<Vec<InstanceProfile>> {
+        let mut next_token: Option<String> = None;
+        let mut profiles: Vec<InstanceProfile> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .list_instance_profiles(ListInstanceProfilesRequest {
+                    marker: next_token,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for profile in result.instance_profiles {
+                    profiles.push(profile);
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    async fn package_resources(&self, profiles: Vec<InstanceProfile>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for profile in profiles {
+            resources.push(Resource {
+                id: profile.instance_profile_name,
+                arn: Some(profile.arn),
+                type_: ClientType::IamInstanceProfile,
+                region: self.region.clone(),
+                tags: None,
+                state: if profile.roles.is_empty() {
+                    Some(ResourceState::Available)
+                } else {
+                    Some(ResourceState::Running)
+                },
+                start_time: Some(profile.create_date),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    /// An instance profile can only be removed once all the roles are removed
+    /// from it.
+    async fn delete_instance_profile(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self.client.get_instance_profile(GetInstanceProfileRequest {
+                instance_profile_name: resource.id.to_owned(),
+            });
+
+            for role in handle_future_with_return!(req)
+                .map(|r| r.instance_profile.roles)
+                .unwrap_or_default()
+            {
+                let req = self.client.remove_role_from_instance_profile(
+                    RemoveRoleFromInstanceProfileRequest {
+                        instance_profile_name: resource.id.to_owned(),
+                        role_name: role.role_name,
+                    },
+                );
+                handle_future!(req);
+            }
+
+            let req = self
+                .client
+                .delete_instance_profile(DeleteInstanceProfileRequest {
+                    instance_profile_name: resource.id.to_owned(),
+                });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for IamInstanceProfileClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized IAM instance profile resource scanner");
+        let profiles = self.get_instance_profiles().await?;
+
+        self.package_resources(profiles).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        if resource.state == Some(ResourceState::Available) {
+            debug!(
+                resource = resource.id.as_str(),
+                "Instance profile has no roles."
+            );
+            return Some(true);
+        }
+
+        Some(false)
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_instance_profile(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "iam",
+        global: true,
+        builder: |cd, config, dry_run| Box::new(IamInstanceProfileClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_iam::Role;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    #[tokio::test]
+    async fn remove_profiles_without_roles() {
+        let client = IamInstanceProfileClient {
+            client: IamClient::new_with(
+                MockRequestDispatcher::default(),
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            dry_run: false,
+        };
+        let profiles = vec![
+            InstanceProfile {
+                instance_profile_name: "ci".to_string(),
+                create_date: "2021-03-01T10:00:00Z".to_string(),
+                roles: vec![Role {
+                    role_name: "ci".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            InstanceProfile {
+                instance_profile_name: "legacy".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let resources = client.package_resources(profiles).await.unwrap();
+
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00Z")
+        );
+        assert_eq!(
+            client
+                .additional_filters(&resources[0], &ResourceConfig::default())
+                .await,
+            Some(false)
+        );
+        assert_eq!(resources[1].state, Some(ResourceState::Available));
+        assert_eq!(
+            client
+                .additional_filters(&resources[1], &ResourceConfig::default())
+                .await,
+            Some(true)
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_iam::{
+    DeleteRolePolicyRequest, DeleteRoleRequest, DetachRolePolicyRequest, GetRoleRequest, Iam,
+    IamClient, ListAttachedRolePoliciesRequest, ListInstanceProfilesForRoleRequest,
+    ListRolePoliciesRequest, ListRoleTagsRequest, ListRolesRequest,
+    RemoveRoleFromInstanceProfileRequest, Role, Tag,
+};
+use tracing::{debug, trace};
+
+const MAX_ITEMS: i64 = 1000;
+/// Roles managed by AWS (service-linked roles and SSO roles) cannot be removed
+const AWS_MANAGED_PATHS: [&str; 2] = ["/aws-service-role/", "/aws-reserved/"];
+
+pub(crate) const CLIENT: Client = Client::new("iam_role");
+
+#[derive(Clone)]
+pub struct IamRoleClient {
+    client: IamClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl IamRoleClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        IamRoleClient {
+            client: IamClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_roles(&self) -> Result<Vec<Role>> {
+        let mut next_token: Option<String> = None;
+        let mut roles: Vec<Role> = Vec::new();
+
+        loop {
+            let req = self.client.list_roles(ListRolesRequest {
+                marker: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for role in result.roles {
+                    if !AWS_MANAGED_PATHS.iter().any(|p| role.path.starts_with(p)) {
+                        roles.push(role);
+                    }
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(roles)
+    }
+
+    async fn package_resources(&self, roles: Vec<Role>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for role in roles {
+            let tags = self.list_tags(&role.role_name).await;
+
+            resources.push(Resource {
+                id: role.role_name,
+                arn: Some(role.arn),
+                type_: ClientType::IamRole,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(ResourceState::Available),
+                start_time: Some(role.create_date),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn list_tags(&self, role_name: &str) -> Option<Vec<Tag>> {
+        let req = self.client.list_role_tags(ListRoleTagsRequest {
+            role_name: role_name.to_string(),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+
+        handle_future_with_return!(req).ok().map(|r| r.tags)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key),
+                    value: Some(tag.value),
+                })
+                .collect()
+        })
+    }
+
+    /// Checks if the role was not used within `max_idle_time` using
+    /// `RoleLastUsed`, roles that were never used are checked against their
+    /// creation date.
+    async fn is_unused(&self, resource: &Resource, config: &ResourceConfig) -> Option<bool> {
+        let max_idle_time = config.max_idle_time.as_ref()?;
+        let req = self.client.get_role(GetRoleRequest {
+            role_name: resource.id.to_owned(),
+        });
+        let last_used = handle_future_with_return!(req)
+            .ok()?
+            .role
+            .role_last_used
+            .and_then(|r| r.last_used_date)
+            .or_else(|| resource.start_time.clone())?;
+
+        Some(crate::util::is_ts_older_than(&last_used, max_idle_time))
+    }
+
+    async fn remove_from_instance_profiles(&self, role_name: &str) {
+        let req = self
+            .client
+            .list_instance_profiles_for_role(ListInstanceProfilesForRoleRequest {
+                role_name: role_name.to_string(),
+                max_items: Some(MAX_ITEMS),
+                ..Default::default()
+            });
+
+        for profile in handle_future_with_return!(req)
+            .map(|r| r.instance_profiles)
+            .unwrap_or_default()
+        {
+            let req = self.client.remove_role_from_instance_profile(
+                RemoveRoleFromInstanceProfileRequest {
+                    role_name: role_name.to_string(),
+                    instance_profile_name: profile.instance_profile_name,
+                },
+            );
+            handle_future!(req);
+        }
+    }
+
+    async fn delete_policies(&self, role_name: &str) {
+        let req = self
+            .client
+            .list_attached_role_policies(ListAttachedRolePoliciesRequest {
+                role_name: role_name.to_string(),
+                max_items: Some(MAX_ITEMS),
+                ..Default::default()
+            });
+        for policy_arn in handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.attached_policies)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|p| p.policy_arn)
+        {
+            let req = self.client.detach_role_policy(DetachRolePolicyRequest {
+                role_name: role_name.to_string(),
+                policy_arn,
+            });
+            handle_future!(req);
+        }
+
+        let req = self.client.list_role_policies(ListRolePoliciesRequest {
+            role_name: role_name.to_string(),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+        for policy_name in handle_future_with_return!(req)
+            .map(|r| r.policy_names)
+            .unwrap_or_default()
+        {
+            let req = self.client.delete_role_policy(DeleteRolePolicyRequest {
+                role_name: role_name.to_string(),
+                policy_name,
+            });
+            handle_future!(req);
+        }
+    }
+
+    /// A role can only be removed once it is removed from all the instance
+    /// profiles and all its policies are removed.
+    async fn delete_role(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let role_name = resource.id.as_str();
+
+            self.remove_from_instance_profiles(role_name).await;
+            self.delete_policies(role_name).await;
+
+            let req = self.client.delete_role(DeleteRoleRequest {
+                role_name: role_name.to_string(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for IamRoleClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized IAM role resource scanner");
+        let roles = self.get_roles().await?;
+
+        self.package_resources(roles).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        config: &ResourceConfig,
+    ) -> Option<bool> {
+        let unused = self.is_unused(resource, config).await;
+
+        if unused == Some(true) {
+            debug!(
+                resource = resource.id.as_str(),
+                "Role was not used within the max idle time."
+            );
+        }
+
+        unused
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_role(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "iam",
+        global: true,
+        builder: |cd, config, dry_run| Box::new(IamRoleClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use std::time::Duration;
+
+    fn create_client(body: &str) -> IamRoleClient {
+        IamRoleClient {
+            client: IamClient::new_with(
+                MockRequestDispatcher::default().with_body(body),
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    fn role(path: &str, name: &str) -> String {
+        format!(
+            "<member><Path>{}</Path><RoleName>{}</RoleName><RoleId>AROA{}</RoleId>
+            <Arn>arn:aws:iam::123456789012:role{}{}</Arn>
+            <CreateDate>2021-03-01T10:00:00Z</CreateDate></member>",
+            path, name, name, path, name
+        )
+    }
+
+    #[tokio::test]
+    async fn skip_roles_managed_by_aws() {
+        let client = create_client(&format!(
+            r#"<ListRolesResponse><ListRolesResult>
+                <IsTruncated>false</IsTruncated>
+                <Roles>{}{}{}</Roles>
+            </ListRolesResult>
+            <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+            </ListRolesResponse>"#,
+            role("/", "ci"),
+            role("/aws-service-role/", "AWSServiceRoleForSupport"),
+            role("/aws-reserved/sso.amazonaws.com/", "AWSReservedSSO_Admin")
+        ));
+
+        let roles = client.get_roles().await.unwrap();
+
+        assert_eq!(roles.len(), 1);
+        assert_eq!(roles[0].role_name, "ci");
+    }
+
+    #[tokio::test]
+    async fn unused_since_last_use() {
+        let client = create_client(&format!(
+            r#"<GetRoleResponse><GetRoleResult><Role>
+                <Path>/</Path><RoleName>ci</RoleName><RoleId>AROACI</RoleId>
+                <Arn>arn:aws:iam::123456789012:role/ci</Arn>
+                <CreateDate>2021-03-01T10:00:00Z</CreateDate>
+                <RoleLastUsed><LastUsedDate>{}</LastUsedDate></RoleLastUsed>
+            </Role></GetRoleResult>
+            <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+            </GetRoleResponse>"#,
+            chrono::Utc::now().to_rfc3339()
+        ));
+        let config = ResourceConfig {
+            max_idle_time: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let resource = Resource {
+            id: "ci".to_string(),
+            type_: ClientType::IamRole,
+            start_time: Some("2021-03-01T10:00:00Z".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(client.is_unused(&resource, &config).await, Some(false));
+    }
+}
@@ -0,0 +1,486 @@
+use crate::aws::iam_access_key::{access_key_resource_id, list_access_keys};
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_iam::{
+    DeactivateMFADeviceRequest, DeleteLoginProfileRequest, DeleteSSHPublicKeyRequest,
+    DeleteServiceSpecificCredentialRequest, DeleteSigningCertificateRequest,
+    DeleteUserPolicyRequest, DeleteUserRequest, DeleteVirtualMFADeviceRequest,
+    DetachUserPolicyRequest, GetAccessKeyLastUsedRequest, GetLoginProfileRequest, GetUserRequest,
+    Iam, IamClient, ListAttachedUserPoliciesRequest, ListGroupsForUserRequest,
+    ListMFADevicesRequest, ListSSHPublicKeysRequest, ListServiceSpecificCredentialsRequest,
+    ListSigningCertificatesRequest, ListUserPoliciesRequest, ListUserTagsRequest, ListUsersRequest,
+    RemoveUserFromGroupRequest, Tag, User,
+};
+use tracing::{debug, trace};
+
+const MAX_ITEMS: i64 = 1000;
+
+pub(crate) const CLIENT: Client = Client::new("iam_user");
+
+#[derive(Clone)]
+pub struct IamUserClient {
+    client: IamClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl IamUserClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        IamUserClient {
+            client: IamClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>> {
+        let mut next_token: Option<String> = None;
+        let mut users: Vec<User> = Vec::new();
+
+        loop {
+            let req = self.client.list_users(ListUsersRequest {
+                marker: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for user in result.users {
+                    users.push(user);
+                }
+
+                if result.marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(users)
+    }
+
+    async fn package_resources(&self, users: Vec<User>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for user in users {
+            let tags = self.list_tags(&user.user_name).await;
+
+            resources.push(Resource {
+                id: user.user_name,
+                arn: Some(user.arn),
+                type_: ClientType::IamUser,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(ResourceState::Available),
+                start_time: Some(user.create_date),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn list_tags(&self, user_name: &str) -> Option<Vec<Tag>> {
+        let req = self.client.list_user_tags(ListUserTagsRequest {
+            user_name: user_name.to_string(),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+
+        handle_future_with_return!(req).ok().map(|r| r.tags)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key),
+                    value: Some(tag.value),
+                })
+                .collect()
+        })
+    }
+
+    /// Checks if the user had no console (password) or API (access key)
+    /// activity within `max_idle_time`. Users that were never active are
+    /// checked against their creation date.
+    async fn is_inactive(&self, resource: &Resource, config: &ResourceConfig) -> Option<bool> {
+        let max_idle_time = config.max_idle_time.as_ref()?;
+        let mut last_activity: Vec<String> = Vec::new();
+
+        let req = self.client.get_user(GetUserRequest {
+            user_name: Some(resource.id.to_owned()),
+        });
+        if let Some(password_last_used) = handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.user.password_last_used)
+        {
+            last_activity.push(password_last_used);
+        }
+
+        for key in list_access_keys(&self.client, &resource.id).await {
+            if let Some(key_id) = key.access_key_id {
+                let req = self
+                    .client
+                    .get_access_key_last_used(GetAccessKeyLastUsedRequest {
+                        access_key_id: key_id,
+                    });
+
+                if let Some(last_used) = handle_future_with_return!(req)
+                    .ok()
+                    .and_then(|r| r.access_key_last_used)
+                {
+                    last_activity.push(last_used.last_used_date);
+                }
+            }
+        }
+
+        if last_activity.is_empty() {
+            last_activity.push(resource.start_time.clone()?);
+        }
+
+        Some(
+            last_activity
+                .iter()
+                .all(|ts| crate::util::is_ts_older_than(ts, max_idle_time)),
+        )
+    }
+
+    /// Access keys of the user are removed before the user
+    async fn get_dependencies(&self, resource: &Resource) -> Result<Vec<Resource>> {
+        Ok(list_access_keys(&self.client, &resource.id)
+            .await
+            .into_iter()
+            .filter_map(|key| key.access_key_id)
+            .map(|key_id| Resource {
+                id: access_key_resource_id(&resource.id, &key_id),
+                arn: None,
+                type_: ClientType::IamAccessKey,
+                region: self.region.clone(),
+                tags: None,
+                state: None,
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::DeleteDependent,
+                enforcement_reason: Some(EnforcementReason::Dependent),
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            })
+            .collect())
+    }
+
+    async fn delete_login_profile(&self, user_name: &str) {
+        // Users without console access do not have a login profile
+        if self
+            .client
+            .get_login_profile(GetLoginProfileRequest {
+                user_name: user_name.to_string(),
+            })
+            .await
+            .is_ok()
+        {
+            let req = self.client.delete_login_profile(DeleteLoginProfileRequest {
+                user_name: user_name.to_string(),
+            });
+            handle_future!(req);
+        }
+    }
+
+    async fn delete_mfa_devices(&self, user_name: &str) {
+        let req = self.client.list_mfa_devices(ListMFADevicesRequest {
+            user_name: Some(user_name.to_string()),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+
+        for device in handle_future_with_return!(req)
+            .map(|r| r.mfa_devices)
+            .unwrap_or_default()
+        {
+            let req = self
+                .client
+                .deactivate_mfa_device(DeactivateMFADeviceRequest {
+                    user_name: user_name.to_string(),
+                    serial_number: device.serial_number.clone(),
+                });
+            handle_future!(req);
+
+            // Hardware devices are only deactivated
+            if device.serial_number.starts_with("arn:") {
+                let req = self
+                    .client
+                    .delete_virtual_mfa_device(DeleteVirtualMFADeviceRequest {
+                        serial_number: device.serial_number,
+                    });
+                handle_future!(req);
+            }
+        }
+    }
+
+    async fn delete_credentials(&self, user_name: &str) {
+        let req = self.client.list_ssh_public_keys(ListSSHPublicKeysRequest {
+            user_name: Some(user_name.to_string()),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+        for key in handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.ssh_public_keys)
+            .unwrap_or_default()
+        {
+            let req = self
+                .client
+                .delete_ssh_public_key(DeleteSSHPublicKeyRequest {
+                    user_name: user_name.to_string(),
+                    ssh_public_key_id: key.ssh_public_key_id,
+                });
+            handle_future!(req);
+        }
+
+        let req = self
+            .client
+            .list_signing_certificates(ListSigningCertificatesRequest {
+                user_name: Some(user_name.to_string()),
+                max_items: Some(MAX_ITEMS),
+                ..Default::default()
+            });
+        for certificate in handle_future_with_return!(req)
+            .map(|r| r.certificates)
+            .unwrap_or_default()
+        {
+            let req = self
+                .client
+                .delete_signing_certificate(DeleteSigningCertificateRequest {
+                    user_name: Some(user_name.to_string()),
+                    certificate_id: certificate.certificate_id,
+                });
+            handle_future!(req);
+        }
+
+        let req =
+            self.client
+                .list_service_specific_credentials(ListServiceSpecificCredentialsRequest {
+                    user_name: Some(user_name.to_string()),
+                    ..Default::default()
+                });
+        for credential in handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.service_specific_credentials)
+            .unwrap_or_default()
+        {
+            let req = self.client.delete_service_specific_credential(
+                DeleteServiceSpecificCredentialRequest {
+                    user_name: Some(user_name.to_string()),
+                    service_specific_credential_id: credential.service_specific_credential_id,
+                },
+            );
+            handle_future!(req);
+        }
+    }
+
+    async fn remove_from_groups(&self, user_name: &str) {
+        let req = self.client.list_groups_for_user(ListGroupsForUserRequest {
+            user_name: user_name.to_string(),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+
+        for group in handle_future_with_return!(req)
+            .map(|r| r.groups)
+            .unwrap_or_default()
+        {
+            let req = self
+                .client
+                .remove_user_from_group(RemoveUserFromGroupRequest {
+                    user_name: user_name.to_string(),
+                    group_name: group.group_name,
+                });
+            handle_future!(req);
+        }
+    }
+
+    async fn delete_policies(&self, user_name: &str) {
+        let req = self
+            .client
+            .list_attached_user_policies(ListAttachedUserPoliciesRequest {
+                user_name: user_name.to_string(),
+                max_items: Some(MAX_ITEMS),
+                ..Default::default()
+            });
+        for policy_arn in handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.attached_policies)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|p| p.policy_arn)
+        {
+            let req = self.client.detach_user_policy(DetachUserPolicyRequest {
+                user_name: user_name.to_string(),
+                policy_arn,
+            });
+            handle_future!(req);
+        }
+
+        let req = self.client.list_user_policies(ListUserPoliciesRequest {
+            user_name: user_name.to_string(),
+            max_items: Some(MAX_ITEMS),
+            ..Default::default()
+        });
+        for policy_name in handle_future_with_return!(req)
+            .map(|r| r.policy_names)
+            .unwrap_or_default()
+        {
+            let req = self.client.delete_user_policy(DeleteUserPolicyRequest {
+                user_name: user_name.to_string(),
+                policy_name,
+            });
+            handle_future!(req);
+        }
+    }
+
+    /// A user can only be removed once everything attached to it is removed.
+    /// Access keys are removed as dependencies of the user.
+    async fn delete_user(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let user_name = resource.id.as_str();
+
+            self.delete_login_profile(user_name).await;
+            self.delete_mfa_devices(user_name).await;
+            self.delete_credentials(user_name).await;
+            self.remove_from_groups(user_name).await;
+            self.delete_policies(user_name).await;
+
+            let req = self.client.delete_user(DeleteUserRequest {
+                user_name: user_name.to_string(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for IamUserClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized IAM user resource scanner");
+        let users = self.get_users().await?;
+
+        self.package_resources(users).await
+    }
+
+    async fn dependencies(&self, resource: &Resource) -> Option<Vec<Resource>> {
+        self.get_dependencies(resource).await.ok()
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        config: &ResourceConfig,
+    ) -> Option<bool> {
+        let inactive = self.is_inactive(resource, config).await;
+
+        if inactive == Some(true) {
+            debug!(
+                resource = resource.id.as_str(),
+                "User had no activity within the max idle time."
+            );
+        }
+
+        inactive
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_user(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "iam",
+        global: true,
+        builder: |cd, config, dry_run| Box::new(IamUserClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use std::time::Duration;
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> IamUserClient {
+        IamUserClient {
+            client: IamClient::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    fn config() -> ResourceConfig {
+        ResourceConfig {
+            max_idle_time: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        }
+    }
+
+    fn resource() -> Resource {
+        Resource {
+            id: "alice".to_string(),
+            type_: ClientType::IamUser,
+            start_time: Some("2021-03-01T10:00:00Z".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn active_using_the_console() {
+        let client = create_client(MockRequestDispatcher::default().with_body(&format!(
+            r#"<GetUserResponse><GetUserResult><User>
+                <Path>/</Path><UserName>alice</UserName><UserId>AIDAALICE</UserId>
+                <Arn>arn:aws:iam::123456789012:user/alice</Arn>
+                <CreateDate>2021-03-01T10:00:00Z</CreateDate>
+                <PasswordLastUsed>{}</PasswordLastUsed>
+            </User></GetUserResult>
+            <ResponseMetadata><RequestId>1</RequestId></ResponseMetadata>
+            </GetUserResponse>"#,
+            chrono::Utc::now().to_rfc3339()
+        )));
+
+        assert_eq!(client.is_inactive(&resource(), &config()).await, Some(false));
+    }
+
+    #[tokio::test]
+    async fn inactive_since_creation() {
+        let client = create_client(MockRequestDispatcher::default());
+
+        assert_eq!(client.is_inactive(&resource(), &config()).await, Some(true));
+    }
+}
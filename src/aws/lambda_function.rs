@@ -0,0 +1,303 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use chrono::DateTime;
+use rusoto_core::Region;
+use rusoto_lambda::{
+    DeleteFunctionRequest, FunctionConfiguration, Lambda, LambdaClient, ListFunctionsRequest,
+    ListTagsRequest, PutFunctionConcurrencyRequest,
+};
+use std::collections::HashMap;
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("lambda_function");
+
+/// Format of the last modification time, for instance
+/// `2019-09-18T12:20:45.123+0000`, whose offset is not RFC 3339
+const LAST_MODIFIED_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f%z";
+
+#[derive(Clone)]
+pub struct LambdaFunctionClient {
+    client: LambdaClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl LambdaFunctionClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        LambdaFunctionClient {
+            client: LambdaClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn package_resources(
+        &self,
+        functions: Vec<FunctionConfiguration>,
+    ) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for function in functions {
+            // Lambda@Edge replicas are removed by AWS along with their master
+            if function.master_arn.is_some() {
+                continue;
+            }
+
+            let function_name = function.function_name.unwrap_or_default();
+            let tags = match function.function_arn {
+                Some(ref arn) => self.list_tags(arn).await,
+                None => None,
+            };
+            // Container image functions do not have a runtime
+            let runtime = function.runtime.or(function.package_type);
+
+            resources.push(Resource {
+                id: function_name,
+                arn: function.function_arn,
+                type_: ClientType::LambdaFunction,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(ResourceState::Available),
+                start_time: function.last_modified.as_deref().and_then(to_rfc3339),
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: runtime.map(|r| vec![r]),
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_functions(&self) -> Result<Vec<FunctionConfiguration>> {
+        let mut next_token: Option<String> = None;
+        let mut functions: Vec<FunctionConfiguration> = Vec::new();
+
+        loop {
+            let req = self.client.list_functions(ListFunctionsRequest {
+                marker: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                if let Some(fs) = result.functions {
+                    for f in fs {
+                        functions.push(f);
+                    }
+                }
+
+                if result.next_marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(functions)
+    }
+
+    async fn list_tags(&self, arn: &str) -> Option<HashMap<String, String>> {
+        let req = self.client.list_tags(ListTagsRequest {
+            resource: arn.to_owned(),
+        });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tags)
+            .unwrap_or_default()
+    }
+
+    fn package_tags(&self, tags: Option<HashMap<String, String>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|(key, value)| NTag {
+                    key: Some(key),
+                    value: Some(value),
+                })
+                .collect()
+        })
+    }
+
+    /// Lambda functions cannot be stopped, instead the reserved concurrency of
+    /// the function is set to zero which throttles all the invocations.
+    async fn stop_function(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Stopping");
+
+        if !self.dry_run {
+            let req = self
+                .client
+                .put_function_concurrency(PutFunctionConcurrencyRequest {
+                    function_name: resource.id.to_owned(),
+                    reserved_concurrent_executions: 0,
+                });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_function(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self.client.delete_function(DeleteFunctionRequest {
+                function_name: resource.id.to_owned(),
+                ..Default::default()
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for LambdaFunctionClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Lambda function resource scanner");
+        let functions = self.get_functions().await?;
+
+        self.package_resources(functions).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, resource: &Resource) -> Result<()> {
+        self.stop_function(resource).await
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_function(resource).await
+    }
+}
+
+/// Converts the last modification time of a function to RFC 3339
+fn to_rfc3339(last_modified: &str) -> Option<String> {
+    DateTime::parse_from_str(last_modified, LAST_MODIFIED_FORMAT)
+        .ok()
+        .map(|time| time.to_rfc3339())
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "lambda",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(LambdaFunctionClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/Lambda",
+            dimensions: &[("FunctionName", "{id}")],
+            sparse: true,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_core::signature::SignedRequestPayload;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> LambdaFunctionClient {
+        LambdaFunctionClient {
+            client: LambdaClient::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn convert_last_modified() {
+        assert_eq!(
+            to_rfc3339("2019-09-18T12:20:45.123+0000").as_deref(),
+            Some("2019-09-18T12:20:45.123+00:00")
+        );
+        assert_eq!(to_rfc3339("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn package_functions() {
+        let client = create_client(MockRequestDispatcher::default());
+        let functions = vec![
+            FunctionConfiguration {
+                function_name: Some("api".to_string()),
+                runtime: Some("python3.8".to_string()),
+                last_modified: Some("2019-09-18T12:20:45.123+0000".to_string()),
+                ..Default::default()
+            },
+            FunctionConfiguration {
+                function_name: Some("image".to_string()),
+                package_type: Some("Image".to_string()),
+                ..Default::default()
+            },
+            FunctionConfiguration {
+                function_name: Some("us-east-1.edge".to_string()),
+                master_arn: Some("arn:aws:lambda:us-east-1:123456789012:function:edge".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let resources = client.package_resources(functions).await.unwrap();
+
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].id, "api");
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2019-09-18T12:20:45.123+00:00")
+        );
+        assert_eq!(resources[0].resource_type, Some(vec!["python3.8".to_string()]));
+        assert_eq!(resources[1].id, "image");
+        assert_eq!(resources[1].start_time, None);
+        assert_eq!(resources[1].resource_type, Some(vec!["Image".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn stop_by_throttling_invocations() {
+        let client = create_client(MockRequestDispatcher::default().with_request_checker(
+            |request| {
+                assert_eq!(request.method, "PUT");
+                assert_eq!(request.path, "/2017-10-31/functions/api/concurrency");
+                match request.payload {
+                    Some(SignedRequestPayload::Buffer(ref body)) => assert_eq!(
+                        serde_json::from_slice::<serde_json::Value>(body).unwrap()
+                            ["ReservedConcurrentExecutions"],
+                        0
+                    ),
+                    _ => panic!("missing body"),
+                }
+            },
+        ));
+        let resource = Resource {
+            id: "api".to_string(),
+            type_: ClientType::LambdaFunction,
+            ..Default::default()
+        };
+
+        client.stop(&resource).await.unwrap();
+    }
+}
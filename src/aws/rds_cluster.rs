@@ -1,19 +1,25 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
 use async_trait::async_trait;
 use rusoto_core::Region;
 use rusoto_rds::{
-    DBCluster, DBInstance, DeleteDBClusterMessage, DescribeDBClustersMessage,
-    DescribeDBInstancesMessage, ListTagsForResourceMessage, ModifyDBClusterMessage, Rds, RdsClient,
-    StopDBClusterMessage, Tag,
+    AddTagsToResourceMessage, DBCluster, DBInstance, DeleteDBClusterMessage,
+    DescribeDBClustersMessage, DescribeDBInstancesMessage, ListTagsForResourceMessage,
+    ModifyDBClusterMessage, Rds, RdsClient, StopDBClusterMessage, Tag,
 };
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("rds_aurora");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateDBCluster";
+
 #[derive(Clone)]
 pub struct RdsClusterClient {
     client: RdsClient,
@@ -69,6 +75,7 @@ impl RdsClusterClient {
                 ),
                 state: Some(ResourceState::from_str(cluster.status.as_ref().unwrap()).unwrap()),
                 start_time: cluster.cluster_create_time.take(),
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 dependencies: None,
@@ -128,6 +135,7 @@ impl RdsClusterClient {
                         .unwrap(),
                 ),
                 start_time: db_instance.instance_create_time,
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 dependencies: None,
@@ -300,6 +308,29 @@ impl NukerClient for RdsClusterClient {
         self.delete_cluster(resource).await
     }
 
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Tagging");
+
+        if !self.dry_run {
+            if let Some(ref arn) = resource.arn {
+                self.client
+                    .add_tags_to_resource(AddTagsToResourceMessage {
+                        resource_name: arn.to_owned(),
+                        tags: tags
+                            .iter()
+                            .map(|tag| Tag {
+                                key: tag.key.clone(),
+                                value: tag.value.clone(),
+                            })
+                            .collect(),
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn additional_filters(
         &self,
         _resource: &Resource,
@@ -308,3 +339,21 @@ impl NukerClient for RdsClusterClient {
         None
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &["rds_cluster"],
+        service: "rds",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(RdsClusterClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: Some(CwMetrics {
+            namespace: "AWS/RDS",
+            dimensions: &[("DBClusterIdentifier", "{id}")],
+            sparse: false,
+        }),
+    }
+}
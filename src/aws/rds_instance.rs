@@ -1,15 +1,16 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
 use async_trait::async_trait;
 use rusoto_core::Region;
 use rusoto_rds::{
-    DBInstance, DeleteDBInstanceMessage, DescribeDBInstancesMessage, Filter,
-    ListTagsForResourceMessage, ModifyDBInstanceMessage, Rds, RdsClient, StopDBInstanceMessage,
-    Tag,
+    AddTagsToResourceMessage, DBInstance, DeleteDBInstanceMessage, DescribeDBInstancesMessage,
+    Filter, ListTagsForResourceMessage, ModifyDBInstanceMessage, Rds, RdsClient,
+    StopDBInstanceMessage, Tag,
 };
 use std::str::FromStr;
 use tracing::{debug, trace};
@@ -17,6 +18,11 @@ use tracing::{debug, trace};
 const AURORA_POSTGRES_ENGINE: &str = "aurora-postgresql";
 const AURORA_MYSQL_ENGINE: &str = "aurora-mysql";
 
+pub(crate) const CLIENT: Client = Client::new("rds");
+
+/// CloudTrail event recording the creation of the resources
+const CREATE_EVENT: &str = "CreateDBInstance";
+
 #[derive(Clone)]
 pub struct RdsInstanceClient {
     client: RdsClient,
@@ -74,6 +80,7 @@ impl RdsInstanceClient {
                     ResourceState::from_str(instance.db_instance_status.as_ref().unwrap()).unwrap(),
                 ),
                 start_time: instance.instance_create_time.take(),
+                created: None,
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 dependencies: None,
@@ -217,6 +224,29 @@ impl NukerClient for RdsInstanceClient {
         self.delete_instance(resource).await
     }
 
+    async fn tag(&self, resource: &Resource, tags: &[NTag]) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Tagging");
+
+        if !self.dry_run {
+            if let Some(ref arn) = resource.arn {
+                self.client
+                    .add_tags_to_resource(AddTagsToResourceMessage {
+                        resource_name: arn.to_owned(),
+                        tags: tags
+                            .iter()
+                            .map(|tag| Tag {
+                                key: tag.key.clone(),
+                                value: tag.value.clone(),
+                            })
+                            .collect(),
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn additional_filters(
         &self,
         _resource: &Resource,
@@ -225,3 +255,21 @@ impl NukerClient for RdsInstanceClient {
         None
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &["rds_instance"],
+        service: "rds",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(RdsInstanceClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: true,
+        create_event: Some(CREATE_EVENT),
+        metrics: Some(CwMetrics {
+            namespace: "AWS/RDS",
+            dimensions: &[("DBInstanceIdentifier", "{id}")],
+            sparse: false,
+        }),
+    }
+}
@@ -0,0 +1,60 @@
+use crate::Result;
+use rusoto_core::{Client, Region};
+use rusoto_ec2::{DescribeRegionsRequest, Ec2, Ec2Client};
+use std::str::FromStr;
+use tracing::debug;
+
+/// Regions that are not enabled (opted into) for the account
+const NOT_OPTED_IN_STATUS: &str = "not-opted-in";
+
+/// Region named `name`, regions unknown to rusoto are mapped to a custom
+/// region without an endpoint, the standard endpoint of each service is
+/// resolved by `EndpointsConfig::region`.
+pub fn region_from_name(name: &str) -> Region {
+    Region::from_str(name).unwrap_or_else(|_| Region::Custom {
+        name: name.to_string(),
+        endpoint: String::new(),
+    })
+}
+
+#[derive(Clone)]
+pub struct RegionService {
+    pub client: Ec2Client,
+}
+
+impl RegionService {
+    pub fn new(client: &Client, region: &Region) -> Result<Self> {
+        Ok(RegionService {
+            client: Ec2Client::new_with_client(client.clone(), region.clone()),
+        })
+    }
+
+    /// Discovers the regions that are enabled for the account, regions that
+    /// require opting in are only returned if the account opted into them.
+    pub async fn get_enabled_regions(&self) -> Result<Vec<Region>> {
+        let mut regions: Vec<Region> = Vec::new();
+        let result = self
+            .client
+            .describe_regions(DescribeRegionsRequest {
+                all_regions: Some(true),
+                ..Default::default()
+            })
+            .await?;
+
+        for region in result.regions.unwrap_or_default() {
+            let name = match region.region_name {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if region.opt_in_status.as_deref() == Some(NOT_OPTED_IN_STATUS) {
+                debug!(region = name.as_str(), "Skipping region not opted into");
+                continue;
+            }
+
+            regions.push(region_from_name(&name));
+        }
+
+        Ok(regions)
+    }
+}
@@ -0,0 +1,349 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_route53::{
+    Change, ChangeBatch, ChangeResourceRecordSetsRequest, DeleteHostedZoneRequest,
+    GetHostedZoneRequest, HostedZone, ListHostedZonesRequest, ListResourceRecordSetsRequest,
+    ListTagsForResourceRequest, ResourceRecordSet, Route53, Route53Client, Tag,
+};
+use tracing::{debug, trace};
+
+const HOSTED_ZONE_PREFIX: &str = "/hostedzone/";
+const HOSTED_ZONE_RESOURCE_TYPE: &str = "hostedzone";
+/// Record sets created along with the zone, these are removed with the zone
+const DEFAULT_RECORD_TYPES: [&str; 2] = ["NS", "SOA"];
+const MAX_CHANGES: usize = 100;
+
+pub(crate) const CLIENT: Client = Client::new("route53_hosted_zone");
+
+#[derive(Clone)]
+pub struct Route53HostedZoneClient {
+    client: Route53Client,
+    region: Region,
+    dry_run: bool,
+}
+
+impl Route53HostedZoneClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        Route53HostedZoneClient {
+            client: Route53Client::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    /// Zones created and managed by other services (e.g. Cloud Map) are
+    /// skipped, those are removed along with the owning resource.
+    async fn get_hosted_zones(&self) -> Result<Vec<HostedZone>> {
+        let mut next_token: Option<String> = None;
+        let mut zones: Vec<HostedZone> = Vec::new();
+
+        loop {
+            let req = self.client.list_hosted_zones(ListHostedZonesRequest {
+                marker: next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for zone in result.hosted_zones {
+                    if zone.linked_service.is_none() {
+                        zones.push(zone);
+                    }
+                }
+
+                if !result.is_truncated || result.next_marker.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_marker;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(zones)
+    }
+
+    async fn package_resources(&self, zones: Vec<HostedZone>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for zone in zones {
+            let zone_id = zone.id.trim_start_matches(HOSTED_ZONE_PREFIX).to_string();
+            let tags = self.get_tags(&zone_id).await;
+            let private_zone = zone.config.and_then(|c| c.private_zone) == Some(true);
+
+            resources.push(Resource {
+                arn: Some(format!("arn:aws:route53:::hostedzone/{}", zone_id)),
+                id: zone_id,
+                type_: ClientType::Route53HostedZone,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(ResourceState::Available),
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                resource_type: Some(vec![if private_zone {
+                    "private".to_string()
+                } else {
+                    "public".to_string()
+                }]),
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn get_tags(&self, zone_id: &str) -> Option<Vec<Tag>> {
+        let req = self
+            .client
+            .list_tags_for_resource(ListTagsForResourceRequest {
+                resource_id: zone_id.to_string(),
+                resource_type: HOSTED_ZONE_RESOURCE_TYPE.to_string(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .and_then(|r| r.resource_tag_set.tags)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.into_iter()
+                .map(|tag| NTag {
+                    key: tag.key,
+                    value: tag.value,
+                })
+                .collect()
+        })
+    }
+
+    /// A zone is considered empty when it only has the default NS and SOA
+    /// record sets.
+    async fn is_empty(&self, zone_id: &str) -> Option<bool> {
+        let req = self.client.get_hosted_zone(GetHostedZoneRequest {
+            id: zone_id.to_string(),
+        });
+        let count = handle_future_with_return!(req)
+            .ok()?
+            .hosted_zone
+            .resource_record_set_count?;
+
+        Some(count <= DEFAULT_RECORD_TYPES.len() as i64)
+    }
+
+    async fn get_record_sets(&self, zone_id: &str) -> Vec<ResourceRecordSet> {
+        let mut next_name: Option<String> = None;
+        let mut next_type: Option<String> = None;
+        let mut next_identifier: Option<String> = None;
+        let mut record_sets: Vec<ResourceRecordSet> = Vec::new();
+
+        loop {
+            let req = self
+                .client
+                .list_resource_record_sets(ListResourceRecordSetsRequest {
+                    hosted_zone_id: zone_id.to_string(),
+                    start_record_name: next_name,
+                    start_record_type: next_type,
+                    start_record_identifier: next_identifier,
+                    ..Default::default()
+                });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for record_set in result.resource_record_sets {
+                    record_sets.push(record_set);
+                }
+
+                if !result.is_truncated {
+                    break;
+                } else {
+                    next_name = result.next_record_name;
+                    next_type = result.next_record_type;
+                    next_identifier = result.next_record_identifier;
+                }
+            } else {
+                break;
+            }
+        }
+
+        record_sets
+    }
+
+    /// A hosted zone can only be deleted once all the record sets other than
+    /// the apex NS and SOA records are removed.
+    async fn delete_hosted_zone(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let zone_id = resource.id.as_str();
+            let mut apex: Option<String> = None;
+            let record_sets = self.get_record_sets(zone_id).await;
+
+            // The SOA record only exists at the apex of the zone
+            for record_set in &record_sets {
+                if record_set.type_ == "SOA" {
+                    apex = Some(record_set.name.clone());
+                }
+            }
+
+            let changes: Vec<Change> = record_sets
+                .into_iter()
+                .filter(|r| {
+                    !(Some(&r.name) == apex.as_ref()
+                        && DEFAULT_RECORD_TYPES.contains(&r.type_.as_str()))
+                })
+                .map(|r| Change {
+                    action: "DELETE".to_string(),
+                    resource_record_set: r,
+                })
+                .collect();
+
+            for batch in changes.chunks(MAX_CHANGES) {
+                let req =
+                    self.client
+                        .change_resource_record_sets(ChangeResourceRecordSetsRequest {
+                            hosted_zone_id: zone_id.to_string(),
+                            change_batch: ChangeBatch {
+                                changes: batch.to_vec(),
+                                comment: None,
+                            },
+                        });
+                handle_future_with_return!(req)?;
+            }
+
+            let req = self.client.delete_hosted_zone(DeleteHostedZoneRequest {
+                id: zone_id.to_string(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for Route53HostedZoneClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Route53 hosted zone resource scanner");
+        let zones = self.get_hosted_zones().await?;
+
+        self.package_resources(zones).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        let empty = self.is_empty(&resource.id).await;
+
+        if empty == Some(true) {
+            debug!(
+                resource = resource.id.as_str(),
+                "Hosted zone has no record sets."
+            );
+        }
+
+        empty
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_hosted_zone(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "route53",
+        global: true,
+        builder: |cd, config, dry_run| Box::new(Route53HostedZoneClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use rusoto_route53::HostedZoneConfig;
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> Route53HostedZoneClient {
+        Route53HostedZoneClient {
+            client: Route53Client::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    fn get_hosted_zone(record_set_count: i64) -> MockRequestDispatcher {
+        MockRequestDispatcher::default().with_body(&format!(
+            r#"<GetHostedZoneResponse><HostedZone>
+                <Id>/hostedzone/Z1D633PJN98FT9</Id>
+                <Name>example.com.</Name>
+                <CallerReference>example</CallerReference>
+                <ResourceRecordSetCount>{}</ResourceRecordSetCount>
+            </HostedZone></GetHostedZoneResponse>"#,
+            record_set_count
+        ))
+    }
+
+    #[tokio::test]
+    async fn package_zones() {
+        let client = create_client(MockRequestDispatcher::default());
+        let zones = vec![HostedZone {
+            id: "/hostedzone/Z1D633PJN98FT9".to_string(),
+            name: "internal.example.com.".to_string(),
+            config: Some(HostedZoneConfig {
+                private_zone: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+
+        let resources = client.package_resources(zones).await.unwrap();
+
+        assert_eq!(resources[0].id, "Z1D633PJN98FT9");
+        assert_eq!(
+            resources[0].arn.as_deref(),
+            Some("arn:aws:route53:::hostedzone/Z1D633PJN98FT9")
+        );
+        assert_eq!(resources[0].resource_type, Some(vec!["private".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn empty_with_default_record_sets() {
+        assert_eq!(
+            create_client(get_hosted_zone(2))
+                .is_empty("Z1D633PJN98FT9")
+                .await,
+            Some(true)
+        );
+        assert_eq!(
+            create_client(get_hosted_zone(3))
+                .is_empty("Z1D633PJN98FT9")
+                .await,
+            Some(false)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::{CwMetrics, ResourceType};
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -12,6 +13,8 @@ use rusoto_redshift::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("rs_cluster");
+
 #[derive(Clone)]
 pub struct RsClusterClient {
     client: RedshiftClient,
@@ -63,6 +66,7 @@ impl RsClusterClient {
                 tags: self.package_tags(cluster.tags),
                 state: ResourceState::from_str(cluster.cluster_status.as_deref().unwrap()).ok(),
                 start_time: cluster.cluster_create_time,
+                created: None,
                 resource_type: cluster.node_type.map(|t| vec![t]),
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
@@ -169,3 +173,21 @@ impl NukerClient for RsClusterClient {
         self.terminate_resource(resource.id.to_owned()).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "redshift",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(RsClusterClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: Some(CwMetrics {
+            namespace: "AWS/Redshift",
+            dimensions: &[("ClusterIdentifier", "{id}")],
+            sparse: false,
+        }),
+    }
+}
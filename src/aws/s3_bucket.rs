@@ -1,13 +1,14 @@
+use crate::registry::ResourceType;
 use crate::{
     aws::ClientDetails,
-    client::{ClientType, NukerClient},
-    config::ResourceConfig,
+    client::{Client, ClientType, NukerClient},
+    config::{EndpointsConfig, ResourceConfig},
     handle_future_with_return,
-    resource::{EnforcementState, NTag, Resource, ResourceState},
+    resource::{Creation, EnforcementState, NTag, Resource, ResourceState},
     Error, Result,
 };
 use async_trait::async_trait;
-use rusoto_core::Region;
+use rusoto_core::{Client as RClient, Region};
 use rusoto_s3::{
     Bucket, Delete, DeleteBucketPolicyRequest, DeleteBucketRequest, DeleteObjectsRequest,
     GetBucketAclRequest, GetBucketLocationRequest, GetBucketPolicyRequest,
@@ -23,10 +24,14 @@ static S3_PUBLIC_GROUPS: [&str; 2] = [
     "http://acs.amazonaws.com/groups/global/AllUsers",
 ];
 
+pub(crate) const CLIENT: Client = Client::new("s3_bucket");
+
 #[derive(Clone)]
 pub struct S3BucketClient {
     client: S3Client,
-    region: Region,
+    rclient: RClient,
+    regions: Vec<Region>,
+    endpoints: EndpointsConfig,
     account_num: String,
     config: ResourceConfig,
     dry_run: bool,
@@ -36,29 +41,51 @@ impl S3BucketClient {
     pub fn new(cd: &ClientDetails, config: &ResourceConfig, dry_run: bool) -> Self {
         S3BucketClient {
             client: S3Client::new_with_client(cd.client.clone(), cd.region.clone()),
-            region: cd.region.clone(),
+            rclient: cd.client.clone(),
+            regions: cd.regions.clone(),
+            endpoints: cd.endpoints.clone(),
             account_num: cd.account_number.clone(),
             config: config.clone(),
             dry_run,
         }
     }
 
-    async fn package_resources(&self, buckets: Vec<Bucket>) -> Vec<Resource> {
+    /// Buckets are listed once for the account, but the bucket level
+    /// operations have to be sent to the region the bucket is located in.
+    fn for_region(&self, region: &Region) -> S3BucketClient {
+        let region = self
+            .endpoints
+            .region(ClientType::S3Bucket.service(), region);
+
+        S3BucketClient {
+            client: S3Client::new_with_client(self.rclient.clone(), region),
+            ..self.clone()
+        }
+    }
+
+    async fn package_resources(&self, buckets: Vec<(Bucket, Region)>) -> Vec<Resource> {
         let mut resources: Vec<Resource> = Vec::new();
 
-        for bucket in buckets {
+        for (bucket, region) in buckets {
             let bucket_name = bucket.name.unwrap();
             let arn = format!("arn:aws:s3:::{}", bucket_name);
-            let tags = self.get_tags_for_bucket(&bucket_name).await;
+            let tags = self
+                .for_region(&region)
+                .get_tags_for_bucket(&bucket_name)
+                .await;
 
             resources.push(Resource {
                 id: bucket_name,
                 arn: Some(arn),
                 type_: ClientType::S3Bucket,
-                region: self.region.clone(),
+                region,
                 tags: self.package_tags(tags),
                 state: Some(ResourceState::Available),
                 start_time: None,
+                created: bucket
+                    .creation_date
+                    .as_deref()
+                    .and_then(|date| Creation::from_time(date, self.config.min_age.as_ref())),
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
                 resource_type: None,
@@ -110,9 +137,11 @@ impl S3BucketClient {
         }
     }
 
-    async fn get_buckets(&self) -> Result<Vec<Bucket>> {
+    /// Lists the buckets of the account along with the region they are
+    /// located in, only buckets in the scanned regions are considered.
+    async fn get_buckets(&self) -> Result<Vec<(Bucket, Region)>> {
         let result = self.client.list_buckets().await?;
-        let mut buckets: Vec<Bucket> = Vec::new();
+        let mut buckets: Vec<(Bucket, Region)> = Vec::new();
 
         for bucket in result.buckets.unwrap_or_default() {
             match self
@@ -124,24 +153,14 @@ impl S3BucketClient {
                 .await
             {
                 Ok(bucket_loc_res) => {
-                    let bucket_loc = if !bucket_loc_res
-                        .location_constraint
-                        .as_ref()
-                        .unwrap()
-                        .is_empty()
-                    {
-                        bucket_loc_res
-                            .location_constraint
-                            .as_ref()
-                            .unwrap()
-                            .as_str()
-                    } else {
-                        Region::UsEast1.name()
+                    let bucket_loc = match bucket_loc_res.location_constraint.as_deref() {
+                        Some(loc) if !loc.is_empty() => loc,
+                        _ => Region::UsEast1.name(),
                     };
-                    let region = self.region.name();
 
-                    if bucket_loc == region {
-                        buckets.push(bucket);
+                    if let Some(region) = self.regions.iter().find(|r| r.name() == bucket_loc) {
+                        let region = region.clone();
+                        buckets.push((bucket, region));
                     }
                 }
                 Err(err) => {
@@ -610,7 +629,11 @@ impl NukerClient for S3BucketClient {
                 "Bucket naming is not DNS compliant."
             );
             return Some(true);
-        } else if self.is_bucket_public(&resource.id).await {
+        } else if self
+            .for_region(&resource.region)
+            .is_bucket_public(&resource.id)
+            .await
+        {
             debug!(
                 resource = resource.id.as_str(),
                 "Bucket is publicly accessible."
@@ -622,10 +645,28 @@ impl NukerClient for S3BucketClient {
     }
 
     async fn stop(&self, resource: &Resource) -> Result<()> {
-        self.delete_bucket(&resource.id).await
+        self.for_region(&resource.region)
+            .delete_bucket(&resource.id)
+            .await
     }
 
     async fn delete(&self, resource: &Resource) -> Result<()> {
-        self.delete_bucket(&resource.id).await
+        self.for_region(&resource.region)
+            .delete_bucket(&resource.id)
+            .await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &["s3"],
+        service: "s3",
+        global: true,
+        builder: |cd, config, dry_run| Box::new(S3BucketClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
     }
 }
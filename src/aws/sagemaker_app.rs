@@ -0,0 +1,310 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use rusoto_core::Region;
+use rusoto_sagemaker::{
+    AppDetails, DeleteAppRequest, DescribeAppRequest, DescribeAppResponse, ListAppsRequest,
+    ListTagsInput, SageMaker, SageMakerClient, Tag,
+};
+use std::str::FromStr;
+use tracing::{debug, trace};
+
+/// Kernel gateway apps run the notebook kernels of a Studio user on dedicated
+/// instances, the Jupyter server apps are left alone.
+const KERNEL_GATEWAY: &str = "KernelGateway";
+
+pub(crate) const CLIENT: Client = Client::new("sagemaker_app");
+
+#[derive(Clone)]
+pub struct SagemakerAppClient {
+    client: SageMakerClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl SagemakerAppClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        SagemakerAppClient {
+            client: SageMakerClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_apps(&self) -> Result<Vec<AppDetails>> {
+        let mut next_token: Option<String> = None;
+        let mut apps: Vec<AppDetails> = Vec::new();
+
+        loop {
+            let req = self.client.list_apps(ListAppsRequest {
+                next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for app in result.apps.unwrap_or_default() {
+                    if app.app_type.as_deref() == Some(KERNEL_GATEWAY) {
+                        apps.push(app);
+                    }
+                }
+
+                if result.next_token.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_token;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(apps)
+    }
+
+    async fn package_resources(&self, apps: Vec<AppDetails>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for app in apps {
+            // Apps are only unique within a domain and user profile
+            let app_id = format!(
+                "{}/{}/{}",
+                app.domain_id.unwrap_or_default(),
+                app.user_profile_name.unwrap_or_default(),
+                app.app_name.unwrap_or_default()
+            );
+            let details = self.describe_app(&app_id).await;
+            let arn = details.as_ref().and_then(|d| d.app_arn.clone());
+            let tags = match arn {
+                Some(ref arn) => self.get_tags(arn).await,
+                None => None,
+            };
+
+            resources.push(Resource {
+                id: app_id,
+                arn,
+                type_: ClientType::SagemakerApp,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: ResourceState::from_str(app.status.as_deref().unwrap_or_default()).ok(),
+                start_time: app
+                    .creation_time
+                    .map(|ct| Utc.timestamp(ct as i64, 0).to_rfc3339()),
+                created: None,
+                resource_type: details
+                    .and_then(|d| d.resource_spec)
+                    .and_then(|r| r.instance_type)
+                    .map(|t| vec![t]),
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                dependencies: None,
+                termination_protection: None,
+            })
+        }
+
+        Ok(resources)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key.clone()),
+                    value: Some(tag.value.clone()),
+                })
+                .collect()
+        })
+    }
+
+    async fn get_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self.client.list_tags(ListTagsInput {
+            resource_arn: arn.to_string(),
+            ..Default::default()
+        });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tags)
+            .unwrap_or_default()
+    }
+
+    async fn describe_app(&self, app_id: &str) -> Option<DescribeAppResponse> {
+        let req = self.client.describe_app(to_app_request(app_id)?);
+
+        handle_future_with_return!(req).ok()
+    }
+
+    /// Checks if the last user activity on the app (or its creation, when the
+    /// app was never used) is older than `max_idle_time`.
+    async fn is_idle(&self, resource: &Resource, config: &ResourceConfig) -> Option<bool> {
+        let max_idle_time = config.max_idle_time.as_ref()?;
+        let app = self.describe_app(&resource.id).await?;
+        let last_activity = app
+            .last_user_activity_timestamp
+            .or(app.creation_time)
+            .map(|ts| Utc.timestamp(ts as i64, 0).to_rfc3339())?;
+
+        Some(crate::util::is_ts_older_than(&last_activity, max_idle_time))
+    }
+
+    async fn delete_app(&self, app_id: &str) -> Result<()> {
+        debug!(resource = app_id, "Deleting");
+
+        if !self.dry_run {
+            if let Some(app) = to_app_request(app_id) {
+                let req = self.client.delete_app(DeleteAppRequest {
+                    domain_id: app.domain_id,
+                    user_profile_name: app.user_profile_name,
+                    app_type: app.app_type,
+                    app_name: app.app_name,
+                });
+                handle_future!(req);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the id of an app `<domain_id>/<user_profile_name>/<app_name>` back
+/// into its parts.
+fn to_app_request(app_id: &str) -> Option<DescribeAppRequest> {
+    let mut parts = app_id.splitn(3, '/');
+
+    Some(DescribeAppRequest {
+        domain_id: parts.next()?.to_string(),
+        user_profile_name: parts.next()?.to_string(),
+        app_name: parts.next()?.to_string(),
+        app_type: KERNEL_GATEWAY.to_string(),
+    })
+}
+
+#[async_trait]
+impl NukerClient for SagemakerAppClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Sagemaker app resource scanner");
+        let apps = self.get_apps().await?;
+
+        self.package_resources(apps).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        resource: &Resource,
+        config: &ResourceConfig,
+    ) -> Option<bool> {
+        let idle = self.is_idle(resource, config).await;
+
+        if idle == Some(true) {
+            debug!(
+                resource = resource.id.as_str(),
+                "App exceeded the max idle time."
+            );
+        }
+
+        idle
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_app(resource.id.as_ref()).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(SagemakerAppClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use std::time::Duration;
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> SagemakerAppClient {
+        SagemakerAppClient {
+            client: SageMakerClient::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn split_app_id() {
+        let request = to_app_request("d-1234/alice/datascience-ml-t3-medium").unwrap();
+
+        assert_eq!(request.domain_id, "d-1234");
+        assert_eq!(request.user_profile_name, "alice");
+        assert_eq!(request.app_name, "datascience-ml-t3-medium");
+        assert_eq!(request.app_type, KERNEL_GATEWAY);
+        assert!(to_app_request("d-1234/alice").is_none());
+    }
+
+    #[tokio::test]
+    async fn package_apps() {
+        let client = create_client(MockRequestDispatcher::default());
+        let apps = vec![AppDetails {
+            domain_id: Some("d-1234".to_string()),
+            user_profile_name: Some("alice".to_string()),
+            app_name: Some("datascience".to_string()),
+            app_type: Some(KERNEL_GATEWAY.to_string()),
+            status: Some("InService".to_string()),
+            creation_time: Some(1614592800.512),
+        }];
+
+        let resources = client.package_resources(apps).await.unwrap();
+
+        assert_eq!(resources[0].id, "d-1234/alice/datascience");
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_since_last_user_activity() {
+        let config = ResourceConfig {
+            max_idle_time: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let resource = Resource {
+            id: "d-1234/alice/datascience".to_string(),
+            type_: ClientType::SagemakerApp,
+            ..Default::default()
+        };
+
+        let client = create_client(MockRequestDispatcher::default().with_body(
+            r#"{"CreationTime": 1614592800, "LastUserActivityTimestamp": 1614596400}"#,
+        ));
+        assert_eq!(client.is_idle(&resource, &config).await, Some(true));
+
+        let client = create_client(MockRequestDispatcher::default().with_body(&format!(
+            r#"{{"CreationTime": 1614592800, "LastUserActivityTimestamp": {}}}"#,
+            Utc::now().timestamp()
+        )));
+        assert_eq!(client.is_idle(&resource, &config).await, Some(false));
+    }
+}
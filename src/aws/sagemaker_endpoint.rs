@@ -0,0 +1,289 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use rusoto_core::Region;
+use rusoto_sagemaker::{
+    DeleteEndpointInput, DescribeEndpointConfigInput, DescribeEndpointInput, EndpointSummary,
+    ListEndpointsInput, ListTagsInput, SageMaker, SageMakerClient, Tag,
+};
+use std::str::FromStr;
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("sagemaker_endpoint");
+
+#[derive(Clone)]
+pub struct SagemakerEndpointClient {
+    client: SageMakerClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl SagemakerEndpointClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        SagemakerEndpointClient {
+            client: SageMakerClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    async fn get_endpoints(&self) -> Result<Vec<EndpointSummary>> {
+        let mut next_token: Option<String> = None;
+        let mut endpoints: Vec<EndpointSummary> = Vec::new();
+
+        loop {
+            let req = self.client.list_endpoints(ListEndpointsInput {
+                next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for e in result.endpoints {
+                    endpoints.push(e);
+                }
+
+                if result.next_token.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_token;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(endpoints)
+    }
+
+    async fn package_resources(&self, endpoints: Vec<EndpointSummary>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for endpoint in endpoints {
+            let tags = self.get_tags(&endpoint.endpoint_arn).await;
+            let instance_types = match self.get_endpoint_config_name(&endpoint.endpoint_name).await
+            {
+                Some(config_name) => self.get_instance_types(&config_name).await,
+                None => None,
+            };
+
+            resources.push(Resource {
+                id: endpoint.endpoint_name,
+                arn: Some(endpoint.endpoint_arn),
+                type_: ClientType::SagemakerEndpoint,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: ResourceState::from_str(&endpoint.endpoint_status).ok(),
+                start_time: Some(Utc.timestamp(endpoint.creation_time as i64, 0).to_rfc3339()),
+                created: None,
+                resource_type: instance_types,
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                dependencies: None,
+                termination_protection: None,
+            })
+        }
+
+        Ok(resources)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key.clone()),
+                    value: Some(tag.value.clone()),
+                })
+                .collect()
+        })
+    }
+
+    async fn get_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self.client.list_tags(ListTagsInput {
+            resource_arn: arn.to_string(),
+            ..Default::default()
+        });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tags)
+            .unwrap_or_default()
+    }
+
+    async fn get_endpoint_config_name(&self, endpoint_name: &str) -> Option<String> {
+        let req = self.client.describe_endpoint(DescribeEndpointInput {
+            endpoint_name: endpoint_name.to_owned(),
+        });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.endpoint_config_name)
+    }
+
+    /// Instance types of all the production variants of an endpoint
+    async fn get_instance_types(&self, config_name: &str) -> Option<Vec<String>> {
+        let req = self
+            .client
+            .describe_endpoint_config(DescribeEndpointConfigInput {
+                endpoint_config_name: config_name.to_owned(),
+            });
+
+        handle_future_with_return!(req).ok().map(|r| {
+            r.production_variants
+                .into_iter()
+                .map(|v| v.instance_type)
+                .collect()
+        })
+    }
+
+    /// The endpoint config of an endpoint is removed along with the endpoint,
+    /// unless it is shared with other endpoints.
+    async fn get_dependencies(&self, resource: &Resource) -> Result<Vec<Resource>> {
+        let mut resources = Vec::new();
+
+        if let Some(config_name) = self.get_endpoint_config_name(&resource.id).await {
+            for endpoint in self.get_endpoints().await? {
+                if endpoint.endpoint_name != resource.id
+                    && self
+                        .get_endpoint_config_name(&endpoint.endpoint_name)
+                        .await
+                        .as_deref()
+                        == Some(config_name.as_str())
+                {
+                    debug!(
+                        resource = resource.id.as_str(),
+                        endpoint_config = config_name.as_str(),
+                        "Endpoint config is shared with other endpoints"
+                    );
+                    return Ok(resources);
+                }
+            }
+
+            resources.push(Resource {
+                id: config_name,
+                arn: None,
+                type_: ClientType::SagemakerEndpointConfig,
+                region: self.region.clone(),
+                tags: None,
+                state: None,
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::DeleteDependent,
+                enforcement_reason: Some(EnforcementReason::Dependent),
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            });
+        }
+
+        Ok(resources)
+    }
+
+    async fn delete_endpoint(&self, endpoint_name: &str) -> Result<()> {
+        debug!(resource = endpoint_name, "Deleting");
+
+        if !self.dry_run {
+            let req = self.client.delete_endpoint(DeleteEndpointInput {
+                endpoint_name: endpoint_name.to_owned(),
+            });
+
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for SagemakerEndpointClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Sagemaker endpoint resource scanner");
+        let endpoints = self.get_endpoints().await?;
+
+        self.package_resources(endpoints).await
+    }
+
+    async fn dependencies(&self, resource: &Resource) -> Option<Vec<Resource>> {
+        self.get_dependencies(resource).await.ok()
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_endpoint(resource.id.as_ref()).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(SagemakerEndpointClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+
+    #[tokio::test]
+    async fn package_endpoints() {
+        let client = SagemakerEndpointClient {
+            client: SageMakerClient::new_with(
+                MockRequestDispatcher::default(),
+                MockCredentialsProvider,
+                Region::UsEast1,
+            ),
+            region: Region::UsEast1,
+            dry_run: false,
+        };
+        let endpoints = vec![
+            EndpointSummary {
+                endpoint_name: "churn".to_string(),
+                endpoint_arn: "arn:aws:sagemaker:us-east-1:123456789012:endpoint/churn".to_string(),
+                endpoint_status: "InService".to_string(),
+                creation_time: 1614592800.512,
+                ..Default::default()
+            },
+            EndpointSummary {
+                endpoint_name: "fraud".to_string(),
+                endpoint_arn: "arn:aws:sagemaker:us-east-1:123456789012:endpoint/fraud".to_string(),
+                endpoint_status: "Deleting".to_string(),
+                creation_time: 1614592800.0,
+                ..Default::default()
+            },
+        ];
+
+        let resources = client.package_resources(endpoints).await.unwrap();
+
+        assert_eq!(resources[0].id, "churn");
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00+00:00")
+        );
+        assert_eq!(resources[1].state, Some(ResourceState::Deleted));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementReason, EnforcementState, Resource};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use rusoto_sagemaker::{
+    DeleteEndpointConfigInput, DescribeEndpointConfigInput, SageMaker, SageMakerClient,
+};
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("sagemaker_endpoint_config");
+
+#[derive(Clone)]
+pub struct SagemakerEndpointConfigClient {
+    client: SageMakerClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl SagemakerEndpointConfigClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        SagemakerEndpointConfigClient {
+            client: SageMakerClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    /// Models served by the production variants of the endpoint config
+    async fn get_dependencies(&self, resource: &Resource) -> Result<Vec<Resource>> {
+        let req = self
+            .client
+            .describe_endpoint_config(DescribeEndpointConfigInput {
+                endpoint_config_name: resource.id.to_owned(),
+            });
+        let mut model_names: Vec<String> = handle_future_with_return!(req)?
+            .production_variants
+            .into_iter()
+            .map(|v| v.model_name)
+            .collect();
+        model_names.sort();
+        model_names.dedup();
+
+        Ok(model_names
+            .into_iter()
+            .map(|model_name| Resource {
+                id: model_name,
+                arn: None,
+                type_: ClientType::SagemakerModel,
+                region: self.region.clone(),
+                tags: None,
+                state: None,
+                start_time: None,
+                created: None,
+                enforcement_state: EnforcementState::DeleteDependent,
+                enforcement_reason: Some(EnforcementReason::Dependent),
+                resource_type: None,
+                dependencies: None,
+                termination_protection: None,
+            })
+            .collect())
+    }
+
+    async fn delete_endpoint_config(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self
+                .client
+                .delete_endpoint_config(DeleteEndpointConfigInput {
+                    endpoint_config_name: resource.id.to_owned(),
+                });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for SagemakerEndpointConfigClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Sagemaker endpoint config resource scanner");
+        Ok(vec![])
+    }
+
+    async fn dependencies(&self, resource: &Resource) -> Option<Vec<Resource>> {
+        self.get_dependencies(resource).await.ok()
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_endpoint_config(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| {
+            Box::new(SagemakerEndpointConfigClient::new(cd, config, dry_run))
+        },
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
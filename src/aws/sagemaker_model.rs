@@ -0,0 +1,82 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, NukerClient};
+use crate::config::ResourceConfig;
+use crate::handle_future;
+use crate::registry::ResourceType;
+use crate::resource::Resource;
+use crate::Result;
+use async_trait::async_trait;
+use rusoto_sagemaker::{DeleteModelInput, SageMaker, SageMakerClient};
+use tracing::{debug, trace};
+
+pub(crate) const CLIENT: Client = Client::new("sagemaker_model");
+
+#[derive(Clone)]
+pub struct SagemakerModelClient {
+    client: SageMakerClient,
+    dry_run: bool,
+}
+
+impl SagemakerModelClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        SagemakerModelClient {
+            client: SageMakerClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            dry_run,
+        }
+    }
+
+    async fn delete_model(&self, resource: &Resource) -> Result<()> {
+        debug!(resource = resource.id.as_str(), "Deleting");
+
+        if !self.dry_run {
+            let req = self.client.delete_model(DeleteModelInput {
+                model_name: resource.id.to_owned(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for SagemakerModelClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Sagemaker model resource scanner");
+        Ok(vec![])
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, _resource: &Resource) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.delete_model(resource).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(SagemakerModelClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
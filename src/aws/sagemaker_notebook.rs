@@ -1,6 +1,7 @@
 use crate::aws::ClientDetails;
-use crate::client::{ClientType, NukerClient};
+use crate::client::{Client, ClientType, NukerClient};
 use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
 use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
 use crate::Result;
 use crate::{handle_future, handle_future_with_return};
@@ -13,6 +14,8 @@ use rusoto_sagemaker::{
 use std::str::FromStr;
 use tracing::{debug, trace};
 
+pub(crate) const CLIENT: Client = Client::new("sagemaker_notebook");
+
 #[derive(Clone)]
 pub struct SagemakerNotebookClient {
     client: SageMakerClient,
@@ -91,6 +94,7 @@ impl SagemakerNotebookClient {
                 )
                 .ok(),
                 start_time: Some(format!("{}", notebook.creation_time.unwrap_or(0f64) as i64)),
+                created: None,
                 resource_type: notebook.instance_type.map(|t| vec![t]),
                 enforcement_state: EnforcementState::SkipUnknownState,
                 enforcement_reason: None,
@@ -187,3 +191,17 @@ impl NukerClient for SagemakerNotebookClient {
         self.delete_notebook(resource.id.as_ref()).await
     }
 }
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| Box::new(SagemakerNotebookClient::new(cd, config, dry_run)),
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
@@ -0,0 +1,252 @@
+use crate::aws::ClientDetails;
+use crate::client::{Client, ClientType, NukerClient};
+use crate::config::ResourceConfig;
+use crate::registry::ResourceType;
+use crate::resource::{EnforcementState, NTag, Resource, ResourceState};
+use crate::Result;
+use crate::{handle_future, handle_future_with_return};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use rusoto_core::Region;
+use rusoto_sagemaker::{
+    DescribeProcessingJobRequest, ListProcessingJobsRequest, ListTagsInput, ProcessingJobSummary,
+    SageMaker, SageMakerClient, StopProcessingJobRequest, Tag,
+};
+use tracing::{debug, trace};
+
+const IN_PROGRESS: &str = "InProgress";
+
+pub(crate) const CLIENT: Client = Client::new("sagemaker_processing_job");
+
+#[derive(Clone)]
+pub struct SagemakerProcessingJobClient {
+    client: SageMakerClient,
+    region: Region,
+    dry_run: bool,
+}
+
+impl SagemakerProcessingJobClient {
+    pub fn new(cd: &ClientDetails, _config: &ResourceConfig, dry_run: bool) -> Self {
+        SagemakerProcessingJobClient {
+            client: SageMakerClient::new_with_client(cd.client.clone(), cd.region.clone()),
+            region: cd.region.clone(),
+            dry_run,
+        }
+    }
+
+    /// Only the jobs that are in progress are of interest, completed jobs do
+    /// not incur any cost.
+    async fn get_processing_jobs(&self) -> Result<Vec<ProcessingJobSummary>> {
+        let mut next_token: Option<String> = None;
+        let mut jobs: Vec<ProcessingJobSummary> = Vec::new();
+
+        loop {
+            let req = self.client.list_processing_jobs(ListProcessingJobsRequest {
+                status_equals: Some(IN_PROGRESS.to_string()),
+                next_token,
+                ..Default::default()
+            });
+
+            if let Ok(result) = handle_future_with_return!(req) {
+                for j in result.processing_job_summaries {
+                    jobs.push(j);
+                }
+
+                if result.next_token.is_none() {
+                    break;
+                } else {
+                    next_token = result.next_token;
+                }
+            } else {
+                break;
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    async fn package_resources(&self, jobs: Vec<ProcessingJobSummary>) -> Result<Vec<Resource>> {
+        let mut resources: Vec<Resource> = Vec::new();
+
+        for job in jobs {
+            let tags = self.get_tags(&job.processing_job_arn).await;
+            let instance_type = self.get_instance_type(&job.processing_job_name).await;
+
+            resources.push(Resource {
+                id: job.processing_job_name,
+                arn: Some(job.processing_job_arn),
+                type_: ClientType::SagemakerProcessingJob,
+                region: self.region.clone(),
+                tags: self.package_tags(tags),
+                state: Some(ResourceState::Running),
+                start_time: Some(Utc.timestamp(job.creation_time as i64, 0).to_rfc3339()),
+                created: None,
+                resource_type: instance_type.map(|t| vec![t]),
+                enforcement_state: EnforcementState::SkipUnknownState,
+                enforcement_reason: None,
+                dependencies: None,
+                termination_protection: None,
+            })
+        }
+
+        Ok(resources)
+    }
+
+    fn package_tags(&self, tags: Option<Vec<Tag>>) -> Option<Vec<NTag>> {
+        tags.map(|ts| {
+            ts.iter()
+                .map(|tag| NTag {
+                    key: Some(tag.key.clone()),
+                    value: Some(tag.value.clone()),
+                })
+                .collect()
+        })
+    }
+
+    async fn get_tags(&self, arn: &str) -> Option<Vec<Tag>> {
+        let req = self.client.list_tags(ListTagsInput {
+            resource_arn: arn.to_string(),
+            ..Default::default()
+        });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.tags)
+            .unwrap_or_default()
+    }
+
+    async fn get_instance_type(&self, job_name: &str) -> Option<String> {
+        let req = self
+            .client
+            .describe_processing_job(DescribeProcessingJobRequest {
+                processing_job_name: job_name.to_owned(),
+            });
+
+        handle_future_with_return!(req)
+            .ok()
+            .map(|r| r.processing_resources.cluster_config.instance_type)
+    }
+
+    /// Processing jobs cannot be deleted, both stop and delete stop the job.
+    async fn stop_processing_job(&self, job_name: &str) -> Result<()> {
+        debug!(resource = job_name, "Stopping");
+
+        if !self.dry_run {
+            let req = self.client.stop_processing_job(StopProcessingJobRequest {
+                processing_job_name: job_name.to_owned(),
+            });
+            handle_future!(req);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NukerClient for SagemakerProcessingJobClient {
+    async fn scan(&self) -> Result<Vec<Resource>> {
+        trace!("Initialized Sagemaker processing job resource scanner");
+        let jobs = self.get_processing_jobs().await?;
+
+        self.package_resources(jobs).await
+    }
+
+    async fn dependencies(&self, _resource: &Resource) -> Option<Vec<Resource>> {
+        None
+    }
+
+    async fn additional_filters(
+        &self,
+        _resource: &Resource,
+        _config: &ResourceConfig,
+    ) -> Option<bool> {
+        None
+    }
+
+    async fn stop(&self, resource: &Resource) -> Result<()> {
+        self.stop_processing_job(resource.id.as_ref()).await
+    }
+
+    async fn delete(&self, resource: &Resource) -> Result<()> {
+        self.stop_processing_job(resource.id.as_ref()).await
+    }
+}
+
+pub fn resource_type() -> ResourceType {
+    ResourceType {
+        client: CLIENT,
+        aliases: &[],
+        service: "sagemaker",
+        global: false,
+        builder: |cd, config, dry_run| {
+            Box::new(SagemakerProcessingJobClient::new(cd, config, dry_run))
+        },
+        default_config: ResourceConfig::default,
+        taggable: false,
+        create_event: None,
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+    use std::sync::{Arc, Mutex};
+
+    fn create_client(dispatcher: MockRequestDispatcher) -> SagemakerProcessingJobClient {
+        SagemakerProcessingJobClient {
+            client: SageMakerClient::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1),
+            region: Region::UsEast1,
+            dry_run: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn package_jobs() {
+        let client = create_client(MockRequestDispatcher::default());
+        let jobs = vec![ProcessingJobSummary {
+            processing_job_name: "churn".to_string(),
+            processing_job_arn: "arn:aws:sagemaker:us-east-1:123456789012:processing-job/churn".to_string(),
+            processing_job_status: IN_PROGRESS.to_string(),
+            creation_time: 1614592800.512,
+            ..Default::default()
+        }];
+
+        let resources = client.package_resources(jobs).await.unwrap();
+
+        assert_eq!(resources[0].id, "churn");
+        assert_eq!(resources[0].state, Some(ResourceState::Running));
+        assert_eq!(
+            resources[0].start_time.as_deref(),
+            Some("2021-03-01T10:00:00+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_on_delete() {
+        let targets = Arc::new(Mutex::new(Vec::new()));
+        let recorded = targets.clone();
+        let client = create_client(MockRequestDispatcher::default().with_body("{}").with_request_checker(
+            move |request| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8(request.headers["x-amz-target"][0].clone()).unwrap());
+            },
+        ));
+        let resource = Resource {
+            id: "churn".to_string(),
+            type_: ClientType::SagemakerProcessingJob,
+            ..Default::default()
+        };
+
+        client.stop(&resource).await.unwrap();
+        client.delete(&resource).await.unwrap();
+
+        assert_eq!(
+            *targets.lock().unwrap(),
+            vec!["SageMaker.StopProcessingJob", "SageMaker.StopProcessingJob"]
+        );
+    }
+}
@@ -1,9 +1,23 @@
+use colored::*;
+use std::{io, process::exit};
 use tracing::{info, trace};
 
+const PROMPT_YES: &str = "yes";
+
 #[tokio::main]
 async fn main() -> Result<(), failure::Error> {
     let args = nuker::parse_args();
-    let config = nuker::parse_config_file(&args.config);
+    let config = match nuker::config::load_config_file(
+        &args.config,
+        args.profile_config.as_deref(),
+        args.config_format,
+    ) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
 
     setup_tracing(args.verbose);
 
@@ -13,6 +27,36 @@ async fn main() -> Result<(), failure::Error> {
     );
     trace!("{:#?}", config);
 
+    if let Some(ref id) = args.explain_resource {
+        let id = id.clone();
+        let explanations = nuker::Nuker::new(config, args)
+            .explain_resource(&id)
+            .await?;
+
+        if explanations.is_empty() {
+            eprintln!("No resource found matching: {}", id);
+            exit(1);
+        }
+        for explanation in explanations {
+            println!("{}\n", explanation);
+        }
+
+        return Ok(());
+    }
+
+    if args.dry_run {
+        println!("{}", "DRY RUN ENABLED".blue().bold());
+    } else {
+        println!("{}", "DRY RUN DISABLED".red().bold());
+    }
+
+    if !args.dry_run && !args.force {
+        let input: String = get_input("Are you sure you want to continue (yes/no)?");
+        if input.to_lowercase() != PROMPT_YES {
+            exit(1);
+        }
+    }
+
     let mut nuker = nuker::Nuker::new(config, args);
 
     nuker.run().await?;
@@ -20,6 +64,15 @@ async fn main() -> Result<(), failure::Error> {
     Ok(())
 }
 
+fn get_input(prompt: &str) -> String {
+    println!("{}", prompt);
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return String::new();
+    }
+    input.trim().to_string()
+}
+
 fn setup_tracing(verbose: u64) {
     use tracing::Level;
     use tracing_subscriber::{fmt::time::ChronoUtc, EnvFilter, FmtSubscriber};
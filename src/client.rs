@@ -1,11 +1,15 @@
 //! Represents a Nuker Client
 use crate::{
     config::ResourceConfig,
-    resource::{EnforcementReason, EnforcementState, Resource, ResourceState},
-    CwClient, Event, NSender, Result, StdError, StdResult,
+    explain::{self, Explanation},
+    policy::{Decision, PolicyEngine},
+    registry,
+    resource::{EnforcementReason, EnforcementState, NTag, Resource, ResourceState},
+    CwClient, Error, Event, NSender, Result, StdError, StdResult,
 };
 use async_trait::async_trait;
 use dyn_clone::DynClone;
+use serde::{Serialize, Serializer};
 use std::{
     fmt::{Display, Error as FmtError, Formatter},
     str::FromStr,
@@ -13,77 +17,51 @@ use std::{
 };
 use tracing::{debug, error, trace};
 
-pub const ASG_TYPE: &str = "asg";
 pub const DEFAULT_TYPE: &str = "default";
-pub const EBS_SNAP_TYPE: &str = "ebs_snapshot";
-pub const EBS_VOL_TYPE: &str = "ebs_volume";
-pub const EC2_ADDRESS_TYPE: &str = "ec2_address";
-pub const EC2_ENI_TYPE: &str = "ec2_eni";
-pub const EC2_INSTANCE_TYPE: &str = "ec2_instance";
-pub const EC2_SG_TYPE: &str = "ec2_sg";
-pub const EC2_VPC_TYPE: &str = "ec2_vpc";
-pub const EC2_IGW_TYPE: &str = "ec2_igw";
-pub const EC2_SUBNET_TYPE: &str = "ec2_subnet";
-pub const EC2_RT_TYPE: &str = "ec2_rt";
-pub const EC2_NACL_TYPE: &str = "ec2_network_acl";
-pub const EC2_NATGW_TYPE: &str = "ec2_nat_gw";
-pub const EC2_VPNGW_TYPE: &str = "ec2_vpn_gw";
-pub const EC2_VPC_ENDPOINT_TYPE: &str = "ec2_vpc_endpoint";
-pub const EC2_PEERING_CONNECTION: &str = "ec2_peering_connection";
-pub const ECS_TYPE: &str = "ecs_cluster";
-pub const EKS_TYPE: &str = "eks_cluster";
-pub const ELB_ALB_TYPE: &str = "elb_alb";
-pub const ELB_NLB_TYPE: &str = "elb_nlb";
-pub const EMR_TYPE: &str = "emr_cluster";
-pub const ES_TYPE: &str = "es_domain";
-pub const GLUE_ENDPOINT_TYPE: &str = "glue_endpoint";
-pub const RDS_CLUSTER_TYPE: &str = "rds_aurora";
-pub const RDS_INSTANCE_TYPE: &str = "rds";
-pub const REDSHIFT_CLUSTER_TYPE: &str = "rs_cluster";
-pub const S3_BUCKET_TYPE: &str = "s3_bucket";
-pub const SAGEMAKER_NOTEBOOK_TYPE: &str = "sagemaker_notebook";
 
 pub type ClientType = Client;
 
+/// Identifies a resource type by its name. The built-in resource types are
+/// available as associated constants declared along with their modules in
+/// `aws`, additional types are created using
+/// `Client::new` and have to be registered with the `registry`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Client {
-    Asg,
-    DefaultClient,
-    EbsSnapshot,
-    EbsVolume,
-    Ec2Address,
-    Ec2Eni,
-    Ec2Instance,
-    Ec2Sg,
-    Ec2Vpc,
-    Ec2Igw,
-    Ec2Subnet,
-    Ec2RouteTable,
-    Ec2NetworkACL,
-    Ec2NatGW,
-    Ec2VpnGW,
-    Ec2VpcEndpoint,
-    Ec2PeeringConnection,
-    EcsCluster,
-    EksCluster,
-    ElbAlb,
-    ElbNlb,
-    EmrCluster,
-    EsDomain,
-    GlueEndpoint,
-    RdsCluster,
-    RdsInstance,
-    RsCluster,
-    S3Bucket,
-    SagemakerNotebook,
+pub struct Client(&'static str);
+
+#[allow(non_upper_case_globals)]
+impl Client {
+    pub const DefaultClient: Client = Client(DEFAULT_TYPE);
 }
 
 impl Client {
+    pub const fn new(name: &'static str) -> Client {
+        Client(name)
+    }
+
     pub fn is_default(&self) -> bool {
-        match *&self {
-            Client::DefaultClient => true,
-            _ => false,
-        }
+        *self == Client::DefaultClient
+    }
+
+    /// Global resources are not tied to a region, these are scanned once per
+    /// account instead of once per region.
+    pub fn is_global(&self) -> bool {
+        registry::get(self).map(|rt| rt.global).unwrap_or(false)
+    }
+
+    pub fn name(&self) -> &str {
+        self.0
+    }
+
+    /// Endpoint prefix of the AWS service the resource type belongs to
+    pub fn service(&self) -> &str {
+        registry::get(self)
+            .map(|rt| rt.service)
+            .unwrap_or(DEFAULT_TYPE)
+    }
+
+    /// Iterates over all the registered resource types
+    pub fn iter() -> impl Iterator<Item = Client> {
+        registry::clients().into_iter()
     }
 }
 
@@ -109,113 +87,19 @@ impl Display for ParseClientError {
     }
 }
 
-impl FromStr for Client {
-    type Err = ParseClientError;
-
-    fn from_str(s: &str) -> StdResult<Client, ParseClientError> {
-        let v: &str = &s.to_lowercase();
-        match v {
-            ASG_TYPE => Ok(Client::Asg),
-            EBS_SNAP_TYPE => Ok(Client::EbsSnapshot),
-            EBS_VOL_TYPE => Ok(Client::EbsVolume),
-            EC2_ADDRESS_TYPE => Ok(Client::Ec2Address),
-            EC2_ENI_TYPE => Ok(Client::Ec2Eni),
-            EC2_INSTANCE_TYPE => Ok(Client::Ec2Instance),
-            EC2_SG_TYPE => Ok(Client::Ec2Sg),
-            EC2_VPC_TYPE => Ok(Client::Ec2Vpc),
-            EC2_IGW_TYPE => Ok(Client::Ec2Igw),
-            EC2_SUBNET_TYPE => Ok(Client::Ec2Subnet),
-            EC2_RT_TYPE => Ok(Client::Ec2RouteTable),
-            EC2_NACL_TYPE => Ok(Client::Ec2NetworkACL),
-            EC2_NATGW_TYPE => Ok(Client::Ec2NatGW),
-            EC2_VPNGW_TYPE => Ok(Client::Ec2VpnGW),
-            EC2_VPC_ENDPOINT_TYPE => Ok(Client::Ec2VpcEndpoint),
-            EC2_PEERING_CONNECTION => Ok(Client::Ec2PeeringConnection),
-            ECS_TYPE => Ok(Client::EcsCluster),
-            EKS_TYPE => Ok(Client::EksCluster),
-            ELB_ALB_TYPE => Ok(Client::ElbAlb),
-            ELB_NLB_TYPE => Ok(Client::ElbNlb),
-            EMR_TYPE => Ok(Client::EmrCluster),
-            ES_TYPE => Ok(Client::EsDomain),
-            GLUE_ENDPOINT_TYPE => Ok(Client::GlueEndpoint),
-            RDS_CLUSTER_TYPE => Ok(Client::RdsCluster),
-            RDS_INSTANCE_TYPE => Ok(Client::RdsInstance),
-            REDSHIFT_CLUSTER_TYPE => Ok(Client::RsCluster),
-            S3_BUCKET_TYPE => Ok(Client::S3Bucket),
-            SAGEMAKER_NOTEBOOK_TYPE => Ok(Client::SagemakerNotebook),
-            s => Err(ParseClientError::new(s)),
-        }
+/// Resource types are serialized by their name
+impl Serialize for Client {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
     }
 }
 
-impl Client {
-    pub fn name(&self) -> &str {
-        match *self {
-            Client::Asg => ASG_TYPE,
-            Client::DefaultClient => DEFAULT_TYPE,
-            Client::EbsSnapshot => EBS_SNAP_TYPE,
-            Client::EbsVolume => EBS_VOL_TYPE,
-            Client::Ec2Address => EC2_ADDRESS_TYPE,
-            Client::Ec2Eni => EC2_ENI_TYPE,
-            Client::Ec2Instance => EC2_INSTANCE_TYPE,
-            Client::Ec2Sg => EC2_SG_TYPE,
-            Client::Ec2Vpc => EC2_VPC_TYPE,
-            Client::Ec2Igw => EC2_IGW_TYPE,
-            Client::Ec2Subnet => EC2_SUBNET_TYPE,
-            Client::Ec2RouteTable => EC2_RT_TYPE,
-            Client::Ec2NetworkACL => EC2_NACL_TYPE,
-            Client::Ec2NatGW => EC2_NATGW_TYPE,
-            Client::Ec2VpnGW => EC2_VPNGW_TYPE,
-            Client::Ec2VpcEndpoint => EC2_VPC_ENDPOINT_TYPE,
-            Client::Ec2PeeringConnection => EC2_PEERING_CONNECTION,
-            Client::EcsCluster => ECS_TYPE,
-            Client::EksCluster => EKS_TYPE,
-            Client::ElbAlb => ELB_ALB_TYPE,
-            Client::ElbNlb => ELB_NLB_TYPE,
-            Client::EmrCluster => EMR_TYPE,
-            Client::EsDomain => ES_TYPE,
-            Client::GlueEndpoint => GLUE_ENDPOINT_TYPE,
-            Client::RdsCluster => RDS_CLUSTER_TYPE,
-            Client::RdsInstance => RDS_INSTANCE_TYPE,
-            Client::RsCluster => REDSHIFT_CLUSTER_TYPE,
-            Client::S3Bucket => S3_BUCKET_TYPE,
-            Client::SagemakerNotebook => SAGEMAKER_NOTEBOOK_TYPE,
-        }
-    }
+impl FromStr for Client {
+    type Err = ParseClientError;
 
-    pub fn iter() -> impl Iterator<Item = Client> {
-        [
-            Client::Asg,
-            Client::EbsSnapshot,
-            Client::EbsVolume,
-            Client::Ec2Address,
-            Client::Ec2Eni,
-            Client::Ec2Igw,
-            Client::Ec2Instance,
-            Client::Ec2NatGW,
-            Client::Ec2NetworkACL,
-            Client::Ec2PeeringConnection,
-            Client::Ec2RouteTable,
-            Client::Ec2Sg,
-            Client::Ec2Subnet,
-            Client::Ec2Vpc,
-            Client::Ec2VpcEndpoint,
-            Client::Ec2VpnGW,
-            Client::EcsCluster,
-            Client::EksCluster,
-            Client::ElbAlb,
-            Client::ElbNlb,
-            Client::EmrCluster,
-            Client::EsDomain,
-            Client::GlueEndpoint,
-            Client::RdsCluster,
-            Client::RdsInstance,
-            Client::RsCluster,
-            Client::S3Bucket,
-            Client::SagemakerNotebook,
-        ]
-        .iter()
-        .copied()
+    /// Parses a registered resource type by its name or one of its aliases
+    fn from_str(s: &str) -> StdResult<Client, ParseClientError> {
+        registry::lookup(&s.to_lowercase()).ok_or_else(|| ParseClientError::new(s))
     }
 }
 
@@ -235,11 +119,12 @@ pub trait NukerClient: Send + Sync + DynClone {
         c: Client,
         config: ResourceConfig,
         cw_client: Arc<Box<CwClient>>,
+        policy: Option<PolicyEngine>,
     ) {
         if let Ok(resources) = self.scan().await {
             for mut resource in resources {
                 let enforcement_state = self
-                    .filter_resource(&mut resource, &config, cw_client.clone())
+                    .filter_resource(&mut resource, &config, cw_client.clone(), policy.as_ref())
                     .await;
 
                 if enforcement_state == EnforcementState::Delete
@@ -283,6 +168,21 @@ pub trait NukerClient: Send + Sync + DynClone {
         }
     }
 
+    /// Filters a resource younger than the minimum age, the age is given by
+    /// the creation of the resource or its start time. Resources without
+    /// either are not filtered.
+    fn filter_by_min_age(&self, resource: &Resource, config: &ResourceConfig) -> bool {
+        match (
+            config.min_age,
+            &resource.created,
+            resource.start_time.as_deref(),
+        ) {
+            (Some(ref min_age), Some(created), _) => created.is_younger_than(min_age),
+            (Some(ref min_age), None, Some(st)) => !crate::util::is_ts_older_than(st, min_age),
+            _ => false,
+        }
+    }
+
     /// Filters a resource based on its type and types that are allowed
     fn filter_by_allowed_types(&self, resource: &Resource, config: &ResourceConfig) -> bool {
         if let (Some(allowed), Some(type_)) = (
@@ -295,14 +195,18 @@ pub trait NukerClient: Send + Sync + DynClone {
         }
     }
 
-    /// Filters a resource based the provided whitelist
+    /// Filters a resource based the provided whitelist, along with the global
+    /// whitelist
     fn filter_by_whitelist(&self, resource: &Resource, config: &ResourceConfig) -> bool {
         if let Some(ref whitelist) = config.whitelist {
             if whitelist.contains(&resource.id) {
                 return true;
             }
         }
-        false
+        config
+            .whitelist_entries
+            .iter()
+            .any(|entry| entry.matches(resource))
     }
 
     /// Filters a resource that is not running
@@ -322,48 +226,36 @@ pub trait NukerClient: Send + Sync + DynClone {
     /// Filters a resource based on its naming prefix (specifically for S3 buckets)
     fn filter_by_naming_prefix(&self, resource: &Resource, config: &ResourceConfig) -> bool {
         if let Some(ref np) = config.naming_prefix {
-            !np.regex.as_ref().unwrap().is_match(&resource.id)
+            np.regex
+                .as_ref()
+                .map(|r| !r.is_match(&resource.id))
+                .unwrap_or(false)
         } else {
             false
         }
     }
 
-    /// Filters a resource based on its idle rules (Cloudwatch metrics)
+    /// Filters a resource based on its idle rules (Cloudwatch metrics), the
+    /// metrics of the resource are identified by the dimensions of the
+    /// metrics of its type
     async fn filter_by_idle_rules(
         &self,
         resource: &Resource,
         cw_client: Arc<Box<CwClient>>,
     ) -> bool {
-        match resource.type_ {
-            Client::Ec2Instance => cw_client.filter_instance(resource.id.as_str()).await,
-            Client::EbsVolume => cw_client.filter_volume(resource.id.as_str()).await,
-            Client::RdsInstance => cw_client.filter_db_instance(resource.id.as_str()).await,
-            Client::RdsCluster => cw_client.filter_db_cluster(resource.id.as_str()).await,
-            Client::RsCluster => cw_client.filter_rs_cluster(resource.id.as_str()).await,
-            Client::EsDomain => cw_client.filter_es_domain(resource.id.as_str()).await,
-            Client::ElbAlb => {
-                let dimension_val = format!(
-                    "app/{}/{}",
-                    resource.id.as_str(),
-                    resource.arn.as_deref().unwrap().split('/').last().unwrap()
-                );
-                cw_client
-                    .filter_alb_load_balancer(dimension_val.as_str())
-                    .await
-            }
-            Client::ElbNlb => {
-                let dimension_val = format!(
-                    "app/{}/{}",
-                    resource.id.as_str(),
-                    resource.arn.as_deref().unwrap().split('/').last().unwrap()
-                );
-                cw_client
-                    .filter_nlb_load_balancer(dimension_val.as_str())
-                    .await
-            }
-            Client::EcsCluster => cw_client.filter_ecs_cluster(resource.id.as_str()).await,
-            Client::EmrCluster => cw_client.filter_emr_cluster(resource.id.as_str()).await,
-            _ => false,
+        cw_client.filter_idle(resource).await
+    }
+
+    /// Asks the external policy engine for a decision on the resource, if one
+    /// is configured
+    async fn filter_by_policy(
+        &self,
+        resource: &Resource,
+        policy: Option<&PolicyEngine>,
+    ) -> Option<Decision> {
+        match policy {
+            Some(policy) => policy.decide(resource).await,
+            None => None,
         }
     }
 
@@ -380,12 +272,25 @@ pub trait NukerClient: Send + Sync + DynClone {
         resource: &mut Resource,
         config: &ResourceConfig,
         cw_client: Arc<Box<CwClient>>,
+        policy: Option<&PolicyEngine>,
     ) -> EnforcementState {
         if resource.enforcement_state == EnforcementState::SkipUnknownState {
             if self.filter_by_whitelist(resource, config) {
                 // Skip a resource if its in the whitelist
                 debug!(resource = resource.id.as_str(), "Resource whitelisted");
                 EnforcementState::SkipConfig
+            } else if self.filter_by_min_age(resource, config) {
+                // Skip a resource until it reaches the minimum age
+                debug!(
+                    resource = resource.id.as_str(),
+                    "Resource is younger than the minimum age"
+                );
+                EnforcementState::SkipConfig
+            } else if let Some(decision) = self.filter_by_policy(resource, policy).await {
+                // The decision of the policy engine takes precedence over the
+                // rules of the configuration
+                resource.enforcement_reason = decision.reason;
+                decision.state
             } else if self.filter_by_state(resource) {
                 // Skip resource if its state is stopped
                 EnforcementState::SkipStopped
@@ -396,7 +301,7 @@ pub trait NukerClient: Send + Sync + DynClone {
                     "Resource tags does not match."
                 );
                 resource.enforcement_reason = Some(EnforcementReason::TagRule);
-                EnforcementState::from_target_state(&config.target_state)
+                EnforcementState::from_tag_rule(&config.target_state)
             } else if self.filter_by_allowed_types(resource, config) {
                 // Enforce allowed types
                 debug!(
@@ -450,6 +355,59 @@ pub trait NukerClient: Send + Sync + DynClone {
         }
     }
 
+    /// Evaluates every rule against the resource, unlike `filter_resource`
+    /// the evaluation does not stop at the first rule that matches. The
+    /// resource is expected to be evaluated by `filter_resource` already.
+    async fn explain(
+        &self,
+        resource: &Resource,
+        config: &ResourceConfig,
+        cw_client: Arc<Box<CwClient>>,
+        policy: Option<&PolicyEngine>,
+    ) -> Explanation {
+        let traced = Arc::new(Box::new(cw_client.traced()));
+        let idle = self.filter_by_idle_rules(resource, traced.clone()).await;
+        let decision = self.filter_by_policy(resource, policy).await;
+
+        Explanation {
+            resource: resource.clone(),
+            rules: vec![
+                explain::explain_whitelist(
+                    resource,
+                    config,
+                    self.filter_by_whitelist(resource, config),
+                ),
+                explain::explain_min_age(
+                    resource,
+                    config,
+                    self.filter_by_min_age(resource, config),
+                ),
+                explain::explain_policy(policy, decision),
+                explain::explain_state(resource, self.filter_by_state(resource)),
+                explain::explain_tags(resource, config, self.filter_by_tags(resource, config)),
+                explain::explain_allowed_types(
+                    resource,
+                    config,
+                    self.filter_by_allowed_types(resource, config),
+                ),
+                explain::explain_runtime(
+                    resource,
+                    config,
+                    self.filter_by_runtime(resource, config),
+                ),
+                explain::explain_idle(config, &traced.take_trace(), idle),
+                explain::explain_naming_prefix(
+                    resource,
+                    config,
+                    self.filter_by_naming_prefix(resource, config),
+                ),
+                explain::explain_additional(self.additional_filters(resource, config).await),
+            ],
+        }
+    }
+
+    /// Stops or removes the resource based on its enforcement state, tagging
+    /// is handled separately as the tags to apply are resolved by the caller
     async fn cleanup(&self, resource: &Resource) -> Result<()> {
         match resource.enforcement_state {
             EnforcementState::Stop => self.stop(resource).await?,
@@ -467,6 +425,21 @@ pub trait NukerClient: Send + Sync + DynClone {
 
     /// Delete the resource
     async fn delete(&self, resource: &Resource) -> Result<()>;
+
+    /// Applies the tags to the resource, resource types that do not support
+    /// tag remediation fail
+    async fn tag(&self, resource: &Resource, _tags: &[NTag]) -> Result<()> {
+        Err(Error::Tagging(format!(
+            "tagging is not supported for {}",
+            resource.type_.name()
+        )))
+    }
+
+    /// Tags of the parent resource, for instance of the instance a volume is
+    /// attached to, used to infer the values of the remediated tags
+    async fn parent_tags(&self, _resource: &Resource) -> Option<Vec<NTag>> {
+        None
+    }
 }
 
 dyn_clone::clone_trait_object!(NukerClient);
@@ -1,24 +1,361 @@
 //! Configuration Parser
-use crate::client::Client;
-use clap::{App, Arg};
+use crate::{
+    client::Client,
+    interpolate::interpolate_document,
+    registry::{self, CwMetrics},
+    resource::{NTag, Resource},
+    util::{glob_match, glob_regex},
+    validate::{config_schema, format_path, validate_config_file, validate_document, Diagnostic},
+    Error, Result, StdResult,
+};
+use clap::{App, Arg, ArgMatches};
 use regex::Regex;
+use rusoto_core::Region;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::{fmt, fs::File, io::Read, str::FromStr, time::Duration};
+use std::{
+    fmt,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use toml::{value::Table, Value};
 use tracing::warn;
 
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
+/// Configuration inherited by every resource section
+pub(crate) const DEFAULTS_KEY: &str = "defaults";
+/// Files merged underneath the configuration
+pub(crate) const INCLUDE_KEY: &str = "include";
+/// Named configurations merged on top of the configuration
+pub(crate) const OVERLAYS_KEY: &str = "overlays";
+/// Whitelist applying to every resource type
+pub(crate) const WHITELIST_KEY: &str = "whitelist";
+/// Minimum age applying to every resource type
+pub(crate) const MIN_AGE_KEY: &str = "min_age";
+/// Resource configuration applied in specific regions and accounts
+pub(crate) const OVERRIDES_KEY: &str = "overrides";
+pub(crate) const OVERRIDE_REGIONS_KEY: &str = "regions";
+pub(crate) const OVERRIDE_ACCOUNTS_KEY: &str = "accounts";
+pub(crate) const OVERRIDE_EXCLUDE_KEY: &str = "exclude";
 
-pub type Config = HashMap<Client, ResourceConfig>;
+/// Nuker configuration, the resource configuration is keyed by the client
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub regions: RegionsConfig,
+    pub endpoints: EndpointsConfig,
+    pub hooks: HooksConfig,
+    pub policy: PolicyConfig,
+    /// Whitelist applying to every resource type
+    pub whitelist: Vec<String>,
+    /// Minimum age of the resource types without one
+    pub min_age: Option<Duration>,
+    resources: HashMap<Client, ResourceConfig>,
+    /// Configuration of the `[defaults]`, used for the resource types
+    /// registered after the configuration is parsed
+    defaults: Option<ResourceConfig>,
+    /// Resource sections of the configuration, the overrides are merged on
+    /// top of these
+    sections: HashMap<Client, Value>,
+    overrides: Vec<Override>,
+    /// Resource types excluded by the overrides of the scope
+    excluded: Vec<Client>,
+}
+
+/// Resource configuration merged on top of the configuration in the matching
+/// regions and accounts, either matches all when not specified
+#[derive(Debug, Clone)]
+struct Override {
+    regions: Vec<String>,
+    accounts: Vec<String>,
+    exclude: Vec<Client>,
+    resources: Vec<(Client, Value)>,
+}
+
+impl Override {
+    fn matches(&self, region: &str, account: &str) -> bool {
+        (self.regions.is_empty() || self.regions.iter().any(|r| glob_match(r, region)))
+            && (self.accounts.is_empty() || self.accounts.iter().any(|a| glob_match(a, account)))
+    }
+}
+
+impl Default for Config {
+    /// Default configuration of all the registered resource types
+    fn default() -> Self {
+        Config {
+            regions: RegionsConfig::default(),
+            endpoints: EndpointsConfig::default(),
+            hooks: HooksConfig::default(),
+            policy: PolicyConfig::default(),
+            whitelist: Vec::new(),
+            min_age: None,
+            resources: Client::iter()
+                .filter_map(|c| registry::get(&c).map(|rt| (c, (rt.default_config)())))
+                .collect(),
+            defaults: None,
+            sections: HashMap::new(),
+            overrides: Vec::new(),
+            excluded: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn get(&self, client: &Client) -> Option<&ResourceConfig> {
+        self.resources.get(client)
+    }
+
+    /// Configuration of the resource type, falling back to the `[defaults]`
+    /// or the default configuration of the resource type when it is not
+    /// configured
+    pub fn resource(&self, client: &Client) -> ResourceConfig {
+        match self.resources.get(client).or(self.defaults.as_ref()) {
+            Some(config) => config.clone(),
+            None => {
+                let mut config = registry::get(client)
+                    .map(|rt| (rt.default_config)())
+                    .unwrap_or_default();
+                compile_globals(&mut config, &self.whitelist, self.min_age);
+                config
+            }
+        }
+    }
+
+    pub fn set(&mut self, client: Client, mut config: ResourceConfig) {
+        compile_globals(&mut config, &self.whitelist, self.min_age);
+        self.resources.insert(client, config);
+    }
+
+    pub fn get_mut(&mut self, client: &Client) -> Option<&mut ResourceConfig> {
+        self.resources.get_mut(client)
+    }
+
+    /// Configuration of a region of an account, the overrides matching the
+    /// region and the account are merged on top of the resource sections in
+    /// the order they are configured. Global resources are matched with the
+    /// region `global`.
+    pub fn for_scope(&self, region: &str, account: &str) -> Result<Config> {
+        let mut config = self.clone();
+
+        for ov in self.overrides.iter().filter(|o| o.matches(region, account)) {
+            config.excluded.extend(ov.exclude.iter().copied());
+
+            for (client, section) in &ov.resources {
+                let mut value = config
+                    .sections
+                    .get(client)
+                    .cloned()
+                    .unwrap_or_else(|| Value::Table(Table::new()));
+                merge(&mut value, section.clone());
+
+                config.resources.insert(
+                    *client,
+                    resource_config(value.clone(), &self.whitelist, self.min_age)?,
+                );
+                config.sections.insert(*client, value);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Resource types excluded in the scope of the configuration
+    pub fn excluded(&self) -> &[Client] {
+        &self.excluded
+    }
+}
+
+/// Custom endpoints for running against a local stand-in of AWS (e.g.
+/// LocalStack), `url` applies to all the services unless a service specific
+/// endpoint is configured in `services`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct EndpointsConfig {
+    pub url: Option<String>,
+    #[serde(default)]
+    pub services: HashMap<String, String>,
+}
+
+impl EndpointsConfig {
+    /// Returns the region to use for the service, a configured endpoint maps
+    /// to `Region::Custom` keeping the name of the region for signing.
+    /// Regions unknown to rusoto (custom regions without an endpoint) use the
+    /// standard endpoint of the service in the region.
+    pub fn region(&self, service: &str, region: &Region) -> Region {
+        match (self.services.get(service).or(self.url.as_ref()), region) {
+            (Some(endpoint), _) => Region::Custom {
+                name: region.name().to_string(),
+                endpoint: endpoint.to_owned(),
+            },
+            (None, Region::Custom { name, endpoint }) if endpoint.is_empty() => Region::Custom {
+                name: name.to_owned(),
+                endpoint: standard_endpoint(service, name),
+            },
+            (None, _) => region.clone(),
+        }
+    }
+}
+
+/// Endpoint of the service in the region following the AWS naming scheme
+fn standard_endpoint(service: &str, region: &str) -> String {
+    let prefix = match service {
+        "sagemaker" => "api.sagemaker",
+        service => service,
+    };
+
+    format!("https://{}.{}.amazonaws.com", prefix, region)
+}
+
+/// External commands invoked at the lifecycle points of a run, each command
+/// is given as the program followed by its arguments.
+#[derive(Debug, Deserialize, Clone)]
+pub struct HooksConfig {
+    pub pre_scan: Option<Vec<String>>,
+    pub post_scan: Option<Vec<String>>,
+    /// A non-zero exit status vetoes the stop or removal of the resource
+    pub pre_delete: Option<Vec<String>>,
+    pub post_delete: Option<Vec<String>>,
+    pub run_end: Option<Vec<String>>,
+    /// Commands not completing within the timeout are killed and considered
+    /// failed
+    #[serde(default = "default_hook_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        HooksConfig {
+            pre_scan: None,
+            post_scan: None,
+            pre_delete: None,
+            post_delete: None,
+            run_end: None,
+            timeout: default_hook_timeout(),
+        }
+    }
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.pre_scan.is_none()
+            && self.post_scan.is_none()
+            && self.pre_delete.is_none()
+            && self.post_delete.is_none()
+            && self.run_end.is_none()
+    }
+}
+
+fn default_hook_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// External policy engine deciding the enforcement of the resources, either
+/// an HTTP decision endpoint or an evaluator command given as the program
+/// followed by its arguments.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PolicyConfig {
+    pub url: Option<String>,
+    pub command: Option<Vec<String>>,
+    #[serde(default = "default_policy_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+    /// Applies the rules of the configuration when the policy engine cannot
+    /// be evaluated, otherwise the resource is skipped
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        PolicyConfig {
+            url: None,
+            command: None,
+            timeout: default_policy_timeout(),
+            fallback: false,
+        }
+    }
+}
+
+fn default_policy_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+/// Regions to scan, specified by name or by a glob pattern (e.g. `eu-*`).
+/// When no regions are included, all the regions enabled for the account are
+/// scanned.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RegionsConfig {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Format of a configuration file, every format has the same semantics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format of the file selected by its extension, files without a known
+    /// extension are assumed to be in the `fallback` format
+    pub fn of(path: &Path, fallback: ConfigFormat) -> ConfigFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => fallback,
+        }
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(Error::Config(format!(
+                "unknown configuration format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigFormat::Toml => write!(f, "toml"),
+            ConfigFormat::Yaml => write!(f, "yaml"),
+            ConfigFormat::Json => write!(f, "json"),
+        }
+    }
+}
 
 /// Cli Args
 #[derive(Debug, Clone)]
 pub struct Args {
     pub config: String,
+    /// Format of the config file, selected by its extension when not set
+    pub config_format: Option<ConfigFormat>,
+    /// Named overlay of the configuration
+    pub profile_config: Option<String>,
     pub profile: Option<String>,
     pub regions: Vec<String>,
     pub targets: Option<Vec<Client>>,
     pub exclude: Option<Vec<Client>>,
+    pub exclude_regions: Vec<String>,
+    pub endpoint_url: Option<String>,
+    /// Prints the evaluation of every rule for the resources
+    pub explain: bool,
+    /// Resource to explain, identified by its id or ARN
+    pub explain_resource: Option<String>,
     pub dry_run: bool,
     pub force: bool,
     pub verbose: u64,
@@ -30,68 +367,34 @@ pub struct Args {
 /// This struct is built from reading the configuration file
 #[derive(Debug, Deserialize, Clone)]
 pub struct ParsedConfig {
-    #[serde(default = "default_resource_config")]
-    pub ec2_instance: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_sg: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_eni: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_address: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ebs_volume: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ebs_snapshot: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub elb_alb: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub elb_nlb: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub rds_instance: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub rds_cluster: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub s3_bucket: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub emr_cluster: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub rs_cluster: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub glue_endpoint: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub sagemaker_notebook: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub es_domain: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub asg: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ecs_cluster: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub eks_cluster: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_vpc: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_igw: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_nat_gw: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_network_acl: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_peering_connection: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_rt: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_subnet: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_vpc_endpoint: ResourceConfig,
-    #[serde(default = "default_resource_config")]
-    pub ec2_vpn_gw: ResourceConfig,
+    #[serde(default)]
+    pub regions: RegionsConfig,
+    #[serde(default)]
+    pub endpoints: EndpointsConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    /// Whitelist applying to every resource type
+    #[serde(default)]
+    pub whitelist: Vec<String>,
+    /// Minimum age applying to every resource type
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub min_age: Option<Duration>,
+    /// Resource configuration keyed by the name (or an alias) of the
+    /// resource type
+    #[serde(flatten)]
+    pub resources: HashMap<String, ResourceConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone, Eq, PartialEq)]
 pub enum TargetState {
     Stopped,
     Deleted,
+    /// Applies the missing required tags instead of stopping or removing the
+    /// resources violating them, other rules are not enforced
+    Tagged,
 }
 
 impl Default for TargetState {
@@ -100,14 +403,115 @@ impl Default for TargetState {
     }
 }
 
+/// Tag required on the resources, identified by its name or by alternative
+/// names of which any one satisfies the requirement
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct RequiredTag {
+    #[serde(default)]
     pub name: String,
+    /// Alternative names of the tag, any one of them satisfies the requirement
+    #[serde(default)]
+    pub one_of: Option<Vec<String>>,
     pub pattern: Option<String>,
+    /// Values the tag is allowed to have
+    #[serde(default)]
+    pub allowed_values: Option<Vec<String>>,
+    /// Matches the names of the tags ignoring their case
+    #[serde(default)]
+    pub ignore_case: bool,
+    /// The tag is only required on resources having all of these tags, the
+    /// values accept glob patterns
+    #[serde(default)]
+    pub when: Option<HashMap<String, String>>,
+    /// Value applied when remediating the tag, if it cannot be inferred
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Source the value is inferred from when remediating the tag
+    #[serde(default)]
+    pub infer: Option<TagSource>,
     #[serde(skip)]
     pub regex: Option<Regex>,
 }
 
+/// Source of the value of a remediated tag
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagSource {
+    /// User that created the resource, as recorded by CloudTrail
+    Creator,
+    /// Same tag of the parent resource, for instance the instance a volume is
+    /// attached to
+    Parent,
+}
+
+impl RequiredTag {
+    /// Names satisfying the requirement
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str())
+            .filter(|name| !name.is_empty())
+            .chain(self.one_of.iter().flatten().map(String::as_str))
+    }
+
+    /// Checks if the tag is required on a resource with the provided tags
+    pub fn applies(&self, tags: &[NTag]) -> bool {
+        self.when.iter().flatten().all(|(name, value)| {
+            self.find_tag(tags, std::iter::once(name.as_str()))
+                .map(|(_, v)| glob_match(value, v))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Name and value of the first tag satisfying the requirement
+    pub fn find<'a>(&self, tags: &'a [NTag]) -> Option<(&'a str, &'a str)> {
+        self.find_tag(tags, self.names())
+    }
+
+    /// Checks if the resource with the provided tags violates the requirement
+    pub fn is_violated(&self, tags: &[NTag]) -> bool {
+        self.applies(tags)
+            && self
+                .find(tags)
+                .map(|(_, value)| !self.allows(value))
+                .unwrap_or(true)
+    }
+
+    /// Checks the value of the tag against the pattern and the allowed values
+    pub fn allows(&self, value: &str) -> bool {
+        self.regex
+            .as_ref()
+            .map(|r| r.is_match(value))
+            .unwrap_or(true)
+            && self
+                .allowed_values
+                .as_ref()
+                .map(|allowed| allowed.iter().any(|a| a == value))
+                .unwrap_or(true)
+    }
+
+    fn find_tag<'a, 'b>(
+        &self,
+        tags: &'a [NTag],
+        mut names: impl Iterator<Item = &'b str>,
+    ) -> Option<(&'a str, &'a str)> {
+        let tags: Vec<(&str, &str)> = tags
+            .iter()
+            .filter_map(|t| Some((t.key.as_deref()?, t.value.as_deref().unwrap_or_default())))
+            .collect();
+
+        names.find_map(|name| {
+            tags.iter().copied().find(|(key, _)| {
+                *key == name || (self.ignore_case && key.to_lowercase() == name.to_lowercase())
+            })
+        })
+    }
+}
+
+impl fmt::Display for RequiredTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.names().collect::<Vec<&str>>().join(" | "))
+    }
+}
+
 #[derive(Debug, Deserialize, Copy, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum FilterOp {
@@ -123,6 +527,17 @@ impl Default for FilterOp {
     }
 }
 
+impl fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FilterOp::Lt => write!(f, "<"),
+            FilterOp::Gt => write!(f, ">"),
+            FilterOp::Le => write!(f, "<="),
+            FilterOp::Ge => write!(f, ">="),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Copy, Clone)]
 pub enum MetricStatistic {
     SampleCount,
@@ -155,6 +570,105 @@ pub struct MetricFilter {
     pub op: FilterOp,
     pub dimensions: Option<Vec<MetricDimension>>,
     pub value: f32,
+    /// State of the resource when the metric is not published or has no
+    /// datapoints over the duration. Defaults to idle for the resource types
+    /// publishing sparse metrics, not idle otherwise.
+    #[serde(default)]
+    pub no_datapoints: Option<IdleState>,
+}
+
+/// State of a resource according to an idle rule
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleState {
+    Idle,
+    NotIdle,
+    /// The rule does not decide whether the resource is idle
+    Unknown,
+}
+
+impl IdleState {
+    pub fn from_idle(idle: bool) -> Self {
+        if idle {
+            IdleState::Idle
+        } else {
+            IdleState::NotIdle
+        }
+    }
+}
+
+impl fmt::Display for IdleState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IdleState::Idle => write!(f, "idle"),
+            IdleState::NotIdle => write!(f, "not idle"),
+            IdleState::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// How the idle rules of a resource type are combined
+#[derive(Debug, Default, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleMatch {
+    /// The resource is idle when every idle rule finds it idle
+    #[default]
+    All,
+    /// The resource is idle when any idle rule finds it idle
+    Any,
+}
+
+impl IdleMatch {
+    /// Combines the states of the idle rules, rules in an unknown state are
+    /// ignored and a resource without any rule in a known state is not idle
+    pub fn is_idle(&self, states: &[IdleState]) -> bool {
+        let mut known = states
+            .iter()
+            .filter(|s| **s != IdleState::Unknown)
+            .peekable();
+
+        match *self {
+            IdleMatch::All => known.peek().is_some() && known.all(|s| *s == IdleState::Idle),
+            IdleMatch::Any => known.any(|s| *s == IdleState::Idle),
+        }
+    }
+}
+
+impl fmt::Display for IdleMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IdleMatch::All => write!(f, "all"),
+            IdleMatch::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// CloudWatch metrics of a resource type, replacing the metrics the resource
+/// type is registered with. The values of the dimensions are templates
+/// rendered for each resource, with the placeholders `{id}`, `{arn}`,
+/// `{arn_suffix}` (the part of the ARN following the resource type),
+/// `{region}`, `{account}` and `{tag:<key>}`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MetricsConfig {
+    pub namespace: String,
+    pub dimensions: HashMap<String, String>,
+    /// Metrics are only published when the resource is in use
+    #[serde(default)]
+    pub sparse: bool,
+}
+
+impl From<CwMetrics> for MetricsConfig {
+    fn from(metrics: CwMetrics) -> Self {
+        MetricsConfig {
+            namespace: metrics.namespace.to_string(),
+            dimensions: metrics
+                .dimensions
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            sparse: metrics.sparse,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -183,6 +697,85 @@ pub struct NamingPrefix {
     pub regex: Option<Regex>,
 }
 
+/// Entry of a whitelist, either:
+///
+/// - the id or the ARN of a resource, `*` and `?` glob patterns are accepted
+///   (e.g. `arn:aws:ec2:*:*:instance/*`)
+/// - a regex delimited by slashes (e.g. `/^shared-/`), matched against the id
+///   and the `Name` tag of the resource
+/// - a tag selector `key=value`, the value accepts glob patterns
+#[derive(Debug, Clone)]
+pub struct WhitelistEntry {
+    pub entry: String,
+    selector: Selector,
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Id(String),
+    Glob(Regex),
+    Regex(Regex),
+    Tag(String, Regex),
+}
+
+const NAME_TAG: &str = "Name";
+const ARN_PREFIX: &str = "arn:";
+
+impl WhitelistEntry {
+    pub fn parse(entry: &str) -> StdResult<Self, String> {
+        let selector = if entry.len() > 1 && entry.starts_with('/') && entry.ends_with('/') {
+            let pattern = &entry[1..entry.len() - 1];
+            Regex::new(pattern)
+                .map(Selector::Regex)
+                .map_err(|e| format!("invalid regex '{}': {}", pattern, e))?
+        } else if let (false, Some(eq)) = (entry.starts_with(ARN_PREFIX), entry.find('=')) {
+            let value = glob_regex(&entry[eq + 1..])
+                .ok_or_else(|| format!("invalid tag selector: {}", entry))?;
+            Selector::Tag(entry[..eq].trim().to_string(), value)
+        } else if entry.contains('*') || entry.contains('?') {
+            glob_regex(entry)
+                .map(Selector::Glob)
+                .ok_or_else(|| format!("invalid glob pattern: {}", entry))?
+        } else {
+            Selector::Id(entry.to_string())
+        };
+
+        Ok(WhitelistEntry {
+            entry: entry.to_string(),
+            selector,
+        })
+    }
+
+    pub fn matches(&self, resource: &Resource) -> bool {
+        let arn = resource.arn.as_deref();
+
+        match self.selector {
+            Selector::Id(ref id) => *id == resource.id || arn == Some(id.as_str()),
+            Selector::Glob(ref glob) => {
+                glob.is_match(&resource.id) || arn.map(|a| glob.is_match(a)).unwrap_or(false)
+            }
+            Selector::Regex(ref regex) => {
+                regex.is_match(&resource.id)
+                    || tag_value(resource, NAME_TAG)
+                        .map(|name| regex.is_match(name))
+                        .unwrap_or(false)
+            }
+            Selector::Tag(ref key, ref value) => tag_value(resource, key)
+                .map(|v| value.is_match(v))
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn tag_value<'a>(resource: &'a Resource, key: &str) -> Option<&'a str> {
+    resource
+        .tags
+        .iter()
+        .flatten()
+        .find(|t| t.key.as_deref() == Some(key))
+        .and_then(|t| t.value.as_deref())
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ResourceConfig {
     #[serde(default)]
@@ -191,10 +784,22 @@ pub struct ResourceConfig {
     pub required_tags: Option<Vec<RequiredTag>>,
     #[serde(default)]
     pub allowed_types: Option<Vec<String>>,
+    /// Resources to skip, see [`WhitelistEntry`] for the accepted entries
     #[serde(default)]
     pub whitelist: Option<Vec<String>>,
+    /// Entries of the whitelist and the global whitelist, compiled when the
+    /// configuration is parsed
+    #[serde(skip)]
+    pub whitelist_entries: Vec<WhitelistEntry>,
     #[serde(default)]
     pub metric_filters: Option<Vec<MetricFilter>>,
+    /// CloudWatch metrics the idle rules are evaluated against, defaults to
+    /// the metrics the resource type is registered with
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    /// Whether all or any of the idle rules have to find the resource idle
+    #[serde(default)]
+    pub idle_match: IdleMatch,
     #[serde(default)]
     pub termination_protection: Option<TerminationProtection>,
     #[serde(default)]
@@ -202,10 +807,22 @@ pub struct ResourceConfig {
     #[serde(default)]
     #[serde(with = "humantime_serde")]
     pub max_run_time: Option<Duration>,
+    /// Resources younger than the minimum age are not enforced, giving the
+    /// provisioning tools time to tag them
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub min_age: Option<Duration>,
     #[serde(default)]
     pub disable_additional_rules: bool,
     #[serde(default)]
     pub naming_prefix: Option<NamingPrefix>,
+    #[serde(default)]
+    pub retain_resources: Option<Vec<String>>,
+    #[serde(default)]
+    pub backup_before_delete: bool,
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub max_idle_time: Option<Duration>,
 }
 
 impl Default for ResourceConfig {
@@ -215,12 +832,19 @@ impl Default for ResourceConfig {
             required_tags: None,
             allowed_types: None,
             whitelist: None,
+            whitelist_entries: Vec::new(),
             metric_filters: None,
+            metrics: None,
+            idle_match: IdleMatch::All,
             termination_protection: Some(TerminationProtection { ignore: true }),
             manage_stopped: None,
             max_run_time: None,
+            min_age: None,
             disable_additional_rules: false,
             naming_prefix: None,
+            retain_resources: None,
+            backup_before_delete: false,
+            max_idle_time: None,
         }
     }
 }
@@ -231,6 +855,68 @@ pub fn parse_args() -> Args {
         .about("Cleans up AWS resources based on configurable Rules.")
         .version(VERSION.unwrap_or("unknown"))
         .subcommand(App::new("resource-types").about("Prints out supported resource types"))
+        .subcommand(
+            App::new("validate")
+                .about(
+                    "Validates the configuration file, reporting every problem found along with \
+                    its position in the file.",
+                )
+                .arg(
+                    Arg::with_name("config-file")
+                        .long("config")
+                        .short("C")
+                        .value_name("config")
+                        .help("The config file to validate.")
+                        .takes_value(true),
+                )
+                .arg(config_format_arg()),
+        )
+        .subcommand(
+            App::new("config")
+                .about("Inspects the configuration")
+                .subcommand(
+                    App::new("show")
+                        .about(
+                            "Prints the effective configuration, after merging the included \
+                            files, the overlay and the defaults.",
+                        )
+                        .arg(
+                            Arg::with_name("config-file")
+                                .long("config")
+                                .short("C")
+                                .value_name("config")
+                                .help("The config file to show.")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("profile-config")
+                                .long("profile-config")
+                                .value_name("overlay")
+                                .help("Named overlay of the configuration to apply.")
+                                .takes_value(true),
+                        )
+                        .arg(config_format_arg()),
+                )
+                .subcommand(App::new("schema").about(
+                    "Prints the JSON Schema of the configuration, for editors to validate and \
+                    complete configuration files.",
+                )),
+        )
+        .subcommand(
+            App::new("explain")
+                .about(
+                    "Evaluates every rule against a resource and prints the outcome of each \
+                    rule, nothing is stopped or removed.",
+                )
+                .arg(
+                    Arg::with_name("resource")
+                        .long("resource")
+                        .value_name("id-or-arn")
+                        .help("ID or ARN of the resource to explain")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
         .arg(
             Arg::with_name("config-file")
                 .long("config")
@@ -240,12 +926,35 @@ pub fn parse_args() -> Args {
                 .help("The config file to feed in.")
                 .takes_value(true),
         )
+        .arg(config_format_arg())
+        .arg(
+            Arg::with_name("profile-config")
+                .long("profile-config")
+                .value_name("overlay")
+                .help(
+                    "Named overlay of the configuration (e.g. sandbox) to merge on top of the \
+                    configuration file, overlays are defined in the [overlays.<name>] sections.",
+                )
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("region")
                 .long("region")
                 .help(
-                    "Which regions to enforce the rules in. Default is the rules will be \
-                    enforced across all the regions.",
+                    "Which regions to enforce the rules in, accepts glob patterns (e.g. eu-*). \
+                    Default is the rules will be enforced across all the regions enabled for \
+                    the account. This will take precedence over the configuration file.",
+                )
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("exclude-region")
+                .long("exclude-region")
+                .help(
+                    "Regions to skip, accepts glob patterns (e.g. ap-*). These are skipped in \
+                    addition to the regions excluded in the configuration file.",
                 )
                 .takes_value(true)
                 .multiple(true)
@@ -254,6 +963,7 @@ pub fn parse_args() -> Args {
         .arg(
             Arg::with_name("target")
                 .long("target")
+                .validator(is_client)
                 .help(
                     "Services to include from rules enforcement. This will take precedence \
                 over the configuration file.",
@@ -265,6 +975,7 @@ pub fn parse_args() -> Args {
         .arg(
             Arg::with_name("exclude")
                 .long("exclude")
+                .validator(is_client)
                 .help(
                     "Services to exclude from rules enforcement. This will take precedence \
                 over the configuration file.",
@@ -286,11 +997,26 @@ pub fn parse_args() -> Args {
                 )
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("endpoint-url")
+                .long("endpoint-url")
+                .value_name("url")
+                .help(
+                    "Custom endpoint to send the requests of all the services to, for running \
+                    against a local stand-in of AWS such as LocalStack. This will take \
+                    precedence over the configuration file.",
+                )
+                .takes_value(true),
+        )
         .arg(Arg::with_name("no-dry-run").long("no-dry-run").help(
             "Disables the dry run behavior, which just lists the resources that are \
                     being cleaned but not actually delete them. Enabling this option will disable \
                     dry run behavior and deletes the resources.",
         ))
+        .arg(Arg::with_name("explain").long("explain").help(
+            "Prints the outcome of every rule for each resource, along with the values the \
+                    rules were evaluated against.",
+        ))
         .arg(
             Arg::with_name("force")
                 .long("force")
@@ -311,8 +1037,49 @@ pub fn parse_args() -> Args {
         ::std::process::exit(0);
     }
 
+    if let Some(matches) = args.subcommand_matches("validate") {
+        let format = config_format(matches).or_else(|| config_format(&args));
+
+        match matches
+            .value_of("config-file")
+            .or_else(|| args.value_of("config-file"))
+        {
+            Some(filename) => ::std::process::exit(print_diagnostics(filename, format)),
+            None => args_error("--config <config> is a required parameter"),
+        }
+    }
+
+    if let Some(matches) = args
+        .subcommand_matches("config")
+        .and_then(|m| m.subcommand_matches("show"))
+    {
+        let overlay = matches
+            .value_of("profile-config")
+            .or_else(|| args.value_of("profile-config"));
+        let format = config_format(matches).or_else(|| config_format(&args));
+
+        match matches
+            .value_of("config-file")
+            .or_else(|| args.value_of("config-file"))
+        {
+            Some(filename) => {
+                ::std::process::exit(print_effective_config(filename, overlay, format))
+            }
+            None => args_error("--config <config> is a required parameter"),
+        }
+    }
+
+    if args
+        .subcommand_matches("config")
+        .and_then(|m| m.subcommand_matches("schema"))
+        .is_some()
+    {
+        println!("{:#}", config_schema());
+        ::std::process::exit(0);
+    }
+
     if !args.is_present("config-file") {
-        panic!("--config <config> is a required parameter");
+        args_error("--config <config> is a required parameter");
     }
 
     let verbose = if args.is_present("verbose") {
@@ -339,6 +1106,12 @@ pub fn parse_args() -> Args {
         vec![]
     };
 
+    let exclude_regions: Vec<&str> = if args.is_present("exclude-region") {
+        args.values_of("exclude-region").unwrap().collect()
+    } else {
+        vec![]
+    };
+
     let targets: Option<Vec<Client>> = if args.is_present("target") {
         Some(
             args.values_of("target")
@@ -363,10 +1136,19 @@ pub fn parse_args() -> Args {
 
     Args {
         config: args.value_of("config-file").unwrap().to_string(),
+        config_format: config_format(&args),
+        profile_config: args.value_of("profile-config").map(|s| s.to_owned()),
         regions: regions.iter().map(|r| r.to_string()).collect(),
         profile: args.value_of("profile").map(|s| s.to_owned()),
         targets,
         exclude,
+        exclude_regions: exclude_regions.iter().map(|r| r.to_string()).collect(),
+        endpoint_url: args.value_of("endpoint-url").map(|s| s.to_owned()),
+        explain: args.is_present("explain"),
+        explain_resource: args
+            .subcommand_matches("explain")
+            .and_then(|m| m.value_of("resource"))
+            .map(|s| s.to_owned()),
         dry_run,
         force,
         verbose,
@@ -374,71 +1156,507 @@ pub fn parse_args() -> Args {
     }
 }
 
-/// Parses the nuker configuration file
-pub fn parse_config_file(filename: &str) -> Config {
-    let mut fp = match File::open(filename) {
-        Err(e) => panic!("Could not open file {} with error {}", filename, e),
-        Ok(fp) => fp,
+pub(crate) fn read_config_file(filename: &str) -> Result<String> {
+    let mut buffer = String::new();
+    File::open(filename)
+        .and_then(|mut fp| fp.read_to_string(&mut buffer))
+        .map_err(|e| Error::Config(format!("could not read {}: {}", filename, e)))?;
+
+    Ok(buffer)
+}
+
+fn config_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("config-format")
+        .long("config-format")
+        .value_name("format")
+        .possible_values(&["toml", "yaml", "json"])
+        .help("Format of the config file, by default it is selected by the file extension.")
+        .takes_value(true)
+}
+
+fn config_format(matches: &ArgMatches) -> Option<ConfigFormat> {
+    matches
+        .value_of("config-format")
+        .and_then(|f| ConfigFormat::from_str(f).ok())
+}
+
+fn is_client(value: String) -> StdResult<(), String> {
+    Client::from_str(&value)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn args_error(message: &str) -> ! {
+    clap::Error::with_description(message, clap::ErrorKind::MissingRequiredArgument).exit()
+}
+
+/// Prints the problems of the configuration file, returning the exit code
+fn print_diagnostics(filename: &str, format: Option<ConfigFormat>) -> i32 {
+    match validate_config_file(filename, format) {
+        Ok(diagnostics) if diagnostics.is_empty() => {
+            println!("{}: configuration is valid", filename);
+            0
+        }
+        Ok(diagnostics) => {
+            for diagnostic in &diagnostics {
+                eprintln!("{}", diagnostic);
+            }
+            1
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
+
+/// Prints the effective configuration, returning the exit code
+fn print_effective_config(
+    filename: &str,
+    overlay: Option<&str>,
+    format: Option<ConfigFormat>,
+) -> i32 {
+    match effective_config(filename, overlay, format)
+        .and_then(|c| toml::to_string_pretty(&c).map_err(|e| Error::Config(e.to_string())))
+    {
+        Ok(config) => {
+            println!("{}", config);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
+
+/// Parses the nuker configuration file, the format is selected by the
+/// extension of the file
+pub fn parse_config_file(filename: &str) -> Result<Config> {
+    load_config_file(filename, None, None)
+}
+
+/// Parses the nuker configuration file along with the files it includes,
+/// applying the named overlay on top when provided. Every file is validated
+/// first and all the problems found are reported in the error. The format of
+/// the file is selected by its extension when not provided.
+pub fn load_config_file(
+    filename: &str,
+    overlay: Option<&str>,
+    format: Option<ConfigFormat>,
+) -> Result<Config> {
+    build_config(effective_config(filename, overlay, format)?)
+}
+
+/// Parses the nuker configuration from a TOML document, the configuration is
+/// validated first and all the problems found are reported in the error.
+/// Included files are resolved relative to the working directory.
+pub fn parse_config(buffer: &str) -> Result<Config> {
+    parse_config_document(buffer, ConfigFormat::Toml)
+}
+
+/// Parses the nuker configuration from a document in the given format, see
+/// [`parse_config`]
+pub fn parse_config_document(buffer: &str, format: ConfigFormat) -> Result<Config> {
+    check_diagnostics(validate_document(buffer, format))?;
+
+    let document = parse_document(buffer, format)?;
+    let document = resolve_includes(document, Path::new("."), format, &mut Vec::new())?;
+
+    build_config(apply_layers(document, None)?)
+}
+
+/// Effective configuration document of the file: the included files are
+/// merged, then the named overlay and the `[defaults]` applied. Resource
+/// sections are keyed by the name of the resource type.
+pub fn effective_config(
+    filename: &str,
+    overlay: Option<&str>,
+    format: Option<ConfigFormat>,
+) -> Result<Value> {
+    check_diagnostics(validate_config_file(filename, format)?)?;
+
+    let path = Path::new(filename);
+    let format = format.unwrap_or_else(|| ConfigFormat::of(path, ConfigFormat::Toml));
+    let document = load_document(path, format, &mut Vec::new())?;
+    apply_layers(document, overlay)
+}
+
+/// Parses a configuration document into a TOML document, the configuration
+/// is handled the same whatever the format. Environment variables and files
+/// referred to by the string values are interpolated.
+pub(crate) fn parse_document(buffer: &str, format: ConfigFormat) -> Result<Value> {
+    let document: StdResult<Value, String> = match format {
+        ConfigFormat::Toml => toml::from_str(buffer).map_err(|e| e.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_str(buffer).map_err(|e| e.to_string()),
+        ConfigFormat::Json => serde_json::from_str(buffer).map_err(|e| e.to_string()),
     };
+    let mut document = document.map_err(Error::Config)?;
 
-    let mut buffer = String::new();
-    fp.read_to_string(&mut buffer).unwrap();
-    parse_config(&buffer)
+    match interpolate_document(&mut document).first() {
+        Some((path, message)) => Err(Error::Config(format!("{}: {}", format_path(path), message))),
+        None => Ok(document),
+    }
+}
+
+/// Files making up the configuration along with their format, the file
+/// followed by the files it includes. Included files without a known
+/// extension are assumed to be in the format of the including file.
+pub(crate) fn config_files(
+    filename: &str,
+    format: ConfigFormat,
+) -> Result<Vec<(String, ConfigFormat)>> {
+    let mut files = vec![(
+        canonical(Path::new(filename)).to_string_lossy().to_string(),
+        format,
+    )];
+    let mut i = 0;
+
+    while i < files.len() {
+        let (file, format) = files[i].clone();
+        let path = PathBuf::from(&file);
+        // Includes of files that do not parse are not followed, the parse
+        // error is reported by the validation
+        if let Ok(document) = parse_document(&read_config_file(&file)?, format) {
+            for include in includes(&document) {
+                let include = relative_to(&path, &include);
+                let include_format = ConfigFormat::of(&include, format);
+                let include = canonical(&include).to_string_lossy().to_string();
+                if !files.iter().any(|(f, _)| *f == include) {
+                    files.push((include, include_format));
+                }
+            }
+        }
+        i += 1;
+    }
+
+    Ok(files)
+}
+
+fn check_diagnostics(diagnostics: Vec<Diagnostic>) -> Result<()> {
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+
+    Err(Error::Config(
+        diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join("\n"),
+    ))
+}
+
+/// Loads the document merging the files it includes, in order, underneath it
+fn load_document(path: &Path, format: ConfigFormat, seen: &mut Vec<PathBuf>) -> Result<Value> {
+    let buffer = read_config_file(&path.to_string_lossy())?;
+    let document = parse_document(&buffer, format)?;
+
+    seen.push(canonical(path));
+    let document = resolve_includes(document, path, format, seen)?;
+    seen.pop();
+
+    Ok(document)
+}
+
+fn resolve_includes(
+    mut document: Value,
+    path: &Path,
+    format: ConfigFormat,
+    seen: &mut Vec<PathBuf>,
+) -> Result<Value> {
+    let files: Vec<PathBuf> = includes(&document)
+        .iter()
+        .map(|include| relative_to(path, include))
+        .collect();
+    if let Some(table) = document.as_table_mut() {
+        table.remove(INCLUDE_KEY);
+    }
+
+    let mut merged = Value::Table(Table::new());
+    for file in files {
+        if seen.contains(&canonical(&file)) {
+            return Err(Error::Config(format!(
+                "{} is included recursively",
+                file.display()
+            )));
+        }
+        let include_format = ConfigFormat::of(&file, format);
+        merge(&mut merged, load_document(&file, include_format, seen)?);
+    }
+    merge(&mut merged, document);
+
+    Ok(merged)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn includes(document: &Value) -> Vec<String> {
+    document
+        .get(INCLUDE_KEY)
+        .and_then(Value::as_array)
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|f| f.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Included files are relative to the directory of the including file
+fn relative_to(path: &Path, include: &str) -> PathBuf {
+    match path.parent() {
+        Some(dir) if Path::new(include).is_relative() => dir.join(include),
+        _ => PathBuf::from(include),
+    }
+}
+
+/// Applies the named overlay on top of the document and the `[defaults]`
+/// underneath every resource section, resource sections named by an alias
+/// are renamed to the name of the resource type. Every registered resource
+/// type inherits the defaults, whether it is configured in the document or
+/// not, and the defaults are kept for the resource types registered later.
+fn apply_layers(mut document: Value, overlay: Option<&str>) -> Result<Value> {
+    let table = match document.as_table_mut() {
+        Some(table) => table,
+        None => return Ok(document),
+    };
+    let mut overlays = table.remove(OVERLAYS_KEY);
+
+    if let Some(name) = overlay {
+        match overlays
+            .as_mut()
+            .and_then(|o| o.as_table_mut())
+            .and_then(|o| o.remove(name))
+        {
+            Some(overlay) => merge(&mut document, overlay),
+            None => return Err(Error::Config(format!("overlay not found: {}", name))),
+        }
+    }
+
+    let table = document.as_table_mut().unwrap();
+    let defaults = table.remove(DEFAULTS_KEY);
+    let sections: Vec<String> = table.keys().cloned().collect();
+
+    for key in sections {
+        if let Some(client) = registry::lookup(&key) {
+            let section = table.remove(&key).unwrap();
+            let mut resource = defaults
+                .clone()
+                .unwrap_or_else(|| Value::Table(Table::new()));
+            merge(&mut resource, section);
+
+            match table.get_mut(client.name()) {
+                Some(existing) => merge(existing, resource),
+                None => {
+                    table.insert(client.name().to_string(), resource);
+                }
+            }
+        }
+    }
+
+    if let Some(defaults) = defaults {
+        for client in Client::iter() {
+            table
+                .entry(client.name().to_string())
+                .or_insert_with(|| defaults.clone());
+        }
+        table.insert(DEFAULTS_KEY.to_string(), defaults);
+    }
+
+    // The overrides are merged on top of the resource sections, which
+    // include the defaults already
+    if let Some(overrides) = table.get_mut(OVERRIDES_KEY).and_then(Value::as_array_mut) {
+        for ov in overrides.iter_mut().filter_map(Value::as_table_mut) {
+            let keys: Vec<String> = ov.keys().cloned().collect();
+
+            for key in keys {
+                if let Some(client) = registry::lookup(&key) {
+                    let section = ov.remove(&key).unwrap();
+
+                    match ov.get_mut(client.name()) {
+                        Some(existing) => merge(existing, section),
+                        None => {
+                            ov.insert(client.name().to_string(), section);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(document)
 }
 
-pub fn parse_config(buffer: &str) -> Config {
-    let config: ParsedConfig = toml::from_str(buffer).expect("could not parse toml configuration");
+/// Deep merges the tables of `other` into `base`, any other value of `other`
+/// (including arrays) replaces the value of `base`
+fn merge(base: &mut Value, other: Value) {
+    match (base, other) {
+        (Value::Table(base), Value::Table(other)) => {
+            for (key, value) in other {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, other) => *base = other,
+    }
+}
+
+fn build_config(mut document: Value) -> Result<Config> {
+    let mut overrides = Vec::new();
+    let mut sections = HashMap::new();
+    let mut defaults = None;
+
+    if let Some(table) = document.as_table_mut() {
+        defaults = table.remove(DEFAULTS_KEY);
+
+        if let Some(Value::Array(values)) = table.remove(OVERRIDES_KEY) {
+            for value in values {
+                overrides.push(parse_override(value)?);
+            }
+        }
+
+        for (key, value) in table.iter() {
+            if let Some(client) = registry::lookup(key) {
+                sections.insert(client, value.clone());
+            }
+        }
+    }
+
+    let mut config: ParsedConfig = document
+        .try_into()
+        .map_err(|e| Error::Config(e.to_string()))?;
     let mut config_map: HashMap<Client, ResourceConfig> = HashMap::new();
 
-    config_map.insert(Client::Asg, config.asg);
-    config_map.insert(Client::Ec2Instance, config.ec2_instance);
-    config_map.insert(Client::Ec2Sg, config.ec2_sg);
-    config_map.insert(Client::Ec2Eni, config.ec2_eni);
-    config_map.insert(Client::Ec2Address, config.ec2_address);
-    config_map.insert(Client::EbsVolume, config.ebs_volume);
-    config_map.insert(Client::Ec2Vpc, config.ec2_vpc);
-    config_map.insert(Client::EbsSnapshot, config.ebs_snapshot);
-    config_map.insert(Client::RdsInstance, config.rds_instance);
-    config_map.insert(Client::RdsCluster, config.rds_cluster);
-    config_map.insert(Client::EcsCluster, config.ecs_cluster);
-    config_map.insert(Client::ElbAlb, config.elb_alb);
-    config_map.insert(Client::ElbNlb, config.elb_nlb);
-    config_map.insert(Client::EmrCluster, config.emr_cluster);
-    config_map.insert(Client::EsDomain, config.es_domain);
-    config_map.insert(Client::GlueEndpoint, config.glue_endpoint);
-    config_map.insert(Client::RsCluster, config.rs_cluster);
-    config_map.insert(Client::SagemakerNotebook, config.sagemaker_notebook);
-    config_map.insert(Client::S3Bucket, config.s3_bucket);
-    config_map.insert(Client::Ec2Igw, config.ec2_igw);
-    config_map.insert(Client::Ec2Subnet, config.ec2_subnet);
-    config_map.insert(Client::Ec2RouteTable, config.ec2_rt);
-    config_map.insert(Client::Ec2NetworkACL, config.ec2_network_acl);
-    config_map.insert(Client::Ec2NatGW, config.ec2_nat_gw);
-    config_map.insert(Client::Ec2VpnGW, config.ec2_vpn_gw);
-    config_map.insert(Client::Ec2VpcEndpoint, config.ec2_vpc_endpoint);
-    config_map.insert(Client::Ec2PeeringConnection, config.ec2_peering_connection);
-    config_map.insert(Client::EksCluster, config.eks_cluster);
+    for client in Client::iter() {
+        let resource_type = registry::get(&client).unwrap();
+        let r_config = std::iter::once(client.name())
+            .chain(resource_type.aliases.iter().copied())
+            .find_map(|name| config.resources.remove(name))
+            .unwrap_or_else(resource_type.default_config);
+
+        config_map.insert(client, r_config);
+    }
 
     // Compile all regex expressions up front
     for (_client, r_config) in &mut config_map {
-        if let Some(req_tags) = r_config.required_tags.as_mut() {
-            for rt in req_tags {
-                if let Some(pattern) = rt.pattern.as_mut() {
-                    rt.regex = compile_regex(pattern.as_str());
-                }
-            }
+        compile_regexes(r_config);
+        compile_globals(r_config, &config.whitelist, config.min_age);
+    }
+    let defaults = defaults
+        .map(|value| resource_config(value, &config.whitelist, config.min_age))
+        .transpose()?;
+
+    Ok(Config {
+        regions: config.regions,
+        endpoints: config.endpoints,
+        hooks: config.hooks,
+        policy: config.policy,
+        whitelist: config.whitelist,
+        min_age: config.min_age,
+        resources: config_map,
+        defaults,
+        sections,
+        overrides,
+        excluded: Vec::new(),
+    })
+}
+
+fn parse_override(value: Value) -> Result<Override> {
+    let mut table = match value {
+        Value::Table(table) => table,
+        other => {
+            return Err(Error::Config(format!(
+                "overrides: expected a table, found {}",
+                other.type_str()
+            )))
+        }
+    };
+    let mut patterns = |key: &str| -> Result<Vec<String>> {
+        match table.remove(key) {
+            Some(value) => value.try_into().map_err(|e| Error::Config(e.to_string())),
+            None => Ok(Vec::new()),
         }
+    };
+    let regions = patterns(OVERRIDE_REGIONS_KEY)?;
+    let accounts = patterns(OVERRIDE_ACCOUNTS_KEY)?;
+    let exclude = patterns(OVERRIDE_EXCLUDE_KEY)?
+        .iter()
+        .filter_map(|name| registry::lookup(name))
+        .collect();
+    let resources = table
+        .into_iter()
+        .filter_map(|(key, value)| registry::lookup(&key).map(|client| (client, value)))
+        .collect();
 
-        if let Some(naming_prefix) = r_config.naming_prefix.as_mut() {
-            naming_prefix.regex = compile_regex(naming_prefix.pattern.as_str());
+    Ok(Override {
+        regions,
+        accounts,
+        exclude,
+        resources,
+    })
+}
+
+fn resource_config(
+    value: Value,
+    whitelist: &[String],
+    min_age: Option<Duration>,
+) -> Result<ResourceConfig> {
+    let mut config: ResourceConfig = value.try_into().map_err(|e| Error::Config(e.to_string()))?;
+    compile_regexes(&mut config);
+    compile_globals(&mut config, whitelist, min_age);
+
+    Ok(config)
+}
+
+/// Applies the global configuration to the resource type: the global
+/// whitelist and the global minimum age, unless the resource type has one
+fn compile_globals(config: &mut ResourceConfig, whitelist: &[String], min_age: Option<Duration>) {
+    compile_whitelist(config, whitelist);
+    config.min_age = config.min_age.or(min_age);
+}
+
+/// Compiles the whitelist of the resource type followed by the global
+/// whitelist, invalid entries are reported by the validation and skipped
+fn compile_whitelist(config: &mut ResourceConfig, global: &[String]) {
+    config.whitelist_entries = config
+        .whitelist
+        .iter()
+        .flatten()
+        .chain(global)
+        .filter_map(|entry| match WhitelistEntry::parse(entry) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                warn!("Failed compiling whitelist entry: {}", err);
+                None
+            }
+        })
+        .collect();
+}
+
+fn compile_regexes(config: &mut ResourceConfig) {
+    if let Some(req_tags) = config.required_tags.as_mut() {
+        for rt in req_tags {
+            if let Some(pattern) = rt.pattern.as_mut() {
+                rt.regex = compile_regex(pattern.as_str());
+            }
         }
+    }
 
-        // if let Some(manage_stopped) = &mut config.ec2_instance.manage_stopped {
-        //     manage_stopped.dt_extract_regex = compile_regex(r"^.*\((?P<datetime>.*)\)$");
-        // }
+    if let Some(naming_prefix) = config.naming_prefix.as_mut() {
+        naming_prefix.regex = compile_regex(naming_prefix.pattern.as_str());
     }
 
-    config_map
+    // if let Some(manage_stopped) = &mut config.ec2_instance.manage_stopped {
+    //     manage_stopped.dt_extract_regex = compile_regex(r"^.*\((?P<datetime>.*)\)$");
+    // }
 }
 
 fn compile_regex(pattern: &str) -> Option<Regex> {
@@ -451,6 +1669,347 @@ fn compile_regex(pattern: &str) -> Option<Regex> {
     }
 }
 
-fn default_resource_config() -> ResourceConfig {
-    ResourceConfig::default()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::EnforcementState;
+
+    #[test]
+    fn apply_defaults_and_overlay() {
+        let document: Value = toml::from_str(
+            r#"
+[defaults]
+max_run_time = "7 days"
+allowed_types = ["t3.micro"]
+
+[ec2_instance]
+allowed_types = ["t3.nano"]
+
+[rds_instance]
+target_state = "Stopped"
+
+[overlays.sandbox.defaults]
+max_run_time = "1 day"
+"#,
+        )
+        .unwrap();
+
+        let base = apply_layers(document.clone(), None).unwrap();
+        assert_eq!(
+            base["ec2_instance"]["max_run_time"].as_str(),
+            Some("7 days")
+        );
+        assert_eq!(
+            base["ec2_instance"]["allowed_types"],
+            Value::Array(vec![Value::String("t3.nano".to_string())])
+        );
+        // Aliases are renamed to the name of the resource type
+        assert_eq!(base["rds"]["target_state"].as_str(), Some("Stopped"));
+        // Resource types missing from the document inherit the defaults
+        assert_eq!(base["s3_bucket"]["max_run_time"].as_str(), Some("7 days"));
+        assert_eq!(base["defaults"]["max_run_time"].as_str(), Some("7 days"));
+        assert!(base.get("overlays").is_none());
+
+        let config = build_config(base).unwrap();
+        assert_eq!(
+            config.resource(&Client::S3Bucket).max_run_time,
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            config.resource(&Client::new("custom")).max_run_time,
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+
+        let sandbox = apply_layers(document.clone(), Some("sandbox")).unwrap();
+        assert_eq!(sandbox["rds"]["max_run_time"].as_str(), Some("1 day"));
+
+        assert!(apply_layers(document, Some("prod")).is_err());
+    }
+
+    #[test]
+    fn apply_overrides_in_scope() {
+        let config = parse_config(
+            r#"
+[ec2_instance]
+allowed_types = ["t3.nano"]
+max_run_time = "7 days"
+
+[[overrides]]
+regions = ["us-east-*"]
+
+    [overrides.ec2_instance]
+    allowed_types = ["m5.large"]
+
+[[overrides]]
+accounts = ["123456789012"]
+exclude = ["sagemaker_notebook"]
+
+    [overrides.ec2_instance]
+    max_run_time = "1 day"
+"#,
+        )
+        .unwrap();
+
+        let east = config.for_scope("us-east-1", "000000000000").unwrap();
+        let ec2 = east.get(&Client::Ec2Instance).unwrap();
+        assert_eq!(ec2.allowed_types, Some(vec!["m5.large".to_string()]));
+        assert_eq!(ec2.max_run_time, Some(Duration::from_secs(7 * 86400)));
+
+        let west = config.for_scope("us-west-2", "123456789012").unwrap();
+        assert_eq!(
+            west.get(&Client::Ec2Instance).unwrap().allowed_types,
+            Some(vec!["t3.nano".to_string()])
+        );
+        assert_eq!(
+            west.get(&Client::Ec2Instance).unwrap().max_run_time,
+            Some(Duration::from_secs(86400))
+        );
+        assert_eq!(west.excluded(), &[Client::SagemakerNotebook]);
+        assert!(east.excluded().is_empty());
+    }
+
+    #[test]
+    fn parse_formats_alike() {
+        let toml = "[ec2_instance]\nmax_run_time = \"7 days\"\n\n    [[ec2_instance.required_tags]]\n    name = \"Owner\"\n";
+        let yaml = "ec2_instance:\n  max_run_time: 7 days\n  required_tags:\n    - name: Owner\n";
+        let json =
+            r#"{"ec2_instance": {"max_run_time": "7 days", "required_tags": [{"name": "Owner"}]}}"#;
+
+        let document = parse_document(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(parse_document(yaml, ConfigFormat::Yaml).unwrap(), document);
+        assert_eq!(parse_document(json, ConfigFormat::Json).unwrap(), document);
+
+        let config = parse_config_document(yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(
+            config.get(&Client::Ec2Instance).unwrap().max_run_time,
+            Some(Duration::from_secs(7 * 86400))
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("nuker.yml"), ConfigFormat::Toml),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn match_whitelist_entries() {
+        let config = parse_config(
+            r#"
+whitelist = ["team=platform"]
+
+[ec2_instance]
+whitelist = ["i-0123", "arn:aws:ec2:*:*:instance/i-9*", "/^shared-/"]
+"#,
+        )
+        .unwrap();
+        let whitelisted = |id: &str, name: &str, team: &str| {
+            let resource = Resource {
+                id: id.to_string(),
+                arn: Some(format!(
+                    "arn:aws:ec2:us-east-1:123456789012:instance/{}",
+                    id
+                )),
+                tags: Some(vec![
+                    NTag {
+                        key: Some("Name".to_string()),
+                        value: Some(name.to_string()),
+                    },
+                    NTag {
+                        key: Some("team".to_string()),
+                        value: Some(team.to_string()),
+                    },
+                ]),
+                ..Default::default()
+            };
+
+            config
+                .get(&Client::Ec2Instance)
+                .unwrap()
+                .whitelist_entries
+                .iter()
+                .any(|e| e.matches(&resource))
+        };
+
+        assert!(whitelisted("i-0123", "web", "data"));
+        assert!(whitelisted("i-9876", "web", "data"));
+        assert!(whitelisted("i-4567", "shared-vpn", "data"));
+        assert!(whitelisted("i-4567", "web", "platform"));
+        assert!(!whitelisted("i-4567", "web", "data"));
+
+        // The global whitelist applies to every resource type
+        assert_eq!(
+            config.get(&Client::S3Bucket).unwrap().whitelist_entries[0].entry,
+            "team=platform"
+        );
+        assert!(WhitelistEntry::parse("/((/").is_err());
+    }
+
+    #[test]
+    fn compare_required_tags() {
+        let config = parse_config(
+            r#"
+[[ec2_instance.required_tags]]
+one_of = ["Owner", "Team"]
+ignore_case = true
+
+[[ec2_instance.required_tags]]
+name = "env"
+allowed_values = ["dev", "prod"]
+
+[[ec2_instance.required_tags]]
+name = "CostCenter"
+when = { env = "prod" }
+"#,
+        )
+        .unwrap();
+        let required_tags = config
+            .get(&Client::Ec2Instance)
+            .unwrap()
+            .required_tags
+            .clone()
+            .unwrap();
+        let violated = |tags: &[(Option<&str>, Option<&str>)]| {
+            let tags = tags
+                .iter()
+                .map(|(key, value)| NTag {
+                    key: key.map(String::from),
+                    value: value.map(String::from),
+                })
+                .collect();
+            crate::util::compare_tags(Some(tags), &required_tags)
+        };
+
+        assert!(!violated(&[
+            (Some("team"), Some("a")),
+            (Some("env"), Some("dev"))
+        ]));
+        assert!(violated(&[
+            (Some("team"), Some("a")),
+            (Some("env"), Some("qa"))
+        ]));
+        assert!(violated(&[(Some("env"), Some("dev"))]));
+        assert!(violated(&[
+            (Some("owner"), Some("a")),
+            (Some("env"), Some("prod"))
+        ]));
+        assert!(!violated(&[
+            (Some("owner"), Some("a")),
+            (Some("env"), Some("prod")),
+            (Some("CostCenter"), Some("42")),
+        ]));
+        // Tags without a key or a value do not panic
+        assert!(violated(&[(None, Some("a")), (Some("Owner"), None)]));
+    }
+
+    #[test]
+    fn combine_idle_rules() {
+        let config = parse_config(
+            r#"
+[ec2_instance]
+idle_match = "any"
+
+[[ec2_instance.metric_filters]]
+name = "CPUUtilization"
+statistic = "Maximum"
+duration = "1d"
+period = "1h"
+op = "lt"
+value = 5
+no_datapoints = "unknown"
+"#,
+        )
+        .unwrap();
+        let ec2_instance = config.get(&Client::Ec2Instance).unwrap();
+        let metric_filters = ec2_instance.metric_filters.as_ref().unwrap();
+
+        assert_eq!(ec2_instance.idle_match, IdleMatch::Any);
+        assert_eq!(metric_filters[0].no_datapoints, Some(IdleState::Unknown));
+
+        let (idle, not_idle, unknown) = (IdleState::Idle, IdleState::NotIdle, IdleState::Unknown);
+        assert!(IdleMatch::All.is_idle(&[idle, idle]));
+        assert!(!IdleMatch::All.is_idle(&[idle, not_idle]));
+        assert!(IdleMatch::Any.is_idle(&[not_idle, idle]));
+        // Rules in an unknown state are ignored
+        assert!(IdleMatch::All.is_idle(&[idle, unknown]));
+        assert!(!IdleMatch::All.is_idle(&[unknown]));
+        assert!(!IdleMatch::Any.is_idle(&[]));
+    }
+
+    #[test]
+    fn inherit_min_age() {
+        let config = parse_config(
+            r#"
+min_age = "30m"
+
+[ec2_instance]
+min_age = "2h"
+"#,
+        )
+        .unwrap();
+        let min_age = |client: &Client| config.resource(client).min_age;
+
+        assert_eq!(
+            min_age(&Client::Ec2Instance),
+            Some(Duration::from_secs(7200))
+        );
+        assert_eq!(min_age(&Client::S3Bucket), Some(Duration::from_secs(1800)));
+    }
+
+    #[test]
+    fn parse_tag_remediation() {
+        let config = parse_config(
+            r#"
+[ebs_volume]
+target_state = "Tagged"
+
+[[ebs_volume.required_tags]]
+name = "Owner"
+infer = "creator"
+default = "unknown"
+
+[[ebs_volume.required_tags]]
+name = "env"
+allowed_values = ["dev", "prod"]
+"#,
+        )
+        .unwrap();
+        let ebs_volume = config.get(&Client::EbsVolume).unwrap();
+        let required_tags = ebs_volume.required_tags.as_ref().unwrap();
+        let tags = vec![NTag {
+            key: Some("env".to_string()),
+            value: Some("qa".to_string()),
+        }];
+
+        assert_eq!(ebs_volume.target_state, TargetState::Tagged);
+        assert_eq!(required_tags[0].infer, Some(TagSource::Creator));
+        assert_eq!(required_tags[0].default.as_deref(), Some("unknown"));
+        assert!(required_tags[0].is_violated(&tags));
+        assert!(required_tags[1].is_violated(&tags));
+        // Only the required tags are remediated when tagging
+        assert_eq!(
+            EnforcementState::from_tag_rule(&ebs_volume.target_state),
+            EnforcementState::Tag
+        );
+        assert_eq!(
+            EnforcementState::from_target_state(&ebs_volume.target_state),
+            EnforcementState::Skip
+        );
+    }
+
+    #[test]
+    fn resolve_endpoints_of_unknown_regions() {
+        let endpoints = EndpointsConfig::default();
+        let region = crate::aws::region_from_name("il-central-1");
+
+        assert_eq!(
+            endpoints.region("sagemaker", &region),
+            Region::Custom {
+                name: "il-central-1".to_string(),
+                endpoint: "https://api.sagemaker.il-central-1.amazonaws.com".to_string(),
+            }
+        );
+        assert_eq!(
+            endpoints.region("ec2", &crate::aws::region_from_name("us-east-1")),
+            Region::UsEast1
+        );
+    }
 }
@@ -17,6 +17,15 @@ struct ErrorResponseError {
     message: String,
 }
 
+/// Error returned by the services using the JSON protocol
+#[derive(Deserialize, Debug)]
+struct JsonErrorResponse {
+    #[serde(rename = "__type")]
+    type_: String,
+    #[serde(default, alias = "Message")]
+    message: String,
+}
+
 #[derive(Debug, Fail)]
 pub enum NError {
     #[fail(display = "failed with internal service request: {} - {}", type_, msg)]
@@ -27,8 +36,14 @@ pub enum NError {
     Throttling(String),
     #[fail(display = "validation failure: {}", _0)]
     Validation(String),
+    #[fail(display = "failed parsing the configuration: {}", _0)]
+    Config(String),
+    #[fail(display = "failed evaluating the policy: {}", _0)]
+    Policy(String),
     #[fail(display = "dag failure: {}", _0)]
     Dag(String),
+    #[fail(display = "failed tagging the resource: {}", _0)]
+    Tagging(String),
     #[fail(display = "failed with provided credentials: {}", e)]
     InvalidCredentials {
         e: rusoto_credential::CredentialsError,
@@ -54,6 +69,11 @@ impl<E: StdError + 'static> From<RusotoError<E>> for NError {
                         code => trace!("unmatched error code {}", code),
                     }
                 }
+                if let Ok(error) = serde_json::from_slice::<JsonErrorResponse>(body.as_ref()) {
+                    if error.type_.ends_with("ThrottlingException") {
+                        return NError::Throttling(error.message);
+                    }
+                }
                 NError::Rusoto {
                     type_: "Unknown".to_string(),
                     msg: format!("{}", err),
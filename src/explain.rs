@@ -0,0 +1,428 @@
+//! Explains the enforcement decision of a resource
+//!
+//! `filter_resource` stops at the first rule that matches a resource, an
+//! explanation evaluates every rule instead and records the values each rule
+//! was evaluated against: the tag values and patterns, the allowed types,
+//! thresholds and the CloudWatch datapoints used.
+use crate::{
+    aws::MetricEvaluation,
+    config::ResourceConfig,
+    policy::{Decision, PolicyEngine},
+    resource::{EnforcementReason, EnforcementState, Resource},
+};
+use colored::*;
+use std::fmt;
+
+pub const WHITELIST_RULE: &str = "whitelist";
+pub const MIN_AGE_RULE: &str = "min_age";
+pub const POLICY_RULE: &str = "policy";
+pub const STATE_RULE: &str = "state";
+pub const TAG_RULE: &str = "required_tags";
+pub const ALLOWED_TYPE_RULE: &str = "allowed_types";
+pub const RUNTIME_RULE: &str = "max_run_time";
+pub const IDLE_RULE: &str = "idle_rules";
+pub const NAME_RULE: &str = "naming_prefix";
+pub const ADDITIONAL_RULE: &str = "additional_rules";
+
+/// Outcome of a rule for a resource
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The rule matched, the resource is enforced (or skipped for the
+    /// whitelist, minimum age and state rules)
+    Matched,
+    Passed,
+    /// The rule is not configured for the resource type
+    NotConfigured,
+}
+
+impl Outcome {
+    pub fn from_match(matched: bool) -> Self {
+        if matched {
+            Outcome::Matched
+        } else {
+            Outcome::Passed
+        }
+    }
+
+    pub fn name(&self) -> colored::ColoredString {
+        match *self {
+            Outcome::Matched => "matched".red().bold(),
+            Outcome::Passed => "passed".green(),
+            Outcome::NotConfigured => "-".dimmed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleEvaluation {
+    pub rule: &'static str,
+    pub outcome: Outcome,
+    /// Values the rule was evaluated against
+    pub details: Vec<String>,
+}
+
+impl RuleEvaluation {
+    pub fn new(rule: &'static str, outcome: Outcome, details: Vec<String>) -> Self {
+        RuleEvaluation {
+            rule,
+            outcome,
+            details,
+        }
+    }
+
+    pub fn not_configured(rule: &'static str) -> Self {
+        RuleEvaluation::new(rule, Outcome::NotConfigured, Vec::new())
+    }
+}
+
+/// Evaluations of all the rules for a resource, along with the enforcement
+/// state the resource was given
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub resource: Resource,
+    pub rules: Vec<RuleEvaluation>,
+}
+
+impl Explanation {
+    /// Rule that decided the enforcement state of the resource, rules are
+    /// applied in the order they are evaluated
+    pub fn deciding_rule(&self) -> Option<&RuleEvaluation> {
+        self.rules.iter().find(|r| r.outcome == Outcome::Matched)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.resource)?;
+        if let Some(ref arn) = self.resource.arn {
+            writeln!(f, "  arn: {}", arn)?;
+        }
+        writeln!(f, "  {:<18} {:<8} DETAILS", "RULE", "RESULT")?;
+
+        for rule in &self.rules {
+            let mut details = rule.details.iter();
+            writeln!(
+                f,
+                "  {:<18} {:<8} {}",
+                rule.rule,
+                rule.outcome.name(),
+                details.next().map(String::as_str).unwrap_or_default()
+            )?;
+            for detail in details {
+                writeln!(f, "{}{}", " ".repeat(30), detail)?;
+            }
+        }
+
+        write!(f, "  verdict: {}", self.resource.enforcement_state.name())?;
+        if let Some(ref reason) = self.resource.enforcement_reason {
+            write!(f, " ({})", reason.name())?;
+        }
+
+        // Dependent resources are removed along with the resource they
+        // depend on, regardless of their own rules
+        match self.deciding_rule() {
+            Some(rule) if self.resource.enforcement_state != EnforcementState::DeleteDependent => {
+                write!(f, ", decided by {}", rule.rule)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn explain_whitelist(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    if config.whitelist.is_none() && config.whitelist_entries.is_empty() {
+        return RuleEvaluation::not_configured(WHITELIST_RULE);
+    }
+
+    let entries: Vec<&str> = config
+        .whitelist_entries
+        .iter()
+        .map(|e| e.entry.as_str())
+        .collect();
+    let mut details = vec![format!("whitelist: {:?}", entries)];
+    if let Some(entry) = config
+        .whitelist_entries
+        .iter()
+        .find(|e| e.matches(resource))
+    {
+        details.push(format!("matched entry: {}", entry.entry));
+    }
+
+    RuleEvaluation::new(WHITELIST_RULE, Outcome::from_match(matched), details)
+}
+
+pub(crate) fn explain_min_age(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    match config.min_age {
+        Some(min_age) => RuleEvaluation::new(
+            MIN_AGE_RULE,
+            Outcome::from_match(matched),
+            vec![format!(
+                "created: {}, min age: {}",
+                resource
+                    .created
+                    .as_ref()
+                    .map(|created| created.to_string())
+                    .or_else(|| resource.start_time.clone())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                humantime::format_duration(min_age)
+            )],
+        ),
+        None => RuleEvaluation::not_configured(MIN_AGE_RULE),
+    }
+}
+
+pub(crate) fn explain_policy(
+    policy: Option<&PolicyEngine>,
+    decision: Option<Decision>,
+) -> RuleEvaluation {
+    if policy.is_none() {
+        return RuleEvaluation::not_configured(POLICY_RULE);
+    }
+
+    match decision {
+        Some(decision) => RuleEvaluation::new(
+            POLICY_RULE,
+            Outcome::Matched,
+            vec![format!(
+                "decision: {:?}, reason: {}",
+                decision.state,
+                decision
+                    .reason
+                    .as_ref()
+                    .map(EnforcementReason::name)
+                    .unwrap_or("-")
+            )],
+        ),
+        None => RuleEvaluation::new(
+            POLICY_RULE,
+            Outcome::Passed,
+            vec!["no decision returned".to_string()],
+        ),
+    }
+}
+
+pub(crate) fn explain_state(resource: &Resource, matched: bool) -> RuleEvaluation {
+    match resource.state {
+        Some(ref state) => RuleEvaluation::new(
+            STATE_RULE,
+            Outcome::from_match(matched),
+            vec![format!("state: {:?}", state)],
+        ),
+        None => RuleEvaluation::new(
+            STATE_RULE,
+            Outcome::from_match(matched),
+            vec!["state: unknown".to_string()],
+        ),
+    }
+}
+
+pub(crate) fn explain_tags(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    let required_tags = match config.required_tags {
+        Some(ref rt) => rt,
+        None => return RuleEvaluation::not_configured(TAG_RULE),
+    };
+    let tags = resource.tags.clone().unwrap_or_default();
+
+    let details = required_tags
+        .iter()
+        .map(|rt| {
+            let mut rule = rt
+                .pattern
+                .as_ref()
+                .map(|p| format!(" =~ /{}/", p))
+                .unwrap_or_default();
+            if let Some(ref allowed) = rt.allowed_values {
+                rule.push_str(&format!(" in {:?}", allowed));
+            }
+
+            if !rt.applies(&tags) {
+                return format!("{}{}: not required", rt, rule);
+            }
+
+            match rt.find(&tags) {
+                Some((_, value)) => format!(
+                    "{}{}: '{}' {}",
+                    rt,
+                    rule,
+                    value,
+                    if rt.allows(value) {
+                        "matches"
+                    } else {
+                        "does not match"
+                    }
+                ),
+                None => format!("{}{}: missing", rt, rule),
+            }
+        })
+        .collect();
+
+    RuleEvaluation::new(TAG_RULE, Outcome::from_match(matched), details)
+}
+
+pub(crate) fn explain_allowed_types(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    match config.allowed_types {
+        Some(ref allowed) => RuleEvaluation::new(
+            ALLOWED_TYPE_RULE,
+            Outcome::from_match(matched),
+            vec![format!(
+                "types: {:?}, allowed: {:?}",
+                resource.resource_type.as_deref().unwrap_or_default(),
+                allowed
+            )],
+        ),
+        None => RuleEvaluation::not_configured(ALLOWED_TYPE_RULE),
+    }
+}
+
+pub(crate) fn explain_runtime(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    match config.max_run_time {
+        Some(max_run_time) => RuleEvaluation::new(
+            RUNTIME_RULE,
+            Outcome::from_match(matched),
+            vec![format!(
+                "started: {}, max run time: {}",
+                resource.start_time.as_deref().unwrap_or("unknown"),
+                humantime::format_duration(max_run_time)
+            )],
+        ),
+        None => RuleEvaluation::not_configured(RUNTIME_RULE),
+    }
+}
+
+pub(crate) fn explain_idle(
+    config: &ResourceConfig,
+    evaluations: &[MetricEvaluation],
+    matched: bool,
+) -> RuleEvaluation {
+    if evaluations.is_empty() {
+        return RuleEvaluation::new(
+            IDLE_RULE,
+            Outcome::from_match(matched),
+            vec!["no idle rules evaluated".to_string()],
+        );
+    }
+
+    let rules = evaluations.iter().map(|e| {
+        let mf = &e.metric_filter;
+        let dimensions: Vec<String> = e
+            .dimensions
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        let datapoints = match e.datapoints {
+            Some(ref dps) => format!("datapoints: {:?}", dps),
+            None => "no metrics published".to_string(),
+        };
+
+        format!(
+            "{}({}) {} {} over {} (period {}) [{}]: {} -> {}",
+            mf.statistic,
+            mf.name,
+            mf.op,
+            mf.value,
+            humantime::format_duration(mf.duration),
+            humantime::format_duration(mf.period),
+            dimensions.join(", "),
+            datapoints,
+            e.state
+        )
+    });
+    let details = std::iter::once(format!(
+        "idle when {} of the rules match",
+        config.idle_match
+    ))
+    .chain(rules)
+    .collect();
+
+    RuleEvaluation::new(IDLE_RULE, Outcome::from_match(matched), details)
+}
+
+pub(crate) fn explain_naming_prefix(
+    resource: &Resource,
+    config: &ResourceConfig,
+    matched: bool,
+) -> RuleEvaluation {
+    match config.naming_prefix {
+        Some(ref np) => RuleEvaluation::new(
+            NAME_RULE,
+            Outcome::from_match(matched),
+            vec![format!("'{}' =~ /{}/", resource.id, np.pattern)],
+        ),
+        None => RuleEvaluation::not_configured(NAME_RULE),
+    }
+}
+
+pub(crate) fn explain_additional(matched: Option<bool>) -> RuleEvaluation {
+    match matched {
+        Some(matched) => {
+            RuleEvaluation::new(ADDITIONAL_RULE, Outcome::from_match(matched), Vec::new())
+        }
+        None => RuleEvaluation::not_configured(ADDITIONAL_RULE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::RequiredTag, resource::NTag};
+    use regex::Regex;
+
+    #[test]
+    fn explain_required_tags() {
+        let resource = Resource {
+            tags: Some(vec![NTag {
+                key: Some("Owner".to_string()),
+                value: Some("team-a".to_string()),
+            }]),
+            ..Default::default()
+        };
+        let config = ResourceConfig {
+            required_tags: Some(vec![
+                RequiredTag {
+                    name: "Owner".to_string(),
+                    pattern: Some("^team-b$".to_string()),
+                    regex: Regex::new("^team-b$").ok(),
+                    ..Default::default()
+                },
+                RequiredTag {
+                    name: "Env".to_string(),
+                    pattern: None,
+                    regex: None,
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let evaluation = explain_tags(&resource, &config, true);
+
+        assert_eq!(evaluation.outcome, Outcome::Matched);
+        assert_eq!(
+            evaluation.details,
+            vec![
+                "Owner =~ /^team-b$/: 'team-a' does not match".to_string(),
+                "Env: missing".to_string(),
+            ]
+        );
+    }
+}
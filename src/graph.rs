@@ -1,5 +1,6 @@
 //! Graph utility to create DAG for tracking `Resource` dependencies.
 
+use crate::client::ClientType;
 use crate::resource::Resource;
 use crate::{Error, Result};
 use petgraph::{
@@ -17,14 +18,17 @@ pub enum Relation {
 
 pub struct Dag {
     pub graph: Graph<Resource, Relation>,
-    id_map: HashMap<String, NodeIndex<u32>>,
+    /// Resources are keyed by their type along with the id, as resources of
+    /// different types can share the same id (e.g. SageMaker endpoints and
+    /// their endpoint configs).
+    id_map: HashMap<(ClientType, String), NodeIndex<u32>>,
 }
 
 impl Dag {
     /// Create a Dag with "root" node
     pub fn new() -> Self {
         let graph: Graph<Resource, Relation> = Graph::new();
-        let id_map: HashMap<String, NodeIndex<u32>> = HashMap::new();
+        let id_map: HashMap<(ClientType, String), NodeIndex<u32>> = HashMap::new();
 
         Dag { graph, id_map }
     }
@@ -42,22 +46,22 @@ impl Dag {
 
     /// Add a given Resource to the DAG
     pub fn add_node_to_dag(&mut self, mut r: Resource) {
-        let resource_id = r.id.clone();
+        let resource_key = (r.type_, r.id.clone());
         let resource_dependencies = r.dependencies.take();
 
-        let root_index = if self.id_map.contains_key(&r.id) {
-            *self.id_map.get(&r.id).unwrap()
+        let root_index = if self.id_map.contains_key(&resource_key) {
+            *self.id_map.get(&resource_key).unwrap()
         } else {
             let rid = self.graph.add_node(r);
-            self.id_map.insert(resource_id.clone(), rid);
+            self.id_map.insert(resource_key, rid);
             rid
         };
 
         if let Some(dependencies) = resource_dependencies {
             for dep in dependencies {
-                let dep_id = dep.id.clone();
-                let dep_index = if self.id_map.contains_key(&dep.id) {
-                    let tid = *self.id_map.get(&dep.id).unwrap();
+                let dep_key = (dep.type_, dep.id.clone());
+                let dep_index = if self.id_map.contains_key(&dep_key) {
+                    let tid = *self.id_map.get(&dep_key).unwrap();
 
                     if let Some(node) = self.graph.node_weight_mut(tid) {
                         // Replace the existing resource with dependent's state
@@ -67,7 +71,7 @@ impl Dag {
                     tid
                 } else {
                     let rid = self.graph.add_node(dep);
-                    self.id_map.insert(dep_id.clone(), rid);
+                    self.id_map.insert(dep_key, rid);
                     rid
                 };
 
@@ -0,0 +1,288 @@
+//! Lifecycle hooks
+//!
+//! Hooks are invoked before and after a resource type is scanned, before and
+//! after a resource is stopped or removed, and at the end of a run. Hooks are
+//! either Rust callbacks implementing `Hook` or external commands configured
+//! in the `[hooks]` section of the configuration.
+use crate::{
+    client::Client,
+    config::HooksConfig,
+    resource::{EnforcementState, Resource},
+    util, Result,
+};
+use async_trait::async_trait;
+use rusoto_core::Region;
+use std::sync::Arc;
+use tracing::{debug, error, warn};
+
+const PRE_SCAN: &str = "pre_scan";
+const POST_SCAN: &str = "post_scan";
+const PRE_DELETE: &str = "pre_delete";
+const POST_DELETE: &str = "post_delete";
+const RUN_END: &str = "run_end";
+
+/// Callbacks invoked at the lifecycle points of a run, all the callbacks
+/// default to doing nothing. Hooks are invoked in dry runs as well.
+#[async_trait]
+pub trait Hook: Send + Sync {
+    /// Invoked before a resource type is scanned in a region
+    async fn pre_scan(&self, _resource_type: Client, _region: &Region) {}
+
+    /// Invoked with the evaluated resources once a resource type is scanned
+    /// in a region
+    async fn post_scan(&self, _resource_type: Client, _region: &Region, _resources: &[Resource]) {}
+
+    /// Invoked before a resource is stopped or removed, returning `false`
+    /// vetoes the stop or removal of the resource.
+    async fn pre_delete(&self, _resource: &Resource) -> bool {
+        true
+    }
+
+    /// Invoked after a resource is stopped or removed, along with the outcome
+    async fn post_delete(&self, _resource: &Resource, _result: &Result<()>) {}
+
+    /// Invoked with the resources of the run once they are cleaned up
+    async fn run_end(&self, _resources: &[Resource]) {}
+}
+
+/// Hooks registered for a run, invoked in the order they are added
+#[derive(Clone, Default)]
+pub struct Hooks {
+    hooks: Vec<Arc<dyn Hook>>,
+}
+
+impl Hooks {
+    /// Hooks running the external commands of the configuration
+    pub fn from_config(config: &HooksConfig) -> Self {
+        let mut hooks = Hooks::default();
+
+        if !config.is_empty() {
+            hooks.add(Arc::new(CommandHook::new(config.clone())));
+        }
+
+        hooks
+    }
+
+    pub fn add(&mut self, hook: Arc<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    pub async fn pre_scan(&self, resource_type: Client, region: &Region) {
+        for hook in &self.hooks {
+            hook.pre_scan(resource_type, region).await;
+        }
+    }
+
+    pub async fn post_scan(&self, resource_type: Client, region: &Region, resources: &[Resource]) {
+        for hook in &self.hooks {
+            hook.post_scan(resource_type, region, resources).await;
+        }
+    }
+
+    /// Returns `false` if any of the hooks vetoes the stop or removal of the
+    /// resource, the remaining hooks are not invoked.
+    pub async fn pre_delete(&self, resource: &Resource) -> bool {
+        for hook in &self.hooks {
+            if !hook.pre_delete(resource).await {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub async fn post_delete(&self, resource: &Resource, result: &Result<()>) {
+        for hook in &self.hooks {
+            hook.post_delete(resource, result).await;
+        }
+    }
+
+    pub async fn run_end(&self, resources: &[Resource]) {
+        for hook in &self.hooks {
+            hook.run_end(resources).await;
+        }
+    }
+}
+
+/// Runs the configured external commands. The resource (or the list of
+/// resources) is written as JSON to the stdin of the command and the details
+/// of the event are passed as environment variables:
+///
+/// * `NUKER_HOOK` - name of the hook, for instance `pre_delete`
+/// * `NUKER_RESOURCE_TYPE` and `NUKER_REGION` - for the scan hooks
+/// * `NUKER_RESULT` - `ok` or `error` for the `post_delete` hook
+struct CommandHook {
+    config: HooksConfig,
+}
+
+impl CommandHook {
+    fn new(config: HooksConfig) -> Self {
+        CommandHook { config }
+    }
+
+    /// Runs the command returning whether it exited successfully, commands
+    /// that fail to run or do not complete within the timeout are considered
+    /// failed.
+    async fn run(
+        &self,
+        hook: &str,
+        command: &[String],
+        input: Vec<u8>,
+        mut env: Vec<(&str, String)>,
+    ) -> bool {
+        env.push(("NUKER_HOOK", hook.to_string()));
+
+        match util::run_command(command, &input, env, self.config.timeout).await {
+            Ok(output) => {
+                debug!(
+                    hook = hook,
+                    status = ?output.status,
+                    stdout = String::from_utf8_lossy(&output.stdout).trim(),
+                    "Hook command completed"
+                );
+
+                if !output.status.success() {
+                    warn!(
+                        hook = hook,
+                        status = ?output.status,
+                        stderr = String::from_utf8_lossy(&output.stderr).trim(),
+                        "Hook command failed"
+                    );
+                }
+
+                output.status.success()
+            }
+            Err(err) => {
+                error!(hook = hook, err = ?err, "Failed running hook command");
+                false
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for CommandHook {
+    async fn pre_scan(&self, resource_type: Client, region: &Region) {
+        if let Some(ref command) = self.config.pre_scan {
+            self.run(
+                PRE_SCAN,
+                command,
+                Vec::new(),
+                scan_env(resource_type, region),
+            )
+            .await;
+        }
+    }
+
+    async fn post_scan(&self, resource_type: Client, region: &Region, resources: &[Resource]) {
+        if let Some(ref command) = self.config.post_scan {
+            self.run(
+                POST_SCAN,
+                command,
+                to_json(&resources),
+                scan_env(resource_type, region),
+            )
+            .await;
+        }
+    }
+
+    /// A pre-delete command exiting with a non-zero status vetoes the stop or
+    /// removal of the resource.
+    async fn pre_delete(&self, resource: &Resource) -> bool {
+        match self.config.pre_delete {
+            Some(ref command) => {
+                self.run(PRE_DELETE, command, to_json(resource), Vec::new())
+                    .await
+            }
+            None => true,
+        }
+    }
+
+    async fn post_delete(&self, resource: &Resource, result: &Result<()>) {
+        if let Some(ref command) = self.config.post_delete {
+            let outcome = if result.is_ok() { "ok" } else { "error" };
+            self.run(
+                POST_DELETE,
+                command,
+                to_json(resource),
+                vec![("NUKER_RESULT", outcome.to_string())],
+            )
+            .await;
+        }
+    }
+
+    async fn run_end(&self, resources: &[Resource]) {
+        if let Some(ref command) = self.config.run_end {
+            self.run(RUN_END, command, to_json(&resources), Vec::new())
+                .await;
+        }
+    }
+}
+
+/// Resources that are stopped or removed on cleanup, hooks around the
+/// cleanup are only invoked for these.
+pub(crate) fn is_enforced(resource: &Resource) -> bool {
+    matches!(
+        resource.enforcement_state,
+        EnforcementState::Stop | EnforcementState::Delete | EnforcementState::DeleteDependent
+    )
+}
+
+fn scan_env(resource_type: Client, region: &Region) -> Vec<(&'static str, String)> {
+    vec![
+        ("NUKER_RESOURCE_TYPE", resource_type.name().to_string()),
+        ("NUKER_REGION", region.name().to_string()),
+    ]
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    serde_json::to_vec(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Records whether its pre-delete callback was invoked
+    #[derive(Default)]
+    struct Recorder {
+        invoked: AtomicBool,
+    }
+
+    #[async_trait]
+    impl Hook for Recorder {
+        async fn pre_delete(&self, _resource: &Resource) -> bool {
+            self.invoked.store(true, Ordering::SeqCst);
+            true
+        }
+    }
+
+    fn hooks(script: &str, recorder: Arc<Recorder>) -> Hooks {
+        let mut hooks = Hooks::from_config(&HooksConfig {
+            pre_delete: Some(vec!["sh".to_string(), "-c".to_string(), script.to_string()]),
+            ..Default::default()
+        });
+        hooks.add(recorder);
+        hooks
+    }
+
+    #[tokio::test]
+    async fn veto_using_pre_delete_command() {
+        let recorder = Arc::new(Recorder::default());
+        let hooks = hooks("cat > /dev/null; exit 1", recorder.clone());
+
+        assert!(!hooks.pre_delete(&Resource::default()).await);
+        // Hooks after the vetoing hook are not invoked
+        assert!(!recorder.invoked.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn allow_using_pre_delete_command() {
+        let recorder = Arc::new(Recorder::default());
+        let hooks = hooks("cat > /dev/null", recorder.clone());
+
+        assert!(hooks.pre_delete(&Resource::default()).await);
+        assert!(recorder.invoked.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,135 @@
+//! Configuration interpolation
+//!
+//! String values of the configuration may refer to environment variables and
+//! files, which are substituted when the configuration is parsed:
+//!
+//! - `${NAME}` is replaced by the value of the environment variable `NAME`
+//! - `${NAME:-default}` falls back to `default` when `NAME` is unset or empty
+//! - `${file:/run/secrets/token}` is replaced by the content of the file,
+//!   without the trailing newline
+//!
+//! `$${` is kept as a literal `${`. Keys of the configuration are not
+//! interpolated.
+use std::{env, fs};
+use toml::Value;
+
+const FILE_PREFIX: &str = "file:";
+const DEFAULT_SEPARATOR: &str = ":-";
+
+/// Interpolates every string value of the document, returning the path of
+/// the values that could not be resolved along with the reason. Values that
+/// cannot be resolved are left untouched.
+pub(crate) fn interpolate_document(document: &mut Value) -> Vec<(Vec<String>, String)> {
+    let mut problems = Vec::new();
+    interpolate_value(document, &mut Vec::new(), &mut problems);
+
+    problems
+}
+
+fn interpolate_value(
+    value: &mut Value,
+    path: &mut Vec<String>,
+    problems: &mut Vec<(Vec<String>, String)>,
+) {
+    match value {
+        Value::String(s) => match interpolate(s) {
+            Ok(interpolated) => *s = interpolated,
+            Err(err) => problems.push((path.clone(), err)),
+        },
+        Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                path.push(i.to_string());
+                interpolate_value(value, path, problems);
+                path.pop();
+            }
+        }
+        Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                path.push(key.clone());
+                interpolate_value(value, path, problems);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Substitutes the variables of the string
+pub(crate) fn interpolate(s: &str) -> Result<String, String> {
+    let mut interpolated = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('$') {
+        interpolated.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("$${") {
+            interpolated.push_str("${");
+            rest = &rest[3..];
+        } else if rest.starts_with("${") {
+            let end = rest
+                .find('}')
+                .ok_or_else(|| format!("unterminated variable in '{}'", s))?;
+            interpolated.push_str(&resolve(&rest[2..end])?);
+            rest = &rest[end + 1..];
+        } else {
+            interpolated.push('$');
+            rest = &rest[1..];
+        }
+    }
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
+}
+
+fn resolve(variable: &str) -> Result<String, String> {
+    if let Some(path) = variable.strip_prefix(FILE_PREFIX) {
+        return fs::read_to_string(path)
+            .map(|content| content.trim_end_matches(&['\r', '\n'][..]).to_string())
+            .map_err(|e| format!("could not read {}: {}", path, e));
+    }
+
+    let (name, default) = match variable.find(DEFAULT_SEPARATOR) {
+        Some(i) => (
+            &variable[..i],
+            Some(&variable[i + DEFAULT_SEPARATOR.len()..]),
+        ),
+        None => (variable, None),
+    };
+
+    match (env::var(name), default) {
+        (Ok(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Ok(value), _) => Ok(value),
+        (Err(_), Some(default)) => Ok(default.to_string()),
+        (Err(_), None) => Err(format!("environment variable {} is not set", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_variables() {
+        env::set_var("NUKER_TEST_ACCOUNT", "123456789012");
+        env::set_var("NUKER_TEST_EMPTY", "");
+        let secret = env::temp_dir().join("nuker-interpolate-secret");
+        fs::write(&secret, "s3cr3t\n").unwrap();
+
+        assert_eq!(
+            interpolate("arn:aws:iam::${NUKER_TEST_ACCOUNT}:root").unwrap(),
+            "arn:aws:iam::123456789012:root"
+        );
+        assert_eq!(
+            interpolate("${NUKER_TEST_UNSET:-us-east-1}/${NUKER_TEST_EMPTY:-x}").unwrap(),
+            "us-east-1/x"
+        );
+        assert_eq!(
+            interpolate(&format!("token=${{file:{}}}", secret.display())).unwrap(),
+            "token=s3cr3t"
+        );
+        assert_eq!(interpolate("^$$${literal}$").unwrap(), "^$${literal}$");
+        assert!(interpolate("${NUKER_TEST_UNSET}").is_err());
+        assert!(interpolate("${NUKER_TEST_ACCOUNT").is_err());
+    }
+}
@@ -1,24 +1,43 @@
+//! Cleans up AWS resources based on configurable rules.
+//!
+//! Besides the `nuker` executable, the crate can be embedded using
+//! `NukerBuilder`. `Nuker::scan` returns the resources evaluated against the
+//! rules without stopping or removing anything, and `Nuker::cleanup` cleans up
+//! a selected subset of them. Resource types that are not built in can be
+//! added by implementing `client::NukerClient` and registering a
+//! `registry::ResourceType`.
 // #![feature(const_fn)]
 
-mod aws;
-mod config;
+pub mod aws;
+pub mod config;
 mod error;
 mod graph;
+pub mod hooks;
+mod interpolate;
 #[macro_use]
 mod macros;
-mod client;
+pub mod client;
 mod event;
+pub mod explain;
 mod nuke;
-mod resource;
+pub mod policy;
+pub mod registry;
+pub mod resource;
 mod util;
+pub mod validate;
 
-pub use aws::CwClient;
+pub use aws::{Credentials, CwClient};
+pub use client::{Client, NukerClient};
 pub use config::parse_args;
 pub use config::parse_config_file;
+pub use config::Config;
 pub use error::NError as Error;
 pub use event::Event;
+pub use hooks::{Hook, Hooks};
 pub use macros::*;
-pub use nuke::Nuker;
+pub use nuke::{Nuker, NukerBuilder};
+pub use registry::{register, CwMetrics, ResourceType};
+pub use resource::Resource;
 use std::error::Error as StdError;
 use std::result::Result as StdResult;
 use tokio::sync::mpsc::Sender as NSender;
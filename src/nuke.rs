@@ -1,148 +1,442 @@
 use crate::client::Client;
+use crate::util::glob_match;
 use crate::{
-    aws::AwsNuker,
+    aws::{discover_regions, region_from_name, AwsNuker, Credentials, GLOBAL_SCOPE},
     config::{Args, Config},
-    Result,
+    explain::Explanation,
+    hooks::{Hook, Hooks},
+    resource::Resource,
+    Error, Result,
 };
-use colored::*;
 use rusoto_core::Region;
-use std::{io, process::exit, str::FromStr};
-use tracing::{debug, error, trace};
+use std::sync::Arc;
+use tracing::{debug, error, trace, warn};
 use tracing_futures::Instrument;
 
-const PROMPT_YES: &str = "yes";
-
-static REGIONS: &'static [Region] = &[
-    Region::ApEast1,
-    Region::ApNortheast1,
-    Region::ApNortheast2,
-    Region::ApSouth1,
-    Region::ApSoutheast1,
-    Region::ApSoutheast2,
-    Region::CaCentral1,
-    Region::EuCentral1,
-    Region::EuNorth1,
-    Region::EuWest1,
-    Region::EuWest2,
-    Region::EuWest3,
-    Region::MeSouth1,
-    Region::SaEast1,
-    Region::UsEast1,
-    Region::UsEast2,
-    Region::UsWest1,
-    Region::UsWest2,
+/// Endpoint used for scanning the global resources
+const GLOBAL_REGION: Region = Region::UsEast1;
+
+/// Regions scanned when the enabled regions cannot be discovered
+static REGIONS: &[&str] = &[
+    "af-south-1",
+    "ap-east-1",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ap-south-1",
+    "ap-south-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-southeast-3",
+    "ap-southeast-4",
+    "ca-central-1",
+    "ca-west-1",
+    "eu-central-1",
+    "eu-central-2",
+    "eu-north-1",
+    "eu-south-1",
+    "eu-south-2",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "il-central-1",
+    "me-central-1",
+    "me-south-1",
+    "sa-east-1",
+    "us-east-1",
+    "us-east-2",
+    "us-west-1",
+    "us-west-2",
 ];
 
+/// Builds a `Nuker`, the regions to scan are resolved from the configuration
+/// when none are provided.
+///
+/// ```no_run
+/// # async fn run() -> nuker::Result<()> {
+/// let config = nuker::config::parse_config_file("config.toml")?;
+/// let mut nuker = nuker::NukerBuilder::new(config)
+///     .profile("default")
+///     .region("us-east-1")
+///     .dry_run(true)
+///     .build();
+///
+/// let resources = nuker.scan().await?;
+/// nuker.cleanup(&resources).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct NukerBuilder {
+    config: Config,
+    credentials: Credentials,
+    regions: Vec<String>,
+    exclude_regions: Vec<String>,
+    targets: Option<Vec<Client>>,
+    exclude: Option<Vec<Client>>,
+    hooks: Vec<Arc<dyn Hook>>,
+    explain: bool,
+    dry_run: bool,
+}
+
+impl NukerBuilder {
+    pub fn new(config: Config) -> Self {
+        NukerBuilder {
+            config,
+            credentials: Credentials::default(),
+            regions: Vec::new(),
+            exclude_regions: Vec::new(),
+            targets: None,
+            exclude: None,
+            hooks: Vec::new(),
+            explain: false,
+            dry_run: true,
+        }
+    }
+
+    /// Builder initialized from the command line arguments
+    pub fn from_args(config: Config, args: &Args) -> Self {
+        let mut builder = NukerBuilder::new(config)
+            .credentials(args.profile.clone().into())
+            .explain(args.explain)
+            .dry_run(args.dry_run);
+
+        builder.regions = args.regions.clone();
+        builder.exclude_regions = args.exclude_regions.clone();
+        builder.targets = args.targets.clone();
+        builder.exclude = args.exclude.clone();
+
+        match args.endpoint_url {
+            Some(ref endpoint_url) => builder.endpoint_url(endpoint_url),
+            None => builder,
+        }
+    }
+
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Uses the credentials of the named profile
+    pub fn profile(self, profile: &str) -> Self {
+        self.credentials(Credentials::Profile(profile.to_string()))
+    }
+
+    /// Includes a region by name or by a glob pattern (e.g. `eu-*`)
+    pub fn region(mut self, region: &str) -> Self {
+        self.regions.push(region.to_string());
+        self
+    }
+
+    /// Excludes the regions matching the glob pattern
+    pub fn exclude_region(mut self, region: &str) -> Self {
+        self.exclude_regions.push(region.to_string());
+        self
+    }
+
+    /// Only scans the targeted resource types
+    pub fn target(mut self, client: Client) -> Self {
+        self.targets.get_or_insert_with(Vec::new).push(client);
+        self
+    }
+
+    pub fn exclude(mut self, client: Client) -> Self {
+        self.exclude.get_or_insert_with(Vec::new).push(client);
+        self
+    }
+
+    /// Overrides the endpoint of all the services
+    pub fn endpoint_url(mut self, endpoint_url: &str) -> Self {
+        self.config.endpoints.url = Some(endpoint_url.to_string());
+        self
+    }
+
+    /// Adds a hook, invoked after the commands configured in `[hooks]`
+    pub fn hook(mut self, hook: Arc<dyn Hook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Prints the evaluation of every rule for the resources on `run`
+    pub fn explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Resources are only reported and not cleaned up when enabled, enabled
+    /// by default
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> Nuker {
+        let mut hooks = Hooks::from_config(&self.config.hooks);
+        for hook in self.hooks {
+            hooks.add(hook);
+        }
+
+        Nuker {
+            hooks,
+            config: self.config,
+            credentials: self.credentials,
+            regions: self.regions,
+            exclude_regions: self.exclude_regions,
+            targets: self.targets,
+            exclude: self.exclude,
+            explain: self.explain,
+            dry_run: self.dry_run,
+            nukers: Vec::new(),
+        }
+    }
+}
+
+/// Scans and cleans up the resources across the regions of an account
 pub struct Nuker {
-    args: Args,
     config: Config,
+    credentials: Credentials,
+    regions: Vec<String>,
+    exclude_regions: Vec<String>,
+    targets: Option<Vec<Client>>,
+    exclude: Option<Vec<Client>>,
+    hooks: Hooks,
+    explain: bool,
+    dry_run: bool,
+    /// Nukers of the scanned scopes, keyed by the region or `global`
+    nukers: Vec<(String, AwsNuker)>,
 }
 
 impl Nuker {
     pub fn new(config: Config, args: Args) -> Self {
         trace!("Args: {:?}", args);
-        Nuker { args, config }
+        NukerBuilder::from_args(config, &args).build()
     }
 
+    pub fn builder(config: Config) -> NukerBuilder {
+        NukerBuilder::new(config)
+    }
+
+    /// Scans, prints and cleans up the resources
     pub async fn run(&mut self) -> Result<()> {
-        let mut clients: Vec<AwsNuker> = Vec::new();
-        let mut handles = Vec::new();
+        let resources = self.scan().await?;
 
-        if self.args.dry_run {
-            println!("{}", "DRY RUN ENABLED".blue().bold());
+        if self.explain {
+            for explanation in self.explain(&resources).await? {
+                println!("{}\n", explanation);
+            }
         } else {
-            println!("{}", "DRY RUN DISABLED".red().bold());
+            for resource in &resources {
+                println!("{}", resource);
+            }
         }
 
-        if !self.args.dry_run && !self.args.force {
-            let input: String = self.get_input("Are you sure you want to continue (yes/no)?");
-            if &input.to_lowercase() != PROMPT_YES {
-                exit(1);
-            }
+        self.cleanup(&resources).await
+    }
+
+    /// Scans for the resources across all the regions and evaluates them
+    /// against the rules, nothing is stopped or removed. Resources of a scope
+    /// are ordered by their dependencies.
+    pub async fn scan(&mut self) -> Result<Vec<Resource>> {
+        self.nukers = self.create_nukers().await?;
+
+        let scans = self.nukers.iter_mut().map(|(scope, nuker)| {
+            let span = tracing::trace_span!("nuker", region = scope.as_str());
+            nuker.scan().instrument(span)
+        });
+        let mut resources = Vec::new();
+
+        for result in futures::future::join_all(scans).await {
+            resources.extend(result?);
         }
 
-        let excluded_services = self.excluded_services();
+        Ok(resources)
+    }
 
-        if self.args.regions.is_empty() {
-            debug!("Scanning for resources across all regions - {:?}", REGIONS);
-
-            for region in REGIONS.iter() {
-                clients.push(
-                    AwsNuker::new(
-                        self.args.profile.clone(),
-                        region.to_owned(),
-                        self.config.clone(),
-                        excluded_services.clone(),
-                        self.args.dry_run,
-                    )
-                    .await?,
-                );
-            }
-        } else {
-            debug!("Scanning for resources in regions: {:?}", self.args.regions);
-
-            for region in &self.args.regions {
-                clients.push(
-                    AwsNuker::new(
-                        self.args.profile.clone(),
-                        Region::from_str(region)?,
-                        self.config.clone(),
-                        excluded_services.clone(),
-                        self.args.dry_run,
-                    )
-                    .await?,
-                );
-            }
+    /// Cleans up the provided resources, usually a subset of the resources
+    /// returned by `scan`. Resources are stopped or removed based on their
+    /// enforcement state, in the order provided.
+    pub async fn cleanup(&mut self, resources: &[Resource]) -> Result<()> {
+        if self.nukers.is_empty() {
+            self.nukers = self.create_nukers().await?;
         }
 
-        for mut client in clients {
-            handles.push(tokio::spawn(async move {
-                let region = client.client_details.region.name().to_string();
-                client
-                    .locate_resources()
-                    .instrument(tracing::trace_span!("nuker", region = region.as_str()))
-                    .await;
+        let cleanups = self.nukers.iter_mut().map(|(scope, nuker)| {
+            let scoped: Vec<Resource> = resources
+                .iter()
+                .filter(|r| resource_scope(r) == *scope)
+                .cloned()
+                .collect();
+            let span = tracing::trace_span!("nuker", region = scope.as_str());
 
-                if let Err(err) = client.print_resources().await {
-                    error!(err = ?err, "Failed printing resources");
-                }
+            async move { nuker.cleanup(&scoped).await }.instrument(span)
+        });
 
-                if let Err(err) = client
-                    .cleanup_resources()
-                    .instrument(tracing::trace_span!("nuker", region = region.as_str()))
-                    .await
-                {
-                    error!(err = ?err, "Failed cleaning up resources");
-                }
-            }));
-        }
+        let results = futures::future::join_all(cleanups).await;
+        trace!("All cleanups completed");
+        self.hooks.run_end(resources).await;
 
-        trace!("Waiting for all futures to return");
-        futures::future::join_all(handles).await;
-        trace!("All futures completed");
+        for result in results {
+            if let Err(err) = result {
+                error!(err = ?err, "Failed cleaning up resources");
+                return Err(err);
+            }
+        }
 
         Ok(())
     }
 
-    fn get_input(&self, prompt: &str) -> String {
-        println!("{}", prompt);
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {}
-            Err(_) => {}
+    /// Explains the enforcement state of the provided resources, usually
+    /// returned by `scan`, by evaluating every rule against them
+    pub async fn explain(&mut self, resources: &[Resource]) -> Result<Vec<Explanation>> {
+        if self.nukers.is_empty() {
+            self.nukers = self.create_nukers().await?;
         }
-        input.trim().to_string()
+
+        let explanations = self.nukers.iter_mut().map(|(scope, nuker)| {
+            let scoped: Vec<Resource> = resources
+                .iter()
+                .filter(|r| resource_scope(r) == *scope)
+                .cloned()
+                .collect();
+            let span = tracing::trace_span!("nuker", region = scope.as_str());
+
+            async move { nuker.explain(&scoped).await }.instrument(span)
+        });
+
+        Ok(futures::future::join_all(explanations)
+            .await
+            .into_iter()
+            .flatten()
+            .collect())
     }
 
-    fn excluded_services(&mut self) -> Vec<Client> {
-        if self.args.targets.is_some() {
-            Client::iter()
-                .filter(|s| !self.args.targets.as_ref().unwrap().contains(&s))
+    /// Scans for the resource identified by its id or ARN and explains its
+    /// enforcement state, nothing is stopped or removed
+    pub async fn explain_resource(&mut self, id: &str) -> Result<Vec<Explanation>> {
+        let resources: Vec<Resource> = self
+            .scan()
+            .await?
+            .into_iter()
+            .filter(|r| r.id == id || r.arn.as_deref() == Some(id))
+            .collect();
+
+        self.explain(&resources).await
+    }
+
+    async fn create_nukers(&self) -> Result<Vec<(String, AwsNuker)>> {
+        let mut nukers: Vec<(String, AwsNuker)> = Vec::new();
+        let excluded_services = self.excluded_services();
+        let regions = self.resolve_regions().await?;
+        debug!("Scanning for resources in regions: {:?}", regions);
+
+        // Global resources are scanned once for the account
+        if Client::iter().any(|c| c.is_global() && !excluded_services.contains(&c)) {
+            nukers.push((
+                GLOBAL_SCOPE.to_string(),
+                AwsNuker::new_global(
+                    self.credentials.clone(),
+                    GLOBAL_REGION,
+                    regions.clone(),
+                    self.config.clone(),
+                    excluded_services.clone(),
+                    self.dry_run,
+                )
+                .await?
+                .with_hooks(self.hooks.clone()),
+            ));
+        }
+
+        for region in regions {
+            nukers.push((
+                region.name().to_string(),
+                AwsNuker::new(
+                    self.credentials.clone(),
+                    region,
+                    self.config.clone(),
+                    excluded_services.clone(),
+                    self.dry_run,
+                )
+                .await?
+                .with_hooks(self.hooks.clone()),
+            ));
+        }
+
+        Ok(nukers)
+    }
+
+    /// Resolves the regions to scan. Regions included by name are used as is,
+    /// otherwise the regions enabled for the account are discovered and
+    /// matched against the included patterns. Regions matching any of the
+    /// excluded patterns are skipped.
+    async fn resolve_regions(&self) -> Result<Vec<Region>> {
+        let include = if self.regions.is_empty() {
+            &self.config.regions.include
+        } else {
+            &self.regions
+        };
+        let exclude: Vec<&String> = self
+            .config
+            .regions
+            .exclude
+            .iter()
+            .chain(self.exclude_regions.iter())
+            .collect();
+
+        let candidates: Vec<Region> = if !include.is_empty() && !include.iter().any(|r| is_glob(r))
+        {
+            include.iter().map(|r| region_from_name(r)).collect()
+        } else {
+            let discovered = match discover_regions(&self.credentials, &self.config.endpoints).await
+            {
+                Ok(regions) if !regions.is_empty() => regions,
+                Ok(_) => REGIONS.iter().map(|r| region_from_name(r)).collect(),
+                Err(err) => {
+                    warn!(err = ?err, "Failed discovering regions, using the static list");
+                    REGIONS.iter().map(|r| region_from_name(r)).collect()
+                }
+            };
+
+            discovered
+                .into_iter()
+                .filter(|r| include.is_empty() || include.iter().any(|p| glob_match(p, r.name())))
                 .collect()
-        } else if self.args.exclude.is_some() {
-            self.args.exclude.as_ref().unwrap().clone()
+        };
+
+        let regions: Vec<Region> = candidates
+            .into_iter()
+            .filter(|r| !exclude.iter().any(|p| glob_match(p, r.name())))
+            .collect();
+
+        if regions.is_empty() {
+            return Err(Error::Validation(
+                "No regions left to scan after applying the region filters".to_string(),
+            ));
+        }
+
+        Ok(regions)
+    }
+
+    fn excluded_services(&self) -> Vec<Client> {
+        if let Some(ref targets) = self.targets {
+            Client::iter().filter(|s| !targets.contains(s)).collect()
+        } else if let Some(ref exclude) = self.exclude {
+            exclude.clone()
         } else {
             vec![]
         }
     }
 }
+
+/// Global resources are cleaned up by the global nuker, the rest by the nuker
+/// of their region
+fn resource_scope(resource: &Resource) -> &str {
+    if resource.type_.is_global() {
+        GLOBAL_SCOPE
+    } else {
+        resource.region.name()
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains('*') || pattern.contains('?')
+}
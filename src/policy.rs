@@ -0,0 +1,321 @@
+//! External policy engine
+//!
+//! Enforcement decisions can be delegated to an external policy engine (for
+//! instance Open Policy Agent), either an HTTP decision endpoint or a local
+//! evaluator command. The engine is given the resource as a JSON document and
+//! responds with a decision:
+//!
+//! ```json
+//! {"decision": "delete", "reason": "tag-not-compliant"}
+//! ```
+//!
+//! The decision is one of `skip`, `stop`, `delete` or `tag`, tagging applies
+//! the missing required tags of the resource; responses wrapped in a
+//! `result` object (as returned by OPA) are accepted as well. When the engine
+//! does not return a decision, the rules of the configuration are applied.
+use crate::{
+    config::PolicyConfig,
+    resource::{EnforcementReason, EnforcementState, Resource},
+    util, Error, Result,
+};
+use hyper::{client::HttpConnector, header, Body, Method, Request};
+use hyper_tls::HttpsConnector;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+const RESULT_KEY: &str = "result";
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Skip,
+    Stop,
+    Delete,
+    Tag,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct Response {
+    decision: Option<Action>,
+    reason: Option<String>,
+}
+
+/// Decision of the policy engine for a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub state: EnforcementState,
+    pub reason: Option<EnforcementReason>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyEngine {
+    config: PolicyConfig,
+    client: hyper::Client<HttpsConnector<HttpConnector>>,
+}
+
+impl PolicyEngine {
+    /// Creates the policy engine, if either a decision endpoint or an
+    /// evaluator command is configured
+    pub fn new(config: &PolicyConfig) -> Option<Self> {
+        if config.url.is_none() && config.command.is_none() {
+            return None;
+        }
+
+        Some(PolicyEngine {
+            config: config.clone(),
+            client: hyper::Client::builder().build(HttpsConnector::new()),
+        })
+    }
+
+    /// Asks the policy engine for a decision on the resource. A failure to
+    /// reach the engine results in the resource being skipped, unless the
+    /// engine is configured to fall back to the rules of the configuration.
+    pub async fn decide(&self, resource: &Resource) -> Option<Decision> {
+        match self.evaluate(resource).await {
+            Ok(response) => {
+                debug!(
+                    resource = resource.id.as_str(),
+                    decision = ?response.decision,
+                    reason = ?response.reason,
+                    "Policy decision"
+                );
+                response
+                    .decision
+                    .map(|action| to_decision(action, response.reason))
+            }
+            Err(err) if self.config.fallback => {
+                warn!(resource = resource.id.as_str(), err = ?err, "Policy evaluation failed, applying the configured rules");
+                None
+            }
+            Err(err) => {
+                warn!(resource = resource.id.as_str(), err = ?err, "Policy evaluation failed, skipping resource");
+                Some(Decision {
+                    state: EnforcementState::Skip,
+                    reason: None,
+                })
+            }
+        }
+    }
+
+    async fn evaluate(&self, resource: &Resource) -> Result<Response> {
+        let body = match (&self.config.url, &self.config.command) {
+            (Some(url), _) => self.query_endpoint(url, resource).await?,
+            (None, Some(command)) => self.run_evaluator(command, resource).await?,
+            (None, None) => return Ok(Response::default()),
+        };
+
+        parse_response(&body)
+    }
+
+    /// The resource is sent as the `input` document of the request
+    async fn query_endpoint(&self, url: &str, resource: &Resource) -> Result<Vec<u8>> {
+        let payload = json!({ "input": resource });
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(payload.to_string()))
+            .map_err(|e| Error::Policy(e.to_string()))?;
+
+        let res = tokio::time::timeout(self.config.timeout, self.client.request(req))
+            .await
+            .map_err(|_| Error::Policy(format!("{} timed out", url)))?
+            .map_err(|e| Error::Policy(e.to_string()))?;
+
+        if !res.status().is_success() {
+            return Err(Error::Policy(format!("{} returned {}", url, res.status())));
+        }
+
+        hyper::body::to_bytes(res.into_body())
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| Error::Policy(e.to_string()))
+    }
+
+    /// The resource is written to the stdin of the evaluator, which is
+    /// expected to write the response to stdout
+    async fn run_evaluator(&self, command: &[String], resource: &Resource) -> Result<Vec<u8>> {
+        let input = serde_json::to_vec(resource).map_err(|e| Error::Policy(e.to_string()))?;
+        let output = util::run_command(command, &input, Vec::new(), self.config.timeout)
+            .await
+            .map_err(|e| Error::Policy(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(Error::Policy(format!(
+                "evaluator exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+fn parse_response(body: &[u8]) -> Result<Response> {
+    let mut value: Value =
+        serde_json::from_slice(body).map_err(|e| Error::Policy(e.to_string()))?;
+
+    if let Some(result) = value.get_mut(RESULT_KEY) {
+        value = result.take();
+    }
+
+    if value.is_null() {
+        return Ok(Response::default());
+    }
+
+    serde_json::from_value(value).map_err(|e| Error::Policy(e.to_string()))
+}
+
+/// Reasons matching the name of an enforcement reason map to it, any other
+/// reason is reported as a policy decision.
+fn to_decision(action: Action, reason: Option<String>) -> Decision {
+    let state = match action {
+        Action::Skip => EnforcementState::Skip,
+        Action::Stop => EnforcementState::Stop,
+        Action::Delete => EnforcementState::Delete,
+        Action::Tag => EnforcementState::Tag,
+    };
+    let reason = match action {
+        Action::Skip => None,
+        _ => Some(
+            reason
+                .as_deref()
+                .and_then(EnforcementReason::from_name)
+                .unwrap_or(EnforcementReason::Policy),
+        ),
+    };
+
+    Decision { state, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    /// Serves a single request with the provided body, returning the url
+    fn serve(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4096];
+            let _ = stream.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        format!("http://{}/v1/data/nuker", addr)
+    }
+
+    fn engine(url: Option<String>, command: Option<Vec<String>>, fallback: bool) -> PolicyEngine {
+        PolicyEngine::new(&PolicyConfig {
+            url,
+            command,
+            timeout: Duration::from_secs(5),
+            fallback,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_opa_response() {
+        let response =
+            parse_response(br#"{"result": {"decision": "stop", "reason": "idle"}}"#).unwrap();
+
+        assert_eq!(response.decision, Some(Action::Stop));
+        assert_eq!(
+            to_decision(Action::Stop, response.reason),
+            Decision {
+                state: EnforcementState::Stop,
+                reason: Some(EnforcementReason::Idle),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_undefined_response() {
+        assert_eq!(parse_response(b"{}").unwrap().decision, None);
+        assert_eq!(
+            parse_response(br#"{"result": null}"#).unwrap().decision,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn decide_using_endpoint() {
+        let url = serve(
+            "200 OK",
+            r#"{"decision": "delete", "reason": "owner left"}"#,
+        );
+
+        assert_eq!(
+            engine(Some(url), None, false)
+                .decide(&Resource::default())
+                .await,
+            Some(Decision {
+                state: EnforcementState::Delete,
+                reason: Some(EnforcementReason::Policy),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_on_endpoint_failure() {
+        let url = serve("500 Internal Server Error", "{}");
+
+        assert_eq!(
+            engine(Some(url), None, false)
+                .decide(&Resource::default())
+                .await,
+            Some(Decision {
+                state: EnforcementState::Skip,
+                reason: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn fallback_on_endpoint_failure() {
+        let url = serve("500 Internal Server Error", "{}");
+
+        assert_eq!(
+            engine(Some(url), None, true)
+                .decide(&Resource::default())
+                .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn decide_using_evaluator() {
+        let command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            r#"cat > /dev/null; echo '{"decision": "skip"}'"#.to_string(),
+        ];
+
+        assert_eq!(
+            engine(None, Some(command), false)
+                .decide(&Resource::default())
+                .await,
+            Some(Decision {
+                state: EnforcementState::Skip,
+                reason: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Registry of the resource types known to the Nuker
+//!
+//! Every resource type describes how its client is built, the AWS service it
+//! belongs to and how its idle rules are evaluated. The built-in resource
+//! types are registered on first use, additional types can be added using
+//! `register`.
+use crate::{
+    aws::{self, ClientDetails},
+    client::{Client, NukerClient},
+    config::ResourceConfig,
+};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::RwLock};
+
+/// Builds the client of a resource type
+pub type ClientBuilder = fn(&ClientDetails, &ResourceConfig, bool) -> Box<dyn NukerClient>;
+
+/// CloudWatch metrics published for a resource type, used for evaluating the
+/// idle rules of the resources. These can be replaced by the `metrics` of the
+/// resource type configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct CwMetrics {
+    pub namespace: &'static str,
+    /// Dimensions identifying a resource, the values are templates (for
+    /// instance `{id}`) rendered for each resource
+    pub dimensions: &'static [(&'static str, &'static str)],
+    /// Metrics are only published when the resource is in use, missing
+    /// datapoints are treated as idle
+    pub sparse: bool,
+}
+
+#[derive(Clone)]
+pub struct ResourceType {
+    pub client: Client,
+    /// Alternate names accepted in the configuration and on the command line
+    pub aliases: &'static [&'static str],
+    /// Endpoint prefix of the AWS service
+    pub service: &'static str,
+    /// Global resources are scanned once per account
+    pub global: bool,
+    pub builder: ClientBuilder,
+    /// Configuration used when the resource type is not configured
+    pub default_config: fn() -> ResourceConfig,
+    /// Resources can be tagged, required for the `Tagged` target state
+    pub taggable: bool,
+    /// Name of the CloudTrail event creating a resource, used to look up who
+    /// created the resource
+    pub create_event: Option<&'static str>,
+    pub metrics: Option<CwMetrics>,
+}
+
+static REGISTRY: Lazy<RwLock<HashMap<Client, ResourceType>>> = Lazy::new(|| {
+    RwLock::new(
+        aws::resource_types()
+            .into_iter()
+            .map(|rt| (rt.client, rt))
+            .collect(),
+    )
+});
+
+/// Registers a resource type, replacing any resource type registered with
+/// the same name.
+pub fn register(resource_type: ResourceType) {
+    REGISTRY
+        .write()
+        .unwrap()
+        .insert(resource_type.client, resource_type);
+}
+
+pub fn get(client: &Client) -> Option<ResourceType> {
+    REGISTRY.read().unwrap().get(client).cloned()
+}
+
+/// Looks up a resource type by its name or one of its aliases
+pub fn lookup(name: &str) -> Option<Client> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .values()
+        .find(|rt| rt.client.name() == name || rt.aliases.contains(&name))
+        .map(|rt| rt.client)
+}
+
+/// All the registered resource types ordered by name
+pub fn clients() -> Vec<Client> {
+    let mut clients: Vec<Client> = REGISTRY.read().unwrap().keys().copied().collect();
+    clients.sort_by_key(|c| c.name().to_string());
+    clients
+}
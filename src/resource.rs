@@ -1,11 +1,65 @@
 use crate::{client::*, config::TargetState, StdResult};
 use colored::*;
 use rusoto_core::Region;
+use serde::{Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::warn;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Span of the CloudTrail event history
+pub(crate) const EVENT_HISTORY: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// Creation of a resource looked up in the CloudTrail event history
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Creation {
+    /// Created within the minimum age
+    Recent,
+    /// Created before the minimum age
+    Older,
+    /// No creation is recorded since the minimum age, the resource is older
+    /// than the minimum age or than the event history
+    NotRecorded,
+    /// The event history could not be looked up
+    Unknown,
+}
+
+impl Creation {
+    /// Creation of a resource created at `time`, relative to the minimum age
+    pub fn from_time(time: &str, min_age: Option<&Duration>) -> Option<Creation> {
+        if crate::util::is_ts_older_than(time, min_age?) {
+            Some(Creation::Older)
+        } else {
+            Some(Creation::Recent)
+        }
+    }
+
+    /// Resources of an unknown creation are considered too young, as are the
+    /// resources without a recorded creation when the minimum age exceeds the
+    /// span of the event history
+    pub fn is_younger_than(&self, min_age: &Duration) -> bool {
+        match self {
+            Creation::Recent | Creation::Unknown => true,
+            Creation::Older => false,
+            Creation::NotRecorded => *min_age > EVENT_HISTORY,
+        }
+    }
+}
+
+impl fmt::Display for Creation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Creation::Recent => write!(f, "within the min age"),
+            Creation::Older => write!(f, "before the min age"),
+            Creation::NotRecorded => write!(f, "not recorded"),
+            Creation::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ResourceState {
     Available,
     Deleted,
@@ -46,10 +100,12 @@ impl FromStr for ResourceState {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EnforcementState {
     Stop,
     Delete,
+    Tag,
     DeleteDependent,
     Skip,
     SkipConfig,
@@ -62,6 +118,7 @@ impl EnforcementState {
         match *self {
             EnforcementState::Stop => "would be stopped".blue().bold(),
             EnforcementState::Delete => "would be removed".blue().bold(),
+            EnforcementState::Tag => "would be tagged".blue().bold(),
             EnforcementState::DeleteDependent => "would be removed (dependent)".blue().bold(),
             EnforcementState::Skip => "skipped because of rules".yellow().bold(),
             EnforcementState::SkipConfig => "skipped because of config".yellow().bold(),
@@ -72,16 +129,27 @@ impl EnforcementState {
         }
     }
 
+    /// State enforced on a resource violating a rule, tagging only remediates
+    /// the required tags so the other rules are not enforced when tagging
     pub fn from_target_state(target_state: &TargetState) -> Self {
-        if *target_state == TargetState::Deleted {
-            EnforcementState::Delete
-        } else {
-            EnforcementState::Stop
+        match *target_state {
+            TargetState::Deleted => EnforcementState::Delete,
+            TargetState::Stopped => EnforcementState::Stop,
+            TargetState::Tagged => EnforcementState::Skip,
+        }
+    }
+
+    /// State enforced on a resource violating the required tags
+    pub fn from_tag_rule(target_state: &TargetState) -> Self {
+        match *target_state {
+            TargetState::Tagged => EnforcementState::Tag,
+            _ => EnforcementState::from_target_state(target_state),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EnforcementReason {
     Idle,
     Runtime,
@@ -90,6 +158,7 @@ pub enum EnforcementReason {
     NameRule,
     AdditionalRules,
     Dependent,
+    Policy,
 }
 
 impl EnforcementReason {
@@ -102,20 +171,40 @@ impl EnforcementReason {
             EnforcementReason::NameRule => "name-not-compliant",
             EnforcementReason::AdditionalRules => "additional-rules",
             EnforcementReason::Dependent => "dependent",
+            EnforcementReason::Policy => "policy",
         }
     }
+
+    /// Parses a reason by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            EnforcementReason::Idle,
+            EnforcementReason::Runtime,
+            EnforcementReason::TagRule,
+            EnforcementReason::AllowedTypeRule,
+            EnforcementReason::NameRule,
+            EnforcementReason::AdditionalRules,
+            EnforcementReason::Dependent,
+            EnforcementReason::Policy,
+        ]
+        .iter()
+        .copied()
+        .find(|r| r.name() == name)
+    }
 }
 
 /// Logical abstraction to represent an AWS resource
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Resource {
     /// ID of the resource
     pub id: String,
     /// Amazon Resource Name of the resource
     pub arn: Option<String>,
     /// Type of the resource that is being generated - client mapping
+    #[serde(rename = "type")]
     pub type_: Client,
     /// AWS Region in which the resource exists
+    #[serde(serialize_with = "serialize_region")]
     pub region: Region,
     /// Tags that are associated with a Resource
     pub tags: Option<Vec<NTag>>,
@@ -124,6 +213,9 @@ pub struct Resource {
     pub state: Option<ResourceState>,
     /// Specifies the time at which the Resource is created
     pub start_time: Option<String>,
+    /// Creation of the resource for the resource types without a start time,
+    /// only used for the minimum age
+    pub created: Option<Creation>,
     /// Specifies the state to enforce, whether to skip it, stop it, or delete
     /// it.
     pub enforcement_state: EnforcementState,
@@ -153,6 +245,7 @@ impl Default for Resource {
             tags: None,
             state: None,
             start_time: None,
+            created: None,
             enforcement_state: EnforcementState::Skip,
             enforcement_reason: None,
             resource_type: None,
@@ -162,6 +255,10 @@ impl Default for Resource {
     }
 }
 
+fn serialize_region<S: Serializer>(region: &Region, serializer: S) -> StdResult<S::Ok, S::Error> {
+    serializer.serialize_str(region.name())
+}
+
 impl fmt::Display for Resource {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -194,7 +291,7 @@ impl fmt::Display for Resource {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NTag {
     pub key: Option<String>,
     pub value: Option<String>,
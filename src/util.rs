@@ -1,7 +1,12 @@
 use crate::{config::RequiredTag, resource::NTag};
 use chrono::prelude::*;
-use std::collections::HashMap;
+use regex::Regex;
 use std::time::Duration;
+use std::{
+    io,
+    process::{Output, Stdio},
+};
+use tokio::{io::AsyncWriteExt, process::Command};
 use tracing::debug;
 
 enum DtFormat<'a> {
@@ -12,34 +17,22 @@ enum DtFormat<'a> {
     FromNdtStr(&'a str),
 }
 
-/// Compares resource tags against required tags
-pub fn compare_tags(tags: Option<Vec<NTag>>, required_tags: &Vec<RequiredTag>) -> bool {
-    let mut tags_map = HashMap::new();
-    if tags.is_some() {
-        for tag in tags.unwrap() {
-            tags_map.insert(tag.key.unwrap(), tag.value.unwrap());
-        }
-    }
+/// Compares resource tags against required tags, tags without a value are
+/// considered to have an empty value
+pub fn compare_tags(tags: Option<Vec<NTag>>, required_tags: &[RequiredTag]) -> bool {
+    let tags = tags.unwrap_or_default();
 
-    for rt in required_tags {
-        if tags_map.contains_key(&rt.name) {
-            if rt.regex.is_some() {
-                if !rt
-                    .regex
-                    .as_ref()
-                    .unwrap()
-                    .is_match(tags_map.get(&rt.name).unwrap())
-                {
-                    debug!(
-                        "Required tag pattern does not match: {:?}",
-                        tags_map.get(&rt.name)
-                    );
-                    return true;
-                }
+    for rt in required_tags.iter().filter(|rt| rt.applies(&tags)) {
+        match rt.find(&tags) {
+            Some((name, value)) if !rt.allows(value) => {
+                debug!("Required tag value is not allowed: {}={}", name, value);
+                return true;
+            }
+            Some(_) => {}
+            None => {
+                debug!("Required tag ({}) is missing", rt);
+                return true;
             }
-        } else {
-            debug!("Required tag ({}) is missing", rt.name);
-            return true;
         }
     }
 
@@ -100,6 +93,60 @@ pub fn is_ts_older_than(date: &str, older_than: &Duration) -> bool {
     }
 }
 
+/// Checks if the value matches a glob pattern, `*` matches any sequence of
+/// characters and `?` matches a single character
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    glob_regex(pattern)
+        .map(|r| r.is_match(value))
+        .unwrap_or(false)
+}
+
+/// Regex matching the same values as the glob pattern
+pub fn glob_regex(pattern: &str) -> Option<Regex> {
+    let pattern = format!(
+        "^{}$",
+        regex::escape(pattern)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".")
+    );
+
+    Regex::new(&pattern).ok()
+}
+
+/// Runs an external command given as the program followed by its arguments,
+/// writing `input` to its stdin. Commands not completing within the timeout
+/// are killed.
+pub async fn run_command(
+    command: &[String],
+    input: &[u8],
+    env: Vec<(&str, String)>,
+    timeout: Duration,
+) -> io::Result<Output> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Commands are free to not read their input
+        if let Err(err) = stdin.write_all(input).await {
+            debug!(err = ?err, "Failed writing to the command");
+        }
+    }
+
+    tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "command timed out"))?
+}
+
 pub fn print_type_of<T>(_: &T) -> String {
     format!("{}", std::any::type_name::<T>())
 }
@@ -0,0 +1,1141 @@
+//! Configuration validation
+//!
+//! Reports every problem of a configuration along with the path of the field
+//! and its position (line and column) in the document: unknown keys, invalid
+//! regular expressions and durations, unknown resource types, idle rules
+//! configured for resource types without CloudWatch metrics and variables
+//! that cannot be interpolated.
+use crate::config::{
+    DEFAULTS_KEY, INCLUDE_KEY, OVERLAYS_KEY, OVERRIDES_KEY, OVERRIDE_ACCOUNTS_KEY,
+    OVERRIDE_EXCLUDE_KEY, OVERRIDE_REGIONS_KEY,
+};
+use crate::{
+    aws::check_template,
+    client::Client,
+    config::{
+        ConfigFormat, EndpointsConfig, HooksConfig, PolicyConfig, RegionsConfig, ResourceConfig,
+        WhitelistEntry, MIN_AGE_KEY, WHITELIST_KEY,
+    },
+    interpolate::interpolate_document,
+    registry, Result,
+};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map};
+use std::{fmt, path::Path};
+use toml::{value::Table, Value};
+use yaml_rust::parser::{Event as YamlEvent, Parser as YamlParser};
+
+/// Expected shape of a configuration value
+enum Schema {
+    Table(&'static [(&'static str, Schema)]),
+    /// Table with arbitrary keys
+    Map(&'static Schema),
+    Array(&'static Schema),
+    /// Field that has to be set in its table
+    Required(&'static Schema),
+    Enum(&'static [&'static str]),
+    Whitelist,
+    /// CloudWatch dimension value template
+    Template,
+    Duration,
+    Regex,
+    String,
+    Bool,
+    Number,
+    Value,
+}
+
+const STRINGS: Schema = Schema::Array(&Schema::String);
+
+const WHITELIST: Schema = Schema::Array(&Schema::Whitelist);
+
+static REGIONS: Schema = Schema::Table(&[("include", STRINGS), ("exclude", STRINGS)]);
+
+static ENDPOINTS: Schema = Schema::Table(&[
+    ("url", Schema::String),
+    ("services", Schema::Map(&Schema::String)),
+]);
+
+static HOOKS: Schema = Schema::Table(&[
+    ("pre_scan", STRINGS),
+    ("post_scan", STRINGS),
+    ("pre_delete", STRINGS),
+    ("post_delete", STRINGS),
+    ("run_end", STRINGS),
+    ("timeout", Schema::Duration),
+]);
+
+static POLICY: Schema = Schema::Table(&[
+    ("url", Schema::String),
+    ("command", STRINGS),
+    ("timeout", Schema::Duration),
+    ("fallback", Schema::Bool),
+]);
+
+static REQUIRED_TAG: Schema = Schema::Table(&[
+    ("name", Schema::String),
+    ("one_of", STRINGS),
+    ("pattern", Schema::Regex),
+    ("allowed_values", STRINGS),
+    ("ignore_case", Schema::Bool),
+    ("when", Schema::Map(&Schema::String)),
+    ("default", Schema::String),
+    ("infer", Schema::Enum(&["creator", "parent"])),
+]);
+
+static METRIC_DIMENSION: Schema = Schema::Table(&[
+    ("name", Schema::Required(&Schema::String)),
+    ("value", Schema::Required(&Schema::String)),
+]);
+
+static METRIC_FILTER: Schema = Schema::Table(&[
+    ("name", Schema::Required(&Schema::String)),
+    (
+        "statistic",
+        Schema::Required(&Schema::Enum(&[
+            "SampleCount",
+            "Average",
+            "Sum",
+            "Minimum",
+            "Maximum",
+        ])),
+    ),
+    ("duration", Schema::Required(&Schema::Duration)),
+    ("period", Schema::Required(&Schema::Duration)),
+    (
+        "op",
+        Schema::Required(&Schema::Enum(&["lt", "gt", "le", "ge"])),
+    ),
+    ("dimensions", Schema::Array(&METRIC_DIMENSION)),
+    ("value", Schema::Required(&Schema::Number)),
+    (
+        "no_datapoints",
+        Schema::Enum(&["idle", "not_idle", "unknown"]),
+    ),
+]);
+
+static RESOURCE: Schema = Schema::Table(&[
+    (
+        "target_state",
+        Schema::Enum(&["Stopped", "Deleted", "Tagged"]),
+    ),
+    ("required_tags", Schema::Array(&REQUIRED_TAG)),
+    ("allowed_types", STRINGS),
+    ("whitelist", WHITELIST),
+    ("metric_filters", Schema::Array(&METRIC_FILTER)),
+    ("idle_match", Schema::Enum(&["all", "any"])),
+    (
+        "metrics",
+        Schema::Table(&[
+            ("namespace", Schema::Required(&Schema::String)),
+            ("dimensions", Schema::Map(&Schema::Template)),
+            ("sparse", Schema::Bool),
+        ]),
+    ),
+    (
+        "termination_protection",
+        Schema::Table(&[("ignore", Schema::Required(&Schema::Bool))]),
+    ),
+    (
+        "manage_stopped",
+        Schema::Table(&[("older_than", Schema::Required(&Schema::Duration))]),
+    ),
+    ("max_run_time", Schema::Duration),
+    ("min_age", Schema::Duration),
+    ("disable_additional_rules", Schema::Bool),
+    (
+        "naming_prefix",
+        Schema::Table(&[("pattern", Schema::Required(&Schema::Regex))]),
+    ),
+    ("retain_resources", STRINGS),
+    ("backup_before_delete", Schema::Bool),
+    ("max_idle_time", Schema::Duration),
+]);
+
+const METRIC_FILTERS_KEY: &str = "metric_filters";
+const TARGET_STATE_KEY: &str = "target_state";
+const METRICS_KEY: &str = "metrics";
+const REQUIRED_TAGS_KEY: &str = "required_tags";
+
+/// A problem of the configuration, positions start at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// File the problem was found in, when validating files
+    pub file: Option<String>,
+    /// Path of the field, for instance `ec2_instance.required_tags[0].pattern`
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file)?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, "{}:{}:", line, column)?;
+        }
+        if self.file.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validates the configuration file along with the files it includes, an
+/// error is returned only when a file cannot be read. The format of the file
+/// is selected by its extension when not provided.
+pub fn validate_config_file(
+    filename: &str,
+    format: Option<ConfigFormat>,
+) -> Result<Vec<Diagnostic>> {
+    let format =
+        format.unwrap_or_else(|| ConfigFormat::of(Path::new(filename), ConfigFormat::Toml));
+    let mut diagnostics = Vec::new();
+
+    for (file, format) in crate::config::config_files(filename, format)? {
+        let buffer = crate::config::read_config_file(&file)?;
+
+        diagnostics.extend(
+            validate_document(&buffer, format)
+                .into_iter()
+                .map(|d| Diagnostic {
+                    file: Some(file.clone()),
+                    ..d
+                }),
+        );
+    }
+
+    Ok(diagnostics)
+}
+
+/// Validates a TOML configuration document, returning all the problems found
+pub fn validate_config(buffer: &str) -> Vec<Diagnostic> {
+    validate_document(buffer, ConfigFormat::Toml)
+}
+
+/// Validates a configuration document in the given format, returning all the
+/// problems found along with their position in the document.
+pub fn validate_document(buffer: &str, format: ConfigFormat) -> Vec<Diagnostic> {
+    let document = match format {
+        ConfigFormat::Toml => toml::from_str::<Value>(buffer).map_err(|e| {
+            let position = e.line_col().map(|(line, col)| (line + 1, col + 1));
+            (e.to_string(), position)
+        }),
+        ConfigFormat::Yaml => serde_yaml::from_str::<Value>(buffer).map_err(|e| {
+            let position = e.location().map(|l| (l.line(), l.column()));
+            (e.to_string(), position)
+        }),
+        ConfigFormat::Json => serde_json::from_str::<Value>(buffer).map_err(|e| {
+            let position = Some((e.line(), e.column().max(1))).filter(|(line, _)| *line > 0);
+            (e.to_string(), position)
+        }),
+    };
+    let mut document = match document {
+        Ok(document) => document,
+        Err((message, position)) => {
+            return vec![Diagnostic {
+                file: None,
+                path: String::new(),
+                line: position.map(|(line, _)| line),
+                column: position.map(|(_, column)| column),
+                message,
+            }];
+        }
+    };
+    let mut problems = interpolate_document(&mut document);
+
+    if let Some(table) = document.as_table() {
+        check_document(table, &[], true, &mut problems);
+    }
+
+    let index = match format {
+        ConfigFormat::Toml => index_document(buffer),
+        ConfigFormat::Yaml => index_yaml(buffer),
+        ConfigFormat::Json => index_json(buffer),
+    };
+
+    problems
+        .into_iter()
+        .map(|(path, message)| {
+            let (line, column) = match locate(&index, &path) {
+                Some((line, column)) => (Some(line), Some(column)),
+                None => (None, None),
+            };
+
+            Diagnostic {
+                file: None,
+                path: format_path(&path),
+                line,
+                column,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// Checks the sections of a document, overlays are documents of their own
+/// without includes and overlays.
+fn check_document(
+    table: &Table,
+    prefix: &[String],
+    root: bool,
+    problems: &mut Vec<(Vec<String>, String)>,
+) {
+    for (key, value) in table {
+        let path = child(prefix, key);
+
+        match key.as_str() {
+            "regions" => check::<RegionsConfig>(&REGIONS, value, path, problems),
+            "endpoints" => check::<EndpointsConfig>(&ENDPOINTS, value, path, problems),
+            "hooks" => check::<HooksConfig>(&HOOKS, value, path, problems),
+            "policy" => check::<PolicyConfig>(&POLICY, value, path, problems),
+            WHITELIST_KEY => check::<Vec<String>>(&WHITELIST, value, path, problems),
+            MIN_AGE_KEY => check_schema(&Schema::Duration, value, &path, problems),
+            DEFAULTS_KEY => {
+                check_required_tags(value, &path, problems);
+                check::<ResourceConfig>(&RESOURCE, value, path, problems)
+            }
+            INCLUDE_KEY if root => check::<Vec<String>>(&Schema::Value, value, path, problems),
+            OVERLAYS_KEY if root => match value.as_table() {
+                Some(overlays) => {
+                    for (name, overlay) in overlays {
+                        match overlay.as_table() {
+                            Some(overlay) => {
+                                check_document(overlay, &child(&path, name), false, problems)
+                            }
+                            None => problems.push((
+                                child(&path, name),
+                                format!("expected a table, found {}", overlay.type_str()),
+                            )),
+                        }
+                    }
+                }
+                None => problems.push((
+                    path,
+                    format!("expected a table, found {}", value.type_str()),
+                )),
+            },
+            OVERRIDES_KEY => match value.as_array() {
+                Some(overrides) => {
+                    for (i, ov) in overrides.iter().enumerate() {
+                        check_override(ov, child(&path, &i.to_string()), problems);
+                    }
+                }
+                None => problems.push((
+                    path,
+                    format!("expected an array, found {}", value.type_str()),
+                )),
+            },
+            name => check_resource(name, value, path, problems),
+        }
+    }
+}
+
+/// Overrides are matched by their regions and accounts, the remaining keys
+/// are resource sections
+fn check_override(value: &Value, path: Vec<String>, problems: &mut Vec<(Vec<String>, String)>) {
+    let table = match value.as_table() {
+        Some(table) => table,
+        None => {
+            problems.push((
+                path,
+                format!("expected a table, found {}", value.type_str()),
+            ));
+            return;
+        }
+    };
+
+    for (key, value) in table {
+        let path = child(&path, key);
+
+        match key.as_str() {
+            OVERRIDE_REGIONS_KEY | OVERRIDE_ACCOUNTS_KEY => {
+                check::<Vec<String>>(&Schema::Value, value, path, problems)
+            }
+            OVERRIDE_EXCLUDE_KEY => match value.as_array() {
+                Some(names) => {
+                    for (idx, name) in names.iter().enumerate() {
+                        match name.as_str() {
+                            Some(name) if registry::lookup(name).is_some() => {}
+                            Some(name) => problems.push((
+                                child(&path, &idx.to_string()),
+                                format!("unknown resource type: {}", name),
+                            )),
+                            None => problems.push((
+                                child(&path, &idx.to_string()),
+                                format!("expected a string, found {}", name.type_str()),
+                            )),
+                        }
+                    }
+                }
+                None => problems.push((
+                    path,
+                    format!("expected an array, found {}", value.type_str()),
+                )),
+            },
+            name => check_resource(name, value, path, problems),
+        }
+    }
+}
+
+fn check_resource(
+    name: &str,
+    value: &Value,
+    path: Vec<String>,
+    problems: &mut Vec<(Vec<String>, String)>,
+) {
+    match registry::lookup(name) {
+        Some(client) => {
+            let resource_type = registry::get(&client);
+            // Metrics declared in the configuration take precedence over the
+            // ones registered for the resource type
+            let cw_supported = value.get(METRICS_KEY).is_some()
+                || resource_type
+                    .as_ref()
+                    .map(|rt| rt.metrics.is_some())
+                    .unwrap_or(false);
+            let taggable = resource_type.map(|rt| rt.taggable).unwrap_or(false);
+
+            if !taggable && value.get(TARGET_STATE_KEY).and_then(Value::as_str) == Some("Tagged") {
+                problems.push((
+                    child(&path, TARGET_STATE_KEY),
+                    format!("tag remediation is not supported for {}", client.name()),
+                ));
+            }
+
+            if !cw_supported && value.get(METRIC_FILTERS_KEY).is_some() {
+                problems.push((
+                    child(&path, METRIC_FILTERS_KEY),
+                    format!(
+                        "idle rules are not supported for {}, no CloudWatch metrics are \
+                        registered for the resource type",
+                        client.name()
+                    ),
+                ));
+            }
+
+            check_required_tags(value, &path, problems);
+            check::<ResourceConfig>(&RESOURCE, value, path, problems);
+        }
+        None => problems.push((path, format!("unknown resource type: {}", name))),
+    }
+}
+
+/// Required tags are identified by their name or their alternative names
+fn check_required_tags(value: &Value, path: &[String], problems: &mut Vec<(Vec<String>, String)>) {
+    let tags = value.get(REQUIRED_TAGS_KEY).and_then(Value::as_array);
+
+    for (i, tag) in tags.into_iter().flatten().enumerate() {
+        if tag.get("name").is_none() && tag.get("one_of").is_none() {
+            problems.push((
+                child(&child(path, REQUIRED_TAGS_KEY), &i.to_string()),
+                "missing field: name (or one_of)".to_string(),
+            ));
+        }
+    }
+}
+
+/// Checks the section against its schema, the section is deserialized only
+/// when the schema checks pass to report type errors.
+fn check<T: DeserializeOwned>(
+    schema: &Schema,
+    value: &Value,
+    path: Vec<String>,
+    problems: &mut Vec<(Vec<String>, String)>,
+) {
+    let found = problems.len();
+    check_schema(schema, value, &path, problems);
+
+    if problems.len() == found {
+        if let Err(err) = value.clone().try_into::<T>() {
+            problems.push((path, err.to_string()));
+        }
+    }
+}
+
+fn check_schema(
+    schema: &Schema,
+    value: &Value,
+    path: &[String],
+    problems: &mut Vec<(Vec<String>, String)>,
+) {
+    match (schema, value) {
+        (Schema::Table(fields), Value::Table(table)) => {
+            for (name, _) in fields.iter().filter(|(name, schema)| {
+                matches!(schema, Schema::Required(_)) && !table.contains_key(*name)
+            }) {
+                problems.push((path.to_vec(), format!("missing field: {}", name)));
+            }
+
+            for (key, value) in table {
+                let path = child(path, key);
+
+                match fields.iter().find(|(name, _)| name == key) {
+                    Some((_, schema)) => check_schema(schema, value, &path, problems),
+                    None => problems.push((path, format!("unknown key: {}", key))),
+                }
+            }
+        }
+        (Schema::Map(schema), Value::Table(table)) => {
+            for (key, value) in table {
+                check_schema(schema, value, &child(path, key), problems);
+            }
+        }
+        (Schema::Array(schema), Value::Array(values)) => {
+            for (i, value) in values.iter().enumerate() {
+                check_schema(schema, value, &child(path, &i.to_string()), problems);
+            }
+        }
+        (Schema::Duration, Value::String(s)) => {
+            if let Err(err) = humantime::parse_duration(s) {
+                problems.push((path.to_vec(), format!("invalid duration '{}': {}", s, err)));
+            }
+        }
+        (Schema::Regex, Value::String(s)) => {
+            if let Err(err) = Regex::new(s) {
+                // Syntax errors span multiple lines pointing at the error
+                let err = err.to_string();
+                let reason = err.lines().last().unwrap_or_default();
+                problems.push((
+                    path.to_vec(),
+                    format!(
+                        "invalid regex '{}': {}",
+                        s,
+                        reason.trim_start_matches("error: ")
+                    ),
+                ));
+            }
+        }
+        (Schema::Required(schema), _) => check_schema(schema, value, path, problems),
+        (Schema::Enum(variants), Value::String(s)) => {
+            if !variants.contains(&s.as_str()) {
+                problems.push((
+                    path.to_vec(),
+                    format!(
+                        "unknown value '{}', expected one of: {}",
+                        s,
+                        variants.join(", ")
+                    ),
+                ));
+            }
+        }
+        (Schema::Whitelist, Value::String(s)) => {
+            if let Err(err) = WhitelistEntry::parse(s) {
+                problems.push((path.to_vec(), err));
+            }
+        }
+        (Schema::Template, Value::String(s)) => {
+            if let Err(err) = check_template(s) {
+                problems.push((path.to_vec(), err));
+            }
+        }
+        (Schema::String, Value::String(_))
+        | (Schema::Bool, Value::Boolean(_))
+        | (Schema::Number, Value::Integer(_))
+        | (Schema::Number, Value::Float(_))
+        | (Schema::Value, _) => {}
+        (Schema::Table(_), _) | (Schema::Map(_), _) => problems.push((
+            path.to_vec(),
+            format!("expected a table, found {}", value.type_str()),
+        )),
+        (Schema::Array(_), _) => problems.push((
+            path.to_vec(),
+            format!("expected an array, found {}", value.type_str()),
+        )),
+        (Schema::Bool, _) => problems.push((
+            path.to_vec(),
+            format!("expected a boolean, found {}", value.type_str()),
+        )),
+        (Schema::Number, _) => problems.push((
+            path.to_vec(),
+            format!("expected a number, found {}", value.type_str()),
+        )),
+        (Schema::Enum(_), _)
+        | (Schema::Whitelist, _)
+        | (Schema::Template, _)
+        | (Schema::String, _)
+        | (Schema::Duration, _)
+        | (Schema::Regex, _) => problems.push((
+            path.to_vec(),
+            format!("expected a string, found {}", value.type_str()),
+        )),
+    }
+}
+
+/// JSON Schema of the configuration document, for editors to validate and
+/// complete configuration files in every format
+pub fn config_schema() -> serde_json::Value {
+    let mut schema = document_schema(true);
+    schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+    schema["title"] = json!("nuker configuration");
+    schema["definitions"] = json!({
+        "resource": json_schema(&RESOURCE),
+        "overlay": document_schema(false),
+    });
+
+    schema
+}
+
+/// Schema of the document, overlays are documents without includes and
+/// overlays of their own
+fn document_schema(root: bool) -> serde_json::Value {
+    let resource = json!({ "$ref": "#/definitions/resource" });
+    let mut sections = Map::new();
+
+    for client in Client::iter() {
+        let aliases = registry::get(&client)
+            .map(|rt| rt.aliases)
+            .unwrap_or_default();
+
+        for name in std::iter::once(&client.name()).chain(aliases) {
+            sections.insert(name.to_string(), resource.clone());
+        }
+    }
+
+    let mut override_properties = sections.clone();
+    override_properties.insert(OVERRIDE_REGIONS_KEY.to_string(), json_schema(&STRINGS));
+    override_properties.insert(OVERRIDE_ACCOUNTS_KEY.to_string(), json_schema(&STRINGS));
+    override_properties.insert(
+        OVERRIDE_EXCLUDE_KEY.to_string(),
+        json!({
+            "type": "array",
+            "items": { "type": "string", "enum": sections.keys().collect::<Vec<_>>() },
+        }),
+    );
+
+    let mut properties = sections;
+    properties.insert("regions".to_string(), json_schema(&REGIONS));
+    properties.insert("endpoints".to_string(), json_schema(&ENDPOINTS));
+    properties.insert("hooks".to_string(), json_schema(&HOOKS));
+    properties.insert("policy".to_string(), json_schema(&POLICY));
+    properties.insert(WHITELIST_KEY.to_string(), json_schema(&WHITELIST));
+    properties.insert(MIN_AGE_KEY.to_string(), json_schema(&Schema::Duration));
+    properties.insert(DEFAULTS_KEY.to_string(), resource);
+    properties.insert(
+        OVERRIDES_KEY.to_string(),
+        json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": override_properties,
+                "additionalProperties": false,
+            },
+        }),
+    );
+    if root {
+        properties.insert(INCLUDE_KEY.to_string(), json_schema(&STRINGS));
+        properties.insert(
+            OVERLAYS_KEY.to_string(),
+            json!({
+                "type": "object",
+                "additionalProperties": { "$ref": "#/definitions/overlay" },
+            }),
+        );
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+fn json_schema(schema: &Schema) -> serde_json::Value {
+    match schema {
+        Schema::Table(fields) => {
+            let properties: Map<String, serde_json::Value> = fields
+                .iter()
+                .map(|(name, schema)| (name.to_string(), json_schema(schema)))
+                .collect();
+            let required: Vec<&str> = fields
+                .iter()
+                .filter(|(_, schema)| matches!(schema, Schema::Required(_)))
+                .map(|(name, _)| *name)
+                .collect();
+
+            let mut table = json!({
+                "type": "object",
+                "properties": properties,
+                "additionalProperties": false,
+            });
+            if !required.is_empty() {
+                table["required"] = json!(required);
+            }
+
+            table
+        }
+        Schema::Map(schema) => json!({
+            "type": "object",
+            "additionalProperties": json_schema(schema),
+        }),
+        Schema::Array(schema) => json!({ "type": "array", "items": json_schema(schema) }),
+        Schema::Required(schema) => json_schema(schema),
+        Schema::Enum(variants) => json!({ "type": "string", "enum": variants }),
+        Schema::Duration => json!({
+            "type": "string",
+            "description": "Duration, for instance \"7 days\" or \"12h\"",
+        }),
+        Schema::Regex => json!({ "type": "string", "format": "regex" }),
+        Schema::Whitelist => json!({
+            "type": "string",
+            "description": "Id or ARN (glob patterns are accepted), /regex/ matched against \
+                the id and the Name tag, or a key=value tag selector",
+        }),
+        Schema::Template => json!({
+            "type": "string",
+            "description": "Dimension value with {id}, {arn}, {arn_suffix}, {region}, {account} \
+                and {tag:<key>} placeholders",
+        }),
+        Schema::String => json!({ "type": "string" }),
+        Schema::Bool => json!({ "type": "boolean" }),
+        Schema::Number => json!({ "type": "number" }),
+        Schema::Value => json!({}),
+    }
+}
+
+fn child(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+/// Array indices are formatted as `[i]`
+pub(crate) fn format_path(path: &[String]) -> String {
+    let mut formatted = String::new();
+
+    for segment in path {
+        if segment.parse::<usize>().is_ok() {
+            formatted.push_str(&format!("[{}]", segment));
+        } else {
+            if !formatted.is_empty() {
+                formatted.push('.');
+            }
+            formatted.push_str(segment);
+        }
+    }
+
+    formatted
+}
+
+/// Position of the table headers and keys of the document, keyed by their
+/// path. Tables in an array of tables are identified by their index.
+fn index_document(buffer: &str) -> Vec<(Vec<String>, usize, usize)> {
+    let mut index: Vec<(Vec<String>, usize, usize)> = Vec::new();
+    // Number of tables of each array of tables seen so far
+    let mut arrays: Vec<(Vec<String>, usize)> = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    // Depth of the brackets of a value spanning multiple lines
+    let mut depth: i32 = 0;
+
+    for (n, line) in buffer.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let column = line.len() - trimmed.len() + 1;
+        let content = strip_comment(trimmed).trim_end();
+
+        if depth > 0 {
+            depth += bracket_depth(content);
+            continue;
+        }
+        if content.is_empty() {
+            continue;
+        }
+
+        if content.starts_with('[') {
+            let is_array = content.starts_with("[[");
+            let header = content.trim_start_matches('[').trim_end_matches(']');
+            let keys = split_key(header);
+            let mut path: Vec<String> = Vec::new();
+
+            for (i, key) in keys.iter().enumerate() {
+                path.push(key.clone());
+                let last = i == keys.len() - 1;
+
+                if !index.iter().any(|(p, _, _)| *p == path) {
+                    index.push((path.clone(), n + 1, column));
+                }
+
+                match arrays.iter_mut().find(|(p, _)| *p == path) {
+                    Some((_, count)) if last && is_array => {
+                        *count += 1;
+                        path.push((*count - 1).to_string());
+                    }
+                    Some((_, count)) => path.push((*count - 1).to_string()),
+                    None if last && is_array => {
+                        arrays.push((path.clone(), 1));
+                        path.push("0".to_string());
+                    }
+                    None => {}
+                }
+
+                if !index.iter().any(|(p, _, _)| *p == path) {
+                    index.push((path.clone(), n + 1, column));
+                }
+            }
+
+            table = path;
+        } else if let Some(eq) = content.find('=') {
+            let mut path = table.clone();
+            path.extend(split_key(&content[..eq]));
+            index.push((path, n + 1, column));
+            depth = bracket_depth(&content[eq + 1..]);
+        }
+    }
+
+    index
+}
+
+/// Containers of a YAML or JSON document being indexed
+enum Container {
+    /// Key of the value being indexed, unset while expecting a key
+    Mapping(Option<String>),
+    /// Index of the next element
+    Sequence(usize),
+}
+
+impl Container {
+    /// Path of the value starting in the container at `path`, keys of a
+    /// mapping are returned as `None` and recorded as the pending key
+    fn value_path(&mut self, path: &[String], key: Option<&str>) -> Option<Vec<String>> {
+        match self {
+            Container::Mapping(pending) => match pending.take() {
+                Some(pending) => Some(child(path, &pending)),
+                None => {
+                    *pending = Some(key.unwrap_or_default().to_string());
+                    None
+                }
+            },
+            Container::Sequence(next) => {
+                *next += 1;
+                Some(child(path, &(*next - 1).to_string()))
+            }
+        }
+    }
+}
+
+/// Position of the keys and sequence elements of a YAML document, keyed by
+/// their path
+fn index_yaml(buffer: &str) -> Vec<(Vec<String>, usize, usize)> {
+    let mut index: Vec<(Vec<String>, usize, usize)> = Vec::new();
+    let mut containers: Vec<(Vec<String>, Container)> = Vec::new();
+    let mut parser = YamlParser::new(buffer.chars());
+
+    while let Ok((event, marker)) = parser.next() {
+        let (line, column) = (marker.line(), marker.col() + 1);
+        let (container, key) = match event {
+            YamlEvent::StreamEnd => break,
+            YamlEvent::MappingEnd | YamlEvent::SequenceEnd => {
+                containers.pop();
+                continue;
+            }
+            YamlEvent::MappingStart(_) => (Some(Container::Mapping(None)), None),
+            YamlEvent::SequenceStart(_) => (Some(Container::Sequence(0)), None),
+            YamlEvent::Scalar(ref value, ..) => (None, Some(value.as_str())),
+            YamlEvent::Alias(_) => (None, None),
+            _ => continue,
+        };
+
+        let path = match containers.last_mut() {
+            Some((path, parent)) => match parent.value_path(path, key) {
+                Some(path) => {
+                    if let Container::Sequence(_) = parent {
+                        index.push((path.clone(), line, column));
+                    }
+                    path
+                }
+                None => {
+                    let key = child(path, key.unwrap_or_default());
+                    index.push((key.clone(), line, column));
+                    key
+                }
+            },
+            None => Vec::new(),
+        };
+
+        if let Some(container) = container {
+            containers.push((path, container));
+        }
+    }
+
+    index
+}
+
+/// Position of the keys and array elements of a JSON document, keyed by
+/// their path
+fn index_json(buffer: &str) -> Vec<(Vec<String>, usize, usize)> {
+    let mut index: Vec<(Vec<String>, usize, usize)> = Vec::new();
+    let mut containers: Vec<(Vec<String>, Container)> = Vec::new();
+    let mut chars = buffer.chars().peekable();
+    let (mut line, mut column) = (1, 0);
+    // Whether a number or a literal is being read
+    let mut literal = false;
+
+    while let Some(c) = chars.next() {
+        column += 1;
+        let (start_line, start_column) = (line, column);
+
+        let (container, key) = match c {
+            '\n' => {
+                line += 1;
+                column = 0;
+                literal = false;
+                continue;
+            }
+            '}' | ']' => {
+                containers.pop();
+                literal = false;
+                continue;
+            }
+            ',' => {
+                if let Some((_, Container::Mapping(pending))) = containers.last_mut() {
+                    *pending = None;
+                }
+                literal = false;
+                continue;
+            }
+            ':' => {
+                literal = false;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                literal = false;
+                continue;
+            }
+            '{' => (Some(Container::Mapping(None)), None),
+            '[' => (Some(Container::Sequence(0)), None),
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    column += 1;
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            column += 1;
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        c => value.push(c),
+                    }
+                }
+                (None, Some(value))
+            }
+            _ if literal => continue,
+            _ => {
+                literal = true;
+                (None, None)
+            }
+        };
+
+        let path = match containers.last_mut() {
+            Some((path, parent)) => match parent.value_path(path, key.as_deref()) {
+                Some(path) => {
+                    if let Container::Sequence(_) = parent {
+                        index.push((path.clone(), start_line, start_column));
+                    }
+                    path
+                }
+                None => {
+                    let key = child(path, key.as_deref().unwrap_or_default());
+                    index.push((key.clone(), start_line, start_column));
+                    key
+                }
+            },
+            None => Vec::new(),
+        };
+
+        if let Some(container) = container {
+            containers.push((path, container));
+        }
+    }
+
+    index
+}
+
+/// Position of the path, falling back to the closest parent found
+fn locate(index: &[(Vec<String>, usize, usize)], path: &[String]) -> Option<(usize, usize)> {
+    (1..=path.len()).rev().find_map(|len| {
+        index
+            .iter()
+            .find(|(p, _, _)| p.as_slice() == &path[..len])
+            .map(|(_, line, column)| (*line, *column))
+    })
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|k| k.trim().trim_matches('"').trim_matches('\'').to_string())
+        .collect()
+}
+
+/// Removes a trailing comment, ignoring `#` within strings
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn bracket_depth(value: &str) -> i32 {
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+
+    for c in value.chars() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '[') | (None, '{') => depth += 1,
+            (None, ']') | (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_configs_are_valid() {
+        let sample = include_str!("../examples/configs/sample.toml");
+        let localstack = include_str!("../examples/configs/localstack.toml");
+
+        assert_eq!(validate_config(sample), Vec::new());
+        assert_eq!(validate_config(localstack), Vec::new());
+    }
+
+    #[test]
+    fn report_all_problems() {
+        let config = r#"
+[regions]
+include = ["us-east-1"]
+
+[ec2_instance]
+max_run_time = "2 fortnights"
+idle_time = "1 day"
+
+    [[ec2_instance.required_tags]]
+    name = "Owner"
+
+    [[ec2_instance.required_tags]]
+    name = "Name"
+    pattern = "^ec2-("
+
+[s3_bucket]
+    [[s3_bucket.metric_filters]]
+    name = "BucketSizeBytes"
+    statistic = "Maximum"
+    duration = "1 day"
+    period = "1 day"
+    op = "lt"
+    value = 1
+
+[ec2_instances]
+max_run_time = "1 day"
+
+"#;
+
+        let diagnostics: Vec<String> = validate_config(config)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+
+        assert_eq!(diagnostics.len(), 5, "{:#?}", diagnostics);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.starts_with("6:1: ec2_instance.max_run_time: invalid duration")));
+        assert!(diagnostics
+            .contains(&"7:1: ec2_instance.idle_time: unknown key: idle_time".to_string()));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.starts_with("14:5: ec2_instance.required_tags[1].pattern: invalid regex")));
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d
+                    .starts_with("17:5: s3_bucket.metric_filters: idle rules are not supported"))
+        );
+        assert!(diagnostics
+            .contains(&"25:1: ec2_instances: unknown resource type: ec2_instances".to_string()));
+    }
+
+    #[test]
+    fn report_syntax_errors() {
+        let diagnostics = validate_config("[ec2_instance]\nmax_run_time = \n");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(2));
+    }
+
+    #[test]
+    fn report_problems_in_yaml_and_json() {
+        let yaml = "ec2_instance:\n  target_state: Terminated\n  allowed_types: t3.nano\n";
+        let json = r#"{
+  "s3_bucket": {
+    "metric_filters": [
+      {"name": "Bytes"}
+    ]
+  }
+}"#;
+
+        let messages = |diagnostics: Vec<Diagnostic>| -> Vec<String> {
+            diagnostics.iter().map(|d| d.to_string()).collect()
+        };
+
+        assert_eq!(
+            messages(validate_document(yaml, ConfigFormat::Yaml)),
+            vec![
+                "3:3: ec2_instance.allowed_types: expected an array, found string",
+                "2:3: ec2_instance.target_state: unknown value 'Terminated', expected one of: \
+                Stopped, Deleted, Tagged",
+            ]
+        );
+        assert_eq!(
+            messages(validate_document(json, ConfigFormat::Json)),
+            vec![
+                "3:5: s3_bucket.metric_filters: idle rules are not supported for s3_bucket, no \
+                CloudWatch metrics are registered for the resource type",
+                "4:7: s3_bucket.metric_filters[0]: missing field: statistic",
+                "4:7: s3_bucket.metric_filters[0]: missing field: duration",
+                "4:7: s3_bucket.metric_filters[0]: missing field: period",
+                "4:7: s3_bucket.metric_filters[0]: missing field: op",
+                "4:7: s3_bucket.metric_filters[0]: missing field: value",
+            ]
+        );
+        assert_eq!(
+            messages(validate_document("{\"ec2_instance\": ", ConfigFormat::Json)),
+            vec!["1:17: EOF while parsing a value at line 1 column 17"]
+        );
+    }
+
+    #[test]
+    fn reject_tagging_unsupported_types() {
+        let diagnostics: Vec<String> = validate_config(
+            "[ec2_instance]\ntarget_state = \"Tagged\"\n\n[s3_bucket]\ntarget_state = \"Tagged\"\n",
+        )
+        .iter()
+        .map(|d| d.to_string())
+        .collect();
+
+        assert_eq!(
+            diagnostics,
+            vec!["5:1: s3_bucket.target_state: tag remediation is not supported for s3_bucket"]
+        );
+    }
+}